@@ -0,0 +1,532 @@
+//! Container storage
+//! Lists what Docker (or Podman) can reclaim — dangling images, unused volumes,
+//! stopped containers and build cache — and prunes selected items through the CLI
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{format_size, get_dir_size};
+use crate::utils::command::{run_command_outcome, CommandOutcome};
+
+const CLI_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Removal order: containers hold images and volumes, so they go first
+const KIND_ORDER: &[&str] = &["container", "image", "volume", "build_cache"];
+
+/// The container engine CLI; tests substitute a stub
+pub trait ContainerCli: Sync {
+    /// "docker" or "podman"
+    fn engine(&self) -> &str;
+    fn run(&self, args: &[&str]) -> CommandOutcome;
+}
+
+/// The `docker` or `podman` binary on PATH
+pub struct EngineCli {
+    engine: &'static str,
+}
+
+impl EngineCli {
+    /// Docker when installed, otherwise Podman
+    pub fn detect() -> Option<Self> {
+        ["docker", "podman"]
+            .into_iter()
+            .find(|engine| which::which(engine).is_ok())
+            .map(|engine| Self { engine })
+    }
+}
+
+impl ContainerCli for EngineCli {
+    fn engine(&self) -> &str {
+        self.engine
+    }
+
+    fn run(&self, args: &[&str]) -> CommandOutcome {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        run_command_outcome(self.engine, &args, CLI_TIMEOUT)
+    }
+}
+
+/// One reclaimable object
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerItem {
+    pub kind: String, // "image", "volume", "container" or "build_cache"
+    pub id: String,
+    pub name: String,
+    pub size: u64,
+    pub size_display: String,
+    pub created: Option<String>,
+}
+
+/// Identifies an item selected for pruning
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerItemRef {
+    pub kind: String,
+    pub id: String,
+}
+
+/// Everything the engine could free
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerStorageReport {
+    pub engine: Option<String>,
+    pub error: Option<String>, // daemon not running, permission denied, ...
+    pub items: Vec<ContainerItem>,
+    pub total_size: u64,
+    pub total_size_display: String,
+}
+
+/// What a prune will remove, checked against a fresh listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerPrunePlan {
+    pub items: Vec<ContainerItem>,
+    pub skipped: Vec<ContainerItemRef>, // in use again or already gone
+    pub total_size: u64,
+    pub total_size_display: String,
+}
+
+/// Scan the installed engine
+pub fn scan_container_storage() -> ContainerStorageReport {
+    match EngineCli::detect() {
+        Some(cli) => scan_with(&cli),
+        None => report(None, None, Vec::new()),
+    }
+}
+
+/// Plan pruning `selected` with the installed engine
+pub fn plan_container_prune(selected: &[ContainerItemRef]) -> ContainerPrunePlan {
+    match EngineCli::detect() {
+        Some(cli) => plan_with(&cli, selected),
+        None => plan_from(&[], selected),
+    }
+}
+
+/// Prune `selected` with the installed engine, one result per item
+pub fn prune_container_items(selected: &[ContainerItemRef]) -> Vec<Result<String, String>> {
+    match EngineCli::detect() {
+        Some(cli) => prune_with(&cli, selected),
+        None => selected
+            .iter()
+            .map(|_| Err("Neither docker nor podman is installed".to_string()))
+            .collect(),
+    }
+}
+
+pub fn scan_with(cli: &dyn ContainerCli) -> ContainerStorageReport {
+    let engine = Some(cli.engine().to_string());
+    let listed = if cli.engine() == "podman" {
+        podman_items(cli)
+    } else {
+        docker_items(cli)
+    };
+    match listed {
+        Ok(items) => report(engine, None, items),
+        Err(error) => report(engine, Some(error), Vec::new()),
+    }
+}
+
+pub fn plan_with(cli: &dyn ContainerCli, selected: &[ContainerItemRef]) -> ContainerPrunePlan {
+    plan_from(&scan_with(cli).items, selected)
+}
+
+pub fn prune_with(
+    cli: &dyn ContainerCli,
+    selected: &[ContainerItemRef],
+) -> Vec<Result<String, String>> {
+    let plan = plan_with(cli, selected);
+    let mut results: Vec<Option<Result<String, String>>> = vec![None; selected.len()];
+
+    let mut order: Vec<usize> = (0..selected.len()).collect();
+    order.sort_by_key(|&index| {
+        KIND_ORDER
+            .iter()
+            .position(|kind| *kind == selected[index].kind)
+            .unwrap_or(KIND_ORDER.len())
+    });
+
+    for index in order {
+        let selection = &selected[index];
+        let Some(item) = plan
+            .items
+            .iter()
+            .find(|item| item.kind == selection.kind && item.id == selection.id)
+        else {
+            results[index] = Some(Err(format!(
+                "{} {} is in use or no longer exists",
+                selection.kind, selection.id
+            )));
+            continue;
+        };
+
+        let filter = format!("id={}", item.id);
+        let args: Vec<&str> = match item.kind.as_str() {
+            "container" => vec!["container", "rm", &item.id],
+            "image" => vec!["image", "rm", &item.id],
+            "volume" => vec!["volume", "rm", &item.id],
+            _ => vec!["builder", "prune", "-f", "--filter", &filter],
+        };
+        let outcome = cli.run(&args);
+        results[index] = Some(if outcome.success() {
+            Ok(format!(
+                "Removed {} {} (freed {})",
+                item.kind, item.name, item.size_display
+            ))
+        } else {
+            Err(outcome.summary())
+        });
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+fn plan_from(items: &[ContainerItem], selected: &[ContainerItemRef]) -> ContainerPrunePlan {
+    let mut planned = Vec::new();
+    let mut skipped = Vec::new();
+    for selection in selected {
+        match items
+            .iter()
+            .find(|item| item.kind == selection.kind && item.id == selection.id)
+        {
+            Some(item) => planned.push(item.clone()),
+            None => skipped.push(selection.clone()),
+        }
+    }
+    let total_size = planned.iter().map(|item| item.size).sum();
+    ContainerPrunePlan {
+        items: planned,
+        skipped,
+        total_size,
+        total_size_display: format_size(total_size),
+    }
+}
+
+fn report(
+    engine: Option<String>,
+    error: Option<String>,
+    mut items: Vec<ContainerItem>,
+) -> ContainerStorageReport {
+    items.sort_by(|a, b| b.size.cmp(&a.size));
+    let total_size = items.iter().map(|item| item.size).sum();
+    ContainerStorageReport {
+        engine,
+        error,
+        items,
+        total_size,
+        total_size_display: format_size(total_size),
+    }
+}
+
+fn item(kind: &str, id: &str, name: &str, size: u64, created: Option<&str>) -> ContainerItem {
+    ContainerItem {
+        kind: kind.to_string(),
+        id: id.to_string(),
+        name: name.to_string(),
+        size,
+        size_display: format_size(size),
+        created: created.map(str::to_string),
+    }
+}
+
+fn run_json(cli: &dyn ContainerCli, args: &[&str]) -> Result<Value, String> {
+    let outcome = cli.run(args);
+    if !outcome.success() {
+        return Err(outcome.summary());
+    }
+    serde_json::from_str(outcome.stdout.trim())
+        .map_err(|e| format!("Unexpected `{}` output: {}", outcome.command, e))
+}
+
+/// Docker's `system df -v --format json` prints one object with an array per kind
+fn docker_items(cli: &dyn ContainerCli) -> Result<Vec<ContainerItem>, String> {
+    let df = run_json(cli, &["system", "df", "-v", "--format", "json"])?;
+    Ok(parse_docker_df(&df))
+}
+
+fn parse_docker_df(df: &Value) -> Vec<ContainerItem> {
+    let list = |key: &str| {
+        df.get(key)
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default()
+    };
+    let mut items = Vec::new();
+
+    for image in list("Images") {
+        let dangling = field(&image, "Repository") == "<none>" && field(&image, "Tag") == "<none>";
+        if dangling && field(&image, "Containers") == "0" {
+            // UniqueSize is what removing the image frees; shared layers stay
+            let size = [field(&image, "UniqueSize"), field(&image, "Size")]
+                .into_iter()
+                .find_map(|size| parse_human_size(&size))
+                .unwrap_or(0);
+            let id = field(&image, "ID");
+            items.push(item("image", &id, &short_id(&id), size, created(&image)));
+        }
+    }
+
+    for volume in list("Volumes") {
+        if field(&volume, "Links") == "0" {
+            let name = field(&volume, "Name");
+            let size = parse_human_size(&field(&volume, "Size")).unwrap_or(0);
+            items.push(item("volume", &name, &name, size, None));
+        }
+    }
+
+    for container in list("Containers") {
+        if matches!(
+            field(&container, "State").as_str(),
+            "exited" | "created" | "dead"
+        ) {
+            // "2.1kB (virtual 75MB)": only the writable layer is freed
+            let size = parse_human_size(&field(&container, "Size")).unwrap_or(0);
+            let name = format!(
+                "{} ({})",
+                field(&container, "Names"),
+                field(&container, "Image")
+            );
+            items.push(item(
+                "container",
+                &field(&container, "ID"),
+                &name,
+                size,
+                created(&container),
+            ));
+        }
+    }
+
+    for record in list("BuildCache") {
+        if field(&record, "InUse") == "false" {
+            let size = parse_human_size(&field(&record, "Size")).unwrap_or(0);
+            let name = match field(&record, "Description") {
+                description if description.is_empty() => field(&record, "CacheType"),
+                description => description,
+            };
+            let created = record.get("LastUsedAt").and_then(Value::as_str);
+            items.push(item(
+                "build_cache",
+                &field(&record, "ID"),
+                &name,
+                size,
+                created,
+            ));
+        }
+    }
+
+    items
+}
+
+/// Podman rejects `--format` with `system df -v`, so each kind is listed on its own
+fn podman_items(cli: &dyn ContainerCli) -> Result<Vec<ContainerItem>, String> {
+    let images = run_json(
+        cli,
+        &["images", "--filter", "dangling=true", "--format", "json"],
+    )?;
+    let volumes = run_json(
+        cli,
+        &[
+            "volume",
+            "ls",
+            "--filter",
+            "dangling=true",
+            "--format",
+            "json",
+        ],
+    )?;
+    let containers = run_json(
+        cli,
+        &[
+            "ps",
+            "-a",
+            "--size",
+            "--filter",
+            "status=exited",
+            "--filter",
+            "status=created",
+            "--format",
+            "json",
+        ],
+    )?;
+    Ok(parse_podman(&images, &volumes, &containers))
+}
+
+fn parse_podman(images: &Value, volumes: &Value, containers: &Value) -> Vec<ContainerItem> {
+    let list = |value: &Value| value.as_array().cloned().unwrap_or_default();
+    let mut items = Vec::new();
+
+    for image in list(images) {
+        if image.get("Containers").and_then(Value::as_u64).unwrap_or(0) == 0 {
+            let id = field(&image, "Id");
+            let size = image.get("Size").and_then(Value::as_u64).unwrap_or(0);
+            items.push(item("image", &id, &short_id(&id), size, created(&image)));
+        }
+    }
+
+    for volume in list(volumes) {
+        let name = field(&volume, "Name");
+        // Podman does not report volume sizes; rootless mountpoints are readable
+        let size = volume
+            .get("Mountpoint")
+            .and_then(Value::as_str)
+            .map(|mountpoint| get_dir_size(&PathBuf::from(mountpoint)))
+            .unwrap_or(0);
+        items.push(item("volume", &name, &name, size, None));
+    }
+
+    for container in list(containers) {
+        let names = container
+            .get("Names")
+            .and_then(Value::as_array)
+            .and_then(|names| names.first())
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        let size = container
+            .get("Size")
+            .and_then(|size| size.get("rwSize"))
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        let name = format!("{} ({})", names, field(&container, "Image"));
+        items.push(item(
+            "container",
+            &field(&container, "Id"),
+            &name,
+            size,
+            created(&container),
+        ));
+    }
+
+    items
+}
+
+/// A field as a string; Docker prints counts as strings, Podman as numbers
+fn field(value: &Value, key: &str) -> String {
+    match value.get(key) {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Number(number)) => number.to_string(),
+        Some(Value::Bool(flag)) => flag.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn created(value: &Value) -> Option<&str> {
+    value.get("CreatedAt").and_then(Value::as_str)
+}
+
+fn short_id(id: &str) -> String {
+    let id = id.strip_prefix("sha256:").unwrap_or(id);
+    format!("<none> {}", &id[..id.len().min(12)])
+}
+
+/// Docker's decimal sizes ("0B", "512kB", "1.23GB"), ignoring a trailing "(virtual ...)"
+fn parse_human_size(text: &str) -> Option<u64> {
+    let text = text.split_whitespace().next()?;
+    let split = text.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.to_ascii_lowercase().as_str() {
+        "b" => 1e0,
+        "kb" => 1e3,
+        "mb" => 1e6,
+        "gb" => 1e9,
+        "tb" => 1e12,
+        _ => return None,
+    };
+    Some((number * multiplier) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct StubCli {
+        df: &'static str,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl ContainerCli for StubCli {
+        fn engine(&self) -> &str {
+            "docker"
+        }
+
+        fn run(&self, args: &[&str]) -> CommandOutcome {
+            let command = args.join(" ");
+            self.calls.lock().unwrap().push(command.clone());
+            CommandOutcome {
+                stdout: if command.starts_with("system df") {
+                    self.df.to_string()
+                } else {
+                    String::new()
+                },
+                command,
+                exit_code: Some(0),
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn lists_reclaimable_items_and_prunes_selection() {
+        let cli = StubCli {
+            df: r#"{
+                "Images": [
+                    {"ID": "sha256:0123456789abcdef", "Repository": "<none>", "Tag": "<none>", "Containers": "0", "Size": "1.5GB", "UniqueSize": "1.2GB"},
+                    {"ID": "sha256:fedcba", "Repository": "node", "Tag": "20", "Containers": "1", "Size": "1.1GB"}
+                ],
+                "Containers": [
+                    {"ID": "c1", "Names": "web", "Image": "node:20", "State": "running", "Size": "1kB"},
+                    {"ID": "c2", "Names": "old", "Image": "node:20", "State": "exited", "Size": "2.5MB (virtual 1.1GB)"}
+                ],
+                "Volumes": [
+                    {"Name": "pgdata", "Links": "0", "Size": "300MB"},
+                    {"Name": "cache", "Links": "1", "Size": "10MB"}
+                ],
+                "BuildCache": [
+                    {"ID": "b1", "CacheType": "regular", "Description": "RUN npm ci", "InUse": "false", "Size": "450MB"}
+                ]
+            }"#,
+            calls: Mutex::new(Vec::new()),
+        };
+
+        let report = scan_with(&cli);
+        let found: Vec<(&str, u64)> = report
+            .items
+            .iter()
+            .map(|item| (item.kind.as_str(), item.size))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("image", 1_200_000_000),
+                ("build_cache", 450_000_000),
+                ("volume", 300_000_000),
+                ("container", 2_500_000),
+            ]
+        );
+
+        let select = |kind: &str, id: &str| ContainerItemRef {
+            kind: kind.to_string(),
+            id: id.to_string(),
+        };
+        let selected = vec![
+            select("volume", "pgdata"),
+            select("container", "c2"),
+            select("volume", "cache"),
+        ];
+        let plan = plan_with(&cli, &selected);
+        assert_eq!(plan.total_size, 302_500_000);
+        assert_eq!(plan.skipped, vec![select("volume", "cache")]);
+
+        cli.calls.lock().unwrap().clear();
+        let results = prune_with(&cli, &selected);
+        assert!(results[0].is_ok() && results[1].is_ok() && results[2].is_err());
+        // The stopped container is removed before the volume
+        assert_eq!(
+            cli.calls.lock().unwrap()[1..],
+            [
+                "container rm c2".to_string(),
+                "volume rm pgdata".to_string()
+            ]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! IDE and editor caches
+//! JetBrains per-version cache directories (flagging versions no longer installed),
+//! VS Code/Cursor workspace storage, downloaded VSIXs and superseded extension
+//! versions (see `crate::extensions`), and Neovim caches
+
+use rayon::prelude::*;
+use regex::Regex;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use super::{format_size, get_dir_size, CacheInfo};
+use crate::extensions::{superseded_extension_dirs, EDITORS};
+
+/// VS Code family editors: (id, name, user data dir name)
+const VSCODE_EDITORS: &[(&str, &str, &str)] = &[
+    ("vscode", "VS Code", "Code"),
+    ("cursor", "Cursor", "Cursor"),
+];
+
+/// Regenerable directories inside a VS Code style user data dir
+const VSCODE_CACHE_DIRS: &[(&str, &str)] = &[
+    ("User/workspaceStorage", "Workspace Storage"),
+    ("CachedExtensionVSIXs", "Downloaded Extensions (VSIX)"),
+];
+
+/// Scan editor caches, largest first
+pub fn scan_editor_caches() -> Vec<CacheInfo> {
+    let mut caches: Vec<CacheInfo> = editor_cache_paths(true)
+        .into_par_iter()
+        .filter_map(|(id, name, path)| {
+            let size = get_dir_size(&path);
+            (size > 0).then(|| CacheInfo {
+                id,
+                name,
+                path: path.to_string_lossy().to_string(),
+                size,
+                size_display: format_size(size),
+                cache_type: "editor".to_string(),
+            })
+        })
+        .collect();
+    caches.sort_by(|a, b| b.size.cmp(&a.size));
+    caches
+}
+
+/// Whether `path` (canonical) is one of the editor cache directories
+pub(super) fn is_known_editor_cache(path: &Path) -> bool {
+    editor_cache_paths(false)
+        .into_iter()
+        .filter_map(|(_, _, candidate)| candidate.canonicalize().ok())
+        .any(|candidate| candidate == path)
+}
+
+/// (id, display name, path) of every existing editor cache directory; finding the
+/// installed JetBrains IDEs to label old versions is skipped when only paths matter
+fn editor_cache_paths(label_old_versions: bool) -> Vec<(String, String, PathBuf)> {
+    let Some(home) = user_home_dir() else {
+        return Vec::new();
+    };
+    let mut paths = Vec::new();
+
+    let installed = if label_old_versions {
+        installed_jetbrains_versions(&home)
+    } else {
+        HashSet::new()
+    };
+    for cache_root in jetbrains_cache_roots(&home) {
+        paths.extend(jetbrains_caches(&cache_root, &installed));
+    }
+
+    for (id, name, data_dir) in VSCODE_EDITORS {
+        for user_data in vscode_user_data_dirs(&home, data_dir) {
+            for (dir, label) in VSCODE_CACHE_DIRS {
+                let path = user_data.join(dir);
+                if path.is_dir() {
+                    paths.push((
+                        format!("{}_{}", id, dir.replace('/', "_")),
+                        format!("{} {}", name, label),
+                        path,
+                    ));
+                }
+            }
+        }
+    }
+    for (id, name, extensions_dir) in EDITORS {
+        for (extension, version, path) in superseded_extension_dirs(&home.join(extensions_dir)) {
+            paths.push((
+                format!("{}_extension_{}_{}", id, extension, version),
+                format!("{} Old Extension {} {}", name, extension, version),
+                path,
+            ));
+        }
+    }
+
+    let neovim = [
+        ("nvim_cache", "Neovim Cache", home.join(".cache/nvim")),
+        (
+            "nvim_lazy",
+            "Neovim lazy.nvim Plugins",
+            home.join(".local/share/nvim/lazy"),
+        ),
+    ]
+    .into_iter()
+    .chain(
+        env::var("LOCALAPPDATA")
+            .ok()
+            .filter(|local_app_data| !local_app_data.is_empty())
+            .map(|local_app_data| {
+                (
+                    "nvim_cache",
+                    "Neovim Cache",
+                    PathBuf::from(local_app_data).join("Temp/nvim"),
+                )
+            }),
+    );
+    for (id, name, path) in neovim {
+        if path.is_dir() {
+            paths.push((id.to_string(), name.to_string(), path));
+        }
+    }
+
+    paths
+}
+
+fn jetbrains_cache_roots(home: &Path) -> Vec<PathBuf> {
+    let mut roots = vec![
+        home.join(".cache/JetBrains"),
+        home.join("Library/Caches/JetBrains"),
+    ];
+    if let Ok(local_app_data) = env::var("LOCALAPPDATA") {
+        roots.push(PathBuf::from(local_app_data).join("JetBrains"));
+    }
+    roots.into_iter().filter(|root| root.is_dir()).collect()
+}
+
+/// Per-version cache dirs (`IntelliJIdea2024.1`, `PyCharm2023.3`, ...) under `cache_root`
+fn jetbrains_caches(
+    cache_root: &Path,
+    installed: &HashSet<String>,
+) -> Vec<(String, String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(cache_root) else {
+        return Vec::new();
+    };
+    let dirs: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| product_version(name).is_some())
+        .collect();
+
+    dirs.iter()
+        .map(|dir| {
+            let name = if is_old_jetbrains_version(dir, &dirs, installed) {
+                format!("JetBrains {} (old version)", dir)
+            } else {
+                format!("JetBrains {}", dir)
+            };
+            (format!("jetbrains_{}", dir), name, cache_root.join(dir))
+        })
+        .collect()
+}
+
+/// `IntelliJIdea2024.1` -> ("IntelliJIdea", (2024, 1))
+fn product_version(dir: &str) -> Option<(&str, (u32, u32))> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let regex =
+        PATTERN.get_or_init(|| Regex::new(r"^([A-Za-z]+)(\d{4})\.(\d+)$").expect("valid regex"));
+    let captures = regex.captures(dir)?;
+    Some((
+        captures.get(1)?.as_str(),
+        (captures[2].parse().ok()?, captures[3].parse().ok()?),
+    ))
+}
+
+/// Not installed, and a newer version of the same product is installed or has a cache
+fn is_old_jetbrains_version(dir: &str, cache_dirs: &[String], installed: &HashSet<String>) -> bool {
+    let Some((product, version)) = product_version(dir) else {
+        return false;
+    };
+    if installed.contains(dir) {
+        return false;
+    }
+    cache_dirs
+        .iter()
+        .chain(installed.iter())
+        .filter_map(|other| product_version(other))
+        .any(|(other_product, other_version)| other_product == product && other_version > version)
+}
+
+/// `dataDirectoryName` of every installed JetBrains IDE, read from `product-info.json`
+/// at the fixed spots installers and Toolbox put it (no recursive walk)
+fn installed_jetbrains_versions(home: &Path) -> HashSet<String> {
+    const BUNDLE: &str = "*.app/Contents/Resources/product-info.json";
+    let mut layouts: Vec<(PathBuf, &str)> = vec![
+        // Toolbox 2.x puts each IDE in apps/<ide>, 1.x in apps/<IDE>/ch-0/<build>
+        (
+            home.join(".local/share/JetBrains/Toolbox/apps"),
+            "*/product-info.json",
+        ),
+        (
+            home.join(".local/share/JetBrains/Toolbox/apps"),
+            "*/ch-*/*/product-info.json",
+        ),
+        (
+            home.join("Library/Application Support/JetBrains/Toolbox/apps"),
+            "*/*.app/Contents/Resources/product-info.json",
+        ),
+        (
+            home.join("Library/Application Support/JetBrains/Toolbox/apps"),
+            "*/ch-*/*/*.app/Contents/Resources/product-info.json",
+        ),
+        (home.join("Applications"), BUNDLE),
+        (PathBuf::from("/Applications"), BUNDLE),
+        (PathBuf::from("/opt"), "*/product-info.json"),
+        (PathBuf::from("/snap"), "*/current/product-info.json"),
+    ];
+    if let Ok(local_app_data) = env::var("LOCALAPPDATA") {
+        let toolbox = PathBuf::from(&local_app_data).join("JetBrains/Toolbox/apps");
+        layouts.push((toolbox.clone(), "*/product-info.json"));
+        layouts.push((toolbox, "*/ch-*/*/product-info.json"));
+        layouts.push((
+            PathBuf::from(local_app_data).join("Programs"),
+            "*/product-info.json",
+        ));
+    }
+    if let Ok(program_files) = env::var("ProgramFiles") {
+        layouts.push((
+            PathBuf::from(program_files).join("JetBrains"),
+            "*/product-info.json",
+        ));
+    }
+
+    layouts
+        .iter()
+        .filter(|(root, _)| root.is_dir())
+        .filter_map(|(root, pattern)| {
+            let root = glob::Pattern::escape(&root.to_string_lossy());
+            glob::glob(&format!("{}/{}", root, pattern)).ok()
+        })
+        .flatten()
+        .flatten()
+        .filter_map(|path| {
+            let content = fs::read_to_string(path).ok()?;
+            let info: Value = serde_json::from_str(&content).ok()?;
+            info.get("dataDirectoryName")?.as_str().map(str::to_string)
+        })
+        .collect()
+}
+
+/// `~/.config/Code`, `~/Library/Application Support/Code` or `%APPDATA%\Code`
+fn vscode_user_data_dirs(home: &Path, data_dir: &str) -> Vec<PathBuf> {
+    let mut dirs = vec![
+        home.join(".config").join(data_dir),
+        home.join("Library/Application Support").join(data_dir),
+    ];
+    if let Ok(app_data) = env::var("APPDATA") {
+        dirs.push(PathBuf::from(app_data).join(data_dir));
+    }
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_old_ide_versions() {
+        let caches: Vec<String> = ["IntelliJIdea2023.2", "IntelliJIdea2024.1", "PyCharm2023.3"]
+            .iter()
+            .map(|dir| dir.to_string())
+            .collect();
+        let installed: HashSet<String> = ["IntelliJIdea2024.1".to_string()].into();
+        assert!(is_old_jetbrains_version(
+            "IntelliJIdea2023.2",
+            &caches,
+            &installed
+        ));
+        assert!(!is_old_jetbrains_version(
+            "IntelliJIdea2024.1",
+            &caches,
+            &installed
+        ));
+        // The only PyCharm version is kept, even though no install was found
+        assert!(!is_old_jetbrains_version(
+            "PyCharm2023.3",
+            &caches,
+            &installed
+        ));
+    }
+}
@@ -7,6 +7,9 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+pub mod containers;
+pub mod editors;
+
 /// Represents a cache entry that can be cleaned
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheInfo {
@@ -129,11 +132,15 @@ fn get_package_manager_caches() -> Vec<(&'static str, &'static str, Vec<PathBuf>
             "Gradle Cache",
             vec![PathBuf::from(format!("{}/.gradle/caches", home))],
         ),
-        // homebrew (macOS)
+        // homebrew (macOS, Linuxbrew)
         (
             "homebrew",
             "Homebrew Cache",
-            vec![PathBuf::from(format!("{}/Library/Caches/Homebrew", home))],
+            vec![
+                PathBuf::from(env::var("HOMEBREW_CACHE").unwrap_or_default()),
+                PathBuf::from(format!("{}/Library/Caches/Homebrew", home)),
+                PathBuf::from(format!("{}/.cache/Homebrew", home)),
+            ],
         ),
         // go modules
         (
@@ -236,7 +243,10 @@ fn validate_cache_cleanup_target(path: &Path) -> Result<PathBuf, String> {
         ));
     }
 
-    if is_known_package_manager_cache(&canonical) || is_known_project_cache(&canonical) {
+    if is_known_package_manager_cache(&canonical)
+        || is_known_project_cache(&canonical)
+        || editors::is_known_editor_cache(&canonical)
+    {
         Ok(canonical)
     } else {
         Err(format!(
@@ -1,11 +1,18 @@
 //! Tauri commands for cache management
 
+use crate::cache::containers::{
+    plan_container_prune, prune_container_items, scan_container_storage, ContainerItemRef,
+    ContainerPrunePlan, ContainerStorageReport,
+};
+use crate::cache::editors::scan_editor_caches;
 use crate::cache::{clean_cache, scan_package_manager_caches, scan_project_caches, CacheInfo};
 
-/// Scan all package manager caches
+/// Scan all package manager and IDE/editor caches
 #[tauri::command]
 pub fn scan_caches() -> Vec<CacheInfo> {
-    scan_package_manager_caches()
+    let mut caches = scan_package_manager_caches();
+    caches.extend(scan_editor_caches());
+    caches
 }
 
 /// Scan project caches in a directory
@@ -47,3 +54,21 @@ pub fn get_total_cache_size(paths: Vec<String>) -> String {
 
     crate::cache::format_size(total)
 }
+
+/// List reclaimable Docker/Podman images, volumes, containers and build cache
+#[tauri::command]
+pub fn scan_container_storage_cmd() -> ContainerStorageReport {
+    scan_container_storage()
+}
+
+/// Check which selected container items can still be pruned and what they free
+#[tauri::command]
+pub fn plan_container_prune_cmd(items: Vec<ContainerItemRef>) -> ContainerPrunePlan {
+    plan_container_prune(&items)
+}
+
+/// Prune selected container items
+#[tauri::command]
+pub fn prune_container_items_cmd(items: Vec<ContainerItemRef>) -> Vec<Result<String, String>> {
+    prune_container_items(&items)
+}
@@ -1,9 +1,14 @@
 //! Tauri commands for environment configuration diagnostics
 
+use crate::config::java_home::{check_java_home, JavaHomeReport};
+use crate::config::npm_prefix::{check_npm_prefix, fix_npm_prefix, NpmPrefixFix, NpmPrefixReport};
+use crate::config::requirements::{check_project_requirements, ProjectRequirementReport};
 use crate::config::{
     analyze_path, diagnose_environment, get_path_cleanup_suggestions, get_shell_configs,
     EnvDiagnosis, PathEntry, ShellConfig,
 };
+use crate::error::DevJanitorError;
+use std::path::Path;
 
 /// Analyze current PATH
 #[tauri::command]
@@ -18,7 +23,10 @@ pub fn get_shell_configs_cmd() -> Vec<ShellConfig> {
 }
 
 /// Run full environment diagnosis
-#[tauri::command]
+///
+/// The manager doctors can take minutes (`flutter doctor`, `brew doctor`), so this runs
+/// off the main thread.
+#[tauri::command(async)]
 pub fn diagnose_env_cmd() -> EnvDiagnosis {
     diagnose_environment()
 }
@@ -29,3 +37,27 @@ pub fn get_path_suggestions_cmd() -> Vec<String> {
     let entries = analyze_path();
     get_path_cleanup_suggestions(&entries)
 }
+
+/// Inspect the global npm prefix (ownership, PATH, shadowed commands)
+#[tauri::command]
+pub fn check_npm_prefix_cmd() -> Option<NpmPrefixReport> {
+    check_npm_prefix()
+}
+
+/// Move npm globals to a user-level prefix and add its bin directory to the shell rc
+#[tauri::command]
+pub fn fix_npm_prefix_cmd(prefix: Option<String>) -> Result<NpmPrefixFix, DevJanitorError> {
+    fix_npm_prefix(prefix.as_deref())
+}
+
+/// Compare JAVA_HOME with the `java` on PATH and list installed JDKs
+#[tauri::command]
+pub fn check_java_home_cmd() -> JavaHomeReport {
+    check_java_home()
+}
+
+/// Check the toolchain a project declares against installed and active versions
+#[tauri::command]
+pub fn check_project_requirements_cmd(path: String) -> ProjectRequirementReport {
+    check_project_requirements(Path::new(&path))
+}
@@ -0,0 +1,18 @@
+//! Tauri commands for editor extensions
+
+use crate::extensions::{remove_superseded_extensions, scan_editor_extensions, EditorExtensions};
+
+/// List extensions per editor with every version on disk
+#[tauri::command]
+pub fn scan_editor_extensions_cmd() -> Vec<EditorExtensions> {
+    scan_editor_extensions()
+}
+
+/// Remove superseded extension versions of one editor
+#[tauri::command]
+pub fn remove_superseded_extensions_cmd(
+    #[allow(non_snake_case)] editorId: String,
+    paths: Vec<String>,
+) -> Vec<Result<String, String>> {
+    remove_superseded_extensions(&editorId, &paths)
+}
@@ -0,0 +1,23 @@
+//! Tauri commands for toolchain manifests
+
+use crate::manifest::{
+    apply_manifest, diff_manifest_file, export_manifest, ManifestApplyResult, ManifestDiff,
+};
+
+/// Export the current toolchain to a TOML or JSON manifest
+#[tauri::command]
+pub fn export_manifest_cmd(path: String) -> Result<String, String> {
+    export_manifest(&path)
+}
+
+/// Compare a manifest with the tools and packages installed on this machine
+#[tauri::command]
+pub fn diff_manifest_cmd(path: String) -> Result<ManifestDiff, String> {
+    diff_manifest_file(&path)
+}
+
+/// Install the packages and AI tools a manifest lists but this machine lacks
+#[tauri::command]
+pub fn apply_manifest_cmd(path: String) -> Result<Vec<ManifestApplyResult>, String> {
+    apply_manifest(&path)
+}
@@ -5,6 +5,8 @@ pub mod ai_cli;
 pub mod cache;
 pub mod chat_history;
 pub mod config;
+pub mod extensions;
+pub mod manifest;
 pub mod packages;
 pub mod security;
 pub mod services;
@@ -15,6 +17,8 @@ pub use ai_cli::*;
 pub use cache::*;
 pub use chat_history::*;
 pub use config::*;
+pub use extensions::*;
+pub use manifest::*;
 pub use packages::*;
 pub use security::*;
 pub use services::*;
@@ -1,7 +1,20 @@
 //! Tauri commands for package management
 
-use crate::package_manager::{cargo, composer, conda, npm, pip};
-use crate::package_manager::{scan_all_packages, PackageInfo, PackageManager};
+use crate::error::DevJanitorError;
+use crate::package_manager::batch::{
+    run_batch, BatchItemResult, BatchOperation, BatchPackageRequest, BatchSummary,
+};
+use crate::package_manager::composer::{scan_composer_projects, ComposerProjectReport};
+use crate::package_manager::dependencies::{DependencyGraph, PackageDependencyReport};
+use crate::package_manager::environments::{
+    delete_python_environment, scan_python_environments, PythonEnvironment,
+};
+use crate::package_manager::holds::{
+    hold_package, latest_version, list_holds, release_package, HoldStore, PackageHold,
+};
+use crate::package_manager::homebrew::{homebrew_inventory, HomebrewInventory};
+use crate::package_manager::{create_manager, scan_all_packages, PackageInfo, UninstallReport};
+use crate::utils::command::CommandOutcome;
 
 /// Scan all package managers for installed packages
 #[tauri::command]
@@ -11,102 +24,154 @@ pub fn scan_packages() -> Vec<PackageInfo> {
 
 /// Update a package
 #[tauri::command]
-pub fn update_package(manager: String, name: String) -> Result<String, String> {
-    match manager.as_str() {
-        "npm" => {
-            if let Some(m) = npm::NpmManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("npm is not available".to_string())
-            }
-        }
-        "pip" => {
-            if let Some(m) = pip::PipManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("pip is not available".to_string())
-            }
-        }
-        "cargo" => {
-            if let Some(m) = cargo::CargoManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("cargo is not available".to_string())
-            }
-        }
-        "composer" => {
-            if let Some(m) = composer::ComposerManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("composer is not available".to_string())
-            }
-        }
-        "conda" => {
-            if let Some(m) = conda::CondaManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("conda is not available".to_string())
-            }
-        }
-        "homebrew" => {
-            use crate::package_manager::homebrew;
-            if let Some(m) = homebrew::HomebrewManager::new() {
-                m.update_package(&name)
-            } else {
-                Err("homebrew is not available".to_string())
-            }
-        }
-        _ => Err(format!("Unknown package manager: {}", manager)),
-    }
+pub fn update_package(manager: String, name: String) -> Result<CommandOutcome, DevJanitorError> {
+    let package_manager = create_manager(&manager)?;
+    HoldStore::load().ensure_update_allowed(&manager, &name, || {
+        latest_version(package_manager.as_ref(), &name)
+    })?;
+    package_manager.update_package(&name)
 }
 
 /// Uninstall a package
+///
+/// Refuses when other installed packages still depend on it unless `force` is set,
+/// and optionally removes dependencies that would be left orphaned.
 #[tauri::command]
-pub fn uninstall_package(manager: String, name: String) -> Result<String, String> {
-    match manager.as_str() {
-        "npm" => {
-            if let Some(m) = npm::NpmManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("npm is not available".to_string())
-            }
-        }
-        "pip" => {
-            if let Some(m) = pip::PipManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("pip is not available".to_string())
-            }
-        }
-        "cargo" => {
-            if let Some(m) = cargo::CargoManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("cargo is not available".to_string())
-            }
-        }
-        "composer" => {
-            if let Some(m) = composer::ComposerManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("composer is not available".to_string())
-            }
+pub fn uninstall_package(
+    manager: String,
+    name: String,
+    force: Option<bool>,
+    #[allow(non_snake_case)] removeOrphans: Option<bool>,
+) -> Result<UninstallReport, DevJanitorError> {
+    let holds = HoldStore::load();
+    holds.ensure_not_held(&manager, &name, "uninstall")?;
+
+    let package_manager = create_manager(&manager)?;
+    let report = package_manager
+        .dependency_graph()
+        .and_then(|graph| graph.report(&name));
+
+    if let Some(report) = &report {
+        if !report.required_by.is_empty() && !force.unwrap_or(false) {
+            return Err(DevJanitorError::Other(format!(
+                "{} is required by: {}. Uninstall those first or force the removal.",
+                name,
+                report.required_by.join(", ")
+            )));
         }
-        "conda" => {
-            if let Some(m) = conda::CondaManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("conda is not available".to_string())
+    }
+
+    let mut result = UninstallReport {
+        outcome: package_manager.uninstall_package(&name)?,
+        orphans: Vec::new(),
+        kept_held: Vec::new(),
+    };
+
+    if removeOrphans.unwrap_or(false) {
+        let orphans = report
+            .map(|report| report.orphans_after_uninstall)
+            .unwrap_or_default();
+        for orphan in orphans {
+            if holds.find(&manager, &orphan).is_some() {
+                result.kept_held.push(orphan);
+                continue;
             }
-        }
-        "homebrew" => {
-            use crate::package_manager::homebrew;
-            if let Some(m) = homebrew::HomebrewManager::new() {
-                m.uninstall_package(&name)
-            } else {
-                Err("homebrew is not available".to_string())
+            match package_manager.uninstall_package(&orphan) {
+                Ok(outcome) => result.orphans.push(outcome),
+                Err(DevJanitorError::PackageOperation(outcome)) => result.orphans.push(*outcome),
+                Err(error) => return Err(error),
             }
         }
-        _ => Err(format!("Unknown package manager: {}", manager)),
     }
+
+    Ok(result)
+}
+
+/// Update or uninstall many packages at once
+///
+/// Each package result is streamed through `onProgress`; the summary is returned at the end.
+/// Runs off the main thread so the progress messages reach the webview while it works.
+#[tauri::command(async)]
+pub fn batch_package_operation_cmd(
+    operation: BatchOperation,
+    packages: Vec<BatchPackageRequest>,
+    #[allow(non_snake_case)] onProgress: tauri::ipc::Channel<BatchItemResult>,
+) -> BatchSummary {
+    run_batch(operation, packages, |result| {
+        let _ = onProgress.send(result.clone());
+    })
+}
+
+/// Homebrew formulae, casks, taps, leaves, keg sizes and cleanup candidates
+#[tauri::command]
+pub fn get_homebrew_inventory_cmd() -> Option<HomebrewInventory> {
+    homebrew_inventory()
+}
+
+/// Composer projects under a root with their vendors and PHP compatibility
+#[tauri::command]
+pub fn scan_composer_projects_cmd(
+    path: String,
+    #[allow(non_snake_case)] maxDepth: usize,
+) -> ComposerProjectReport {
+    scan_composer_projects(&path, maxDepth.min(20))
+}
+
+/// List held packages
+#[tauri::command]
+pub fn list_package_holds_cmd() -> Vec<PackageHold> {
+    list_holds()
+}
+
+/// Hold a package so updates and uninstalls skip it
+#[tauri::command]
+pub fn hold_package_cmd(
+    manager: String,
+    name: String,
+    #[allow(non_snake_case)] versionConstraint: Option<String>,
+) -> Result<String, String> {
+    hold_package(&manager, &name, versionConstraint)
+}
+
+/// Release a package hold
+#[tauri::command]
+pub fn release_package_hold_cmd(manager: String, name: String) -> Result<String, String> {
+    release_package(&manager, &name)
+}
+
+/// Get the dependency graph of a package manager's installed packages
+#[tauri::command]
+pub fn get_dependency_graph_cmd(manager: String) -> Result<DependencyGraph, String> {
+    create_manager(&manager)?
+        .dependency_graph()
+        .ok_or_else(|| format!("{} does not expose dependency information", manager))
+}
+
+/// Explain why a package is installed and what uninstalling it would orphan
+#[tauri::command]
+pub fn get_package_dependencies_cmd(
+    manager: String,
+    name: String,
+) -> Result<PackageDependencyReport, String> {
+    create_manager(&manager)?
+        .dependency_graph()
+        .ok_or_else(|| format!("{} does not expose dependency information", manager))?
+        .report(&name)
+        .ok_or_else(|| format!("{} is not installed via {}", name, manager))
+}
+
+/// Scan conda environments, pyenv versions and virtualenvs under an optional root
+#[tauri::command]
+pub fn scan_python_envs_cmd(
+    root: Option<String>,
+    #[allow(non_snake_case)] maxDepth: Option<usize>,
+) -> Vec<PythonEnvironment> {
+    let max_depth = maxDepth.unwrap_or(6).min(20);
+    scan_python_environments(root.as_deref(), max_depth)
+}
+
+/// Delete a whole Python environment (conda env, pyenv version or virtualenv)
+#[tauri::command]
+pub fn delete_python_env_cmd(path: String) -> Result<String, String> {
+    delete_python_environment(&path)
 }
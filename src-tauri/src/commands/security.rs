@@ -1,7 +1,11 @@
 //! Tauri commands for AI tool security scanning
 
+use std::path::Path;
+
+use crate::security_scan::advisories::AdvisoryDatabase;
 use crate::security_scan::{
-    get_rules, scan_ai_tool_security, scan_specific_tool, SecurityScanResult,
+    audit_packages, get_rules, scan_ai_tool_security, scan_specific_tool, PackageAuditResult,
+    SecurityScanResult,
 };
 
 /// Perform a full security scan of all AI tools
@@ -34,6 +38,24 @@ pub fn scan_tool_security_cmd(
     scan_specific_tool(&toolId)
 }
 
+/// Audit global packages (and optionally a project's lockfiles) against the offline advisory DB
+#[tauri::command]
+pub fn audit_packages_cmd(
+    #[allow(non_snake_case)] advisoryDir: Option<String>,
+    #[allow(non_snake_case)] projectPath: Option<String>,
+) -> Result<PackageAuditResult, String> {
+    audit_packages(
+        advisoryDir.as_deref().map(Path::new),
+        projectPath.as_deref().map(Path::new),
+    )
+}
+
+/// Default directory the user syncs advisory exports into
+#[tauri::command]
+pub fn get_advisory_dir_cmd() -> Option<String> {
+    AdvisoryDatabase::default_dir().map(|dir| dir.to_string_lossy().to_string())
+}
+
 /// Tool info for frontend display
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SecurityToolInfo {
@@ -1,16 +1,37 @@
 //! Tauri commands for tool detection and management
 
 use crate::ai_tools::normalize_ai_tool_id;
-use crate::detection::{scan_all_tools, ToolInfo};
+use crate::detection::pins::{find_project_pins, PIN_SCAN_DEPTH};
+use crate::detection::rules::{tool_rule_report, ToolRuleReport};
+use crate::detection::uninstall::{uninstall_managed_version, VersionUninstallResult};
+use crate::detection::{apply_project_pins, scan_all_tools, scan_all_tools_in, ToolInfo};
 
 use crate::ai_cli;
-use crate::utils::command::{command_output_with_timeout, command_output_with_timeout_vec};
+use crate::error::DevJanitorError;
+use crate::package_manager::pip::PipManager;
+use crate::system_packages::{resolve_owner, uninstall_owner, OwningPackage};
+use crate::utils::command::{
+    command_output_with_timeout, command_output_with_timeout_vec, CommandOutcome,
+};
+use std::path::Path;
 use std::time::Duration;
 
-/// Scan for all development tools
+/// Scan for all development tools as seen from `projectRoot`, marking versions pinned
+/// by projects under it; without one shims resolve from the home directory and no pins
+/// are marked (only uninstalls fall back to walking the home directory)
 #[tauri::command]
-pub fn scan_tools() -> Vec<ToolInfo> {
-    scan_all_tools()
+pub fn scan_tools(#[allow(non_snake_case)] projectRoot: Option<String>) -> Vec<ToolInfo> {
+    let root = projectRoot
+        .as_deref()
+        .map(str::trim)
+        .filter(|root| !root.is_empty())
+        .map(Path::new);
+    let mut tools = scan_all_tools_in(root);
+    if let Some(root) = root {
+        let pins = find_project_pins(root, PIN_SCAN_DEPTH);
+        apply_project_pins(&mut tools, &pins);
+    }
+    tools
 }
 
 /// Get tool info by ID
@@ -20,6 +41,12 @@ pub fn get_tool_info(#[allow(non_snake_case)] toolId: String) -> Option<ToolInfo
     tools.into_iter().find(|t| t.id == toolId)
 }
 
+/// Loaded detection rules and any user rule files that failed validation
+#[tauri::command]
+pub fn get_tool_rules_cmd() -> ToolRuleReport {
+    tool_rule_report()
+}
+
 /// Uninstall a tool
 #[tauri::command]
 pub fn uninstall_tool(
@@ -89,29 +116,41 @@ pub fn uninstall_tool(
             }
             #[cfg(target_os = "linux")]
             {
-                Err(format!(
-                    "{} should be uninstalled via your package manager (apt/yum/pacman)",
-                    toolId
+                Err(system_package_advice(
+                    &path,
+                    format!(
+                        "{} should be uninstalled via your package manager (apt/yum/pacman)",
+                        toolId
+                    ),
                 ))
             }
         }
 
         // Docker and containers
-        "docker" | "podman" | "kubectl" => Err(format!(
-            "{} should be uninstalled from your system's application management",
-            toolId
+        "docker" | "podman" | "kubectl" => Err(system_package_advice(
+            &path,
+            format!(
+                "{} should be uninstalled from your system's application management",
+                toolId
+            ),
         )),
 
         // Build tools
-        "cmake" | "make" | "ninja" => Err(format!(
-            "{} should be uninstalled via your system's package manager",
-            toolId
+        "cmake" | "make" | "ninja" => Err(system_package_advice(
+            &path,
+            format!(
+                "{} should be uninstalled via your system's package manager",
+                toolId
+            ),
         )),
 
         // Version control
-        "git" | "svn" => Err(format!(
-            "{} should be uninstalled via your system's package manager or installer",
-            toolId
+        "git" | "svn" => Err(system_package_advice(
+            &path,
+            format!(
+                "{} should be uninstalled via your system's package manager or installer",
+                toolId
+            ),
         )),
 
         _ => Err(format!(
@@ -123,6 +162,46 @@ pub fn uninstall_tool(
     uninstall_result
 }
 
+/// Remove one runtime version with its version manager, keeping defaults and versions
+/// pinned by projects under `scanRoot`
+#[tauri::command]
+pub fn uninstall_runtime_version_cmd(
+    #[allow(non_snake_case)] toolId: String,
+    manager: String,
+    version: String,
+    #[allow(non_snake_case)] scanRoot: Option<String>,
+) -> Result<VersionUninstallResult, DevJanitorError> {
+    uninstall_managed_version(
+        &toolId,
+        &manager,
+        &version,
+        scanRoot.as_deref().map(Path::new),
+    )
+}
+
+/// Resolve the OS package (apt/dnf/pacman) that owns a tool binary
+#[tauri::command]
+pub fn get_tool_owner_cmd(path: String) -> Option<OwningPackage> {
+    resolve_owner(&path)
+}
+
+/// Remove the OS package that owns a tool binary through a pkexec/sudo prompt
+#[tauri::command]
+pub fn uninstall_system_package_cmd(path: String) -> Result<CommandOutcome, DevJanitorError> {
+    uninstall_owner(&path)
+}
+
+/// Exact removal command when an OS package owns `path`, otherwise `fallback`
+fn system_package_advice(path: &str, fallback: String) -> String {
+    match resolve_owner(path) {
+        Some(owner) => format!(
+            "{} is provided by the system package {}. Run: {}",
+            owner.path, owner.package, owner.remove_command
+        ),
+        None => fallback,
+    }
+}
+
 /// Run a command and return result (with 120s timeout)
 fn run_command(cmd: &str, args: &[&str]) -> Result<String, String> {
     let output = command_output_with_timeout(cmd, args, Duration::from_secs(120));
@@ -171,6 +250,11 @@ fn run_owned_command(cmd: &str, args: &[String]) -> Result<String, String> {
 }
 
 fn uninstall_with_pip(package: &str) -> Result<String, String> {
+    // Never let pip remove what the OS Python owns (PEP 668, distro dist-info)
+    if let Some(pip) = PipManager::new() {
+        pip.ensure_writable(package, true, &["uninstall", "-y", package])?;
+    }
+
     let mut candidates: Vec<(&str, Vec<String>)> = Vec::new();
 
     #[cfg(target_os = "windows")]
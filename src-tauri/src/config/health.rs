@@ -0,0 +1,379 @@
+//! Package manager health checks
+//! Runs each available manager's self-check (npm doctor, brew doctor, pip check, ...)
+//! and turns its warnings into diagnosis issues
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use super::DiagnosisIssue;
+use crate::error::FailureKind;
+use crate::utils::command::run_command_outcome;
+
+/// Summary of one manager's self-check run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthCheckResult {
+    pub manager: String,
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    pub issue_count: usize,
+}
+
+/// A self-check command and the parser for its output
+struct HealthCheck {
+    manager: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+    timeout: Duration,
+    parse: fn(&str) -> Vec<DiagnosisIssue>,
+}
+
+fn health_checks() -> Vec<HealthCheck> {
+    vec![
+        HealthCheck {
+            manager: "npm",
+            program: "npm",
+            args: &["doctor"],
+            timeout: Duration::from_secs(60), // pings the registry
+            parse: parse_npm_doctor,
+        },
+        HealthCheck {
+            manager: "Homebrew",
+            program: "brew",
+            args: &["doctor"],
+            timeout: Duration::from_secs(90),
+            parse: parse_brew_doctor,
+        },
+        HealthCheck {
+            manager: "Conda",
+            program: "conda",
+            args: &["info", "--json"],
+            timeout: Duration::from_secs(30),
+            parse: parse_conda_info,
+        },
+        HealthCheck {
+            manager: "pip",
+            program: if cfg!(target_os = "windows") {
+                "python"
+            } else {
+                "python3"
+            },
+            args: &["-m", "pip", "check"],
+            timeout: Duration::from_secs(30),
+            parse: parse_pip_check,
+        },
+        HealthCheck {
+            manager: "rustup",
+            program: "rustup",
+            args: &["check"],
+            timeout: Duration::from_secs(30),
+            parse: parse_rustup_check,
+        },
+        HealthCheck {
+            manager: "Flutter",
+            program: "flutter",
+            args: &["doctor"],
+            timeout: Duration::from_secs(120),
+            parse: parse_flutter_doctor,
+        },
+    ]
+}
+
+/// Run every available manager's self-check in parallel
+pub fn run_health_checks() -> (Vec<HealthCheckResult>, Vec<DiagnosisIssue>) {
+    let runs: Vec<(HealthCheckResult, Vec<DiagnosisIssue>)> = health_checks()
+        .par_iter()
+        .filter(|check| which::which(check.program).is_ok())
+        .map(run_health_check)
+        .collect();
+
+    let mut results = Vec::new();
+    let mut issues = Vec::new();
+    for (result, check_issues) in runs {
+        results.push(result);
+        issues.extend(check_issues);
+    }
+    (results, issues)
+}
+
+fn run_health_check(check: &HealthCheck) -> (HealthCheckResult, Vec<DiagnosisIssue>) {
+    let args: Vec<String> = check.args.iter().map(|arg| arg.to_string()).collect();
+    let outcome = run_command_outcome(check.program, &args, check.timeout);
+    let timed_out = outcome.failure == Some(FailureKind::Timeout);
+
+    // Doctors exit non-zero when they find problems, so parse the output either way
+    let mut issues: Vec<DiagnosisIssue> =
+        (check.parse)(&format!("{}\n{}", outcome.stdout, outcome.stderr))
+            .into_iter()
+            .map(|issue| DiagnosisIssue {
+                category: check.manager.to_string(),
+                ..issue
+            })
+            .collect();
+
+    if timed_out {
+        issues.push(DiagnosisIssue {
+            severity: "info".to_string(),
+            category: check.manager.to_string(),
+            message: format!(
+                "`{}` did not finish within {}s",
+                outcome.command,
+                check.timeout.as_secs()
+            ),
+            suggestion: Some(format!(
+                "Run `{}` manually to see its report",
+                outcome.command
+            )),
+        });
+    }
+
+    let result = HealthCheckResult {
+        manager: check.manager.to_string(),
+        command: outcome.command,
+        exit_code: outcome.exit_code,
+        timed_out,
+        duration_ms: outcome.duration_ms,
+        issue_count: issues.len(),
+    };
+    (result, issues)
+}
+
+/// Issue with the category filled in later by the caller
+fn issue(severity: &str, message: String, suggestion: Option<String>) -> DiagnosisIssue {
+    DiagnosisIssue {
+        severity: severity.to_string(),
+        category: String::new(),
+        message,
+        suggestion,
+    }
+}
+
+/// `npm doctor`: a "Check / Value / Recommendation" table (npm 7-9), or
+/// check, "Ok"/"Not ok" and recommendation on consecutive lines (npm 10+)
+fn parse_npm_doctor(output: &str) -> Vec<DiagnosisIssue> {
+    let lines: Vec<&str> = output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    let mut issues = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        if line.eq_ignore_ascii_case("not ok") {
+            let check = index
+                .checked_sub(1)
+                .map(|previous| lines[previous])
+                .unwrap_or("npm doctor");
+            let recommendation = lines
+                .get(index + 1)
+                .filter(|next| !next.starts_with("Checking") && !next.starts_with("Connecting"))
+                .map(|next| next.to_string());
+            issues.push(issue("warning", check.to_string(), recommendation));
+        } else if let Some((check, rest)) = line.split_once(" not ok") {
+            let recommendation = rest.trim();
+            issues.push(issue(
+                "warning",
+                format!("npm doctor: {} is not ok", check.trim()),
+                (!recommendation.is_empty()).then(|| recommendation.to_string()),
+            ));
+        }
+    }
+    issues
+}
+
+/// `brew doctor`: paragraphs starting with "Warning:", details indented below
+fn parse_brew_doctor(output: &str) -> Vec<DiagnosisIssue> {
+    output
+        .split("Warning: ")
+        .skip(1)
+        .filter_map(|block| {
+            let mut paragraphs = block.trim().splitn(2, '\n');
+            let message = paragraphs.next()?.trim().to_string();
+            let detail = paragraphs
+                .next()
+                .map(|rest| {
+                    rest.lines()
+                        .map(str::trim)
+                        .take_while(|line| !line.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .filter(|detail| !detail.is_empty());
+            Some(issue("warning", message, detail))
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct CondaInfo {
+    #[serde(default)]
+    offline: bool,
+    #[serde(default)]
+    root_writable: Option<bool>,
+    #[serde(default)]
+    channels: Vec<String>,
+}
+
+/// `conda info --json`: offline mode, read-only base and missing channels
+fn parse_conda_info(output: &str) -> Vec<DiagnosisIssue> {
+    // stderr is appended after the JSON document, so read just the first value
+    let Some(start) = output.find('{') else {
+        return Vec::new();
+    };
+    let Some(Ok(info)) = serde_json::Deserializer::from_str(&output[start..])
+        .into_iter::<CondaInfo>()
+        .next()
+    else {
+        return Vec::new();
+    };
+
+    let mut issues = Vec::new();
+    if info.offline {
+        issues.push(issue(
+            "warning",
+            "Conda is in offline mode".to_string(),
+            Some("Run `conda config --set offline false` to reach channels again".to_string()),
+        ));
+    }
+    if info.root_writable == Some(false) {
+        issues.push(issue(
+            "info",
+            "The base environment is not writable; packages install into user environments"
+                .to_string(),
+            Some("Create a user environment with `conda create -n <name>`".to_string()),
+        ));
+    }
+    if info.channels.is_empty() {
+        issues.push(issue(
+            "warning",
+            "No conda channels are configured".to_string(),
+            Some("Add one with `conda config --add channels conda-forge`".to_string()),
+        ));
+    }
+    issues
+}
+
+/// `pip check`: one line per broken requirement
+fn parse_pip_check(output: &str) -> Vec<DiagnosisIssue> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.contains(" requires ") || line.contains(" has requirement "))
+        .map(|line| {
+            let package = line.split_whitespace().next().unwrap_or(line);
+            issue(
+                "warning",
+                line.trim_end_matches('.').to_string(),
+                Some(format!(
+                    "Upgrade or reinstall {} so its requirements are met",
+                    package
+                )),
+            )
+        })
+        .collect()
+}
+
+/// `rustup check`: "stable-x86_64-unknown-linux-gnu - Update available : 1.75.0 -> 1.76.0"
+fn parse_rustup_check(output: &str) -> Vec<DiagnosisIssue> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.contains("Update available"))
+        .filter_map(|line| {
+            let (toolchain, status) = line.split_once(" - ")?;
+            let toolchain = toolchain.trim();
+            let update = status.split_once(':').map(|(_, update)| update.trim());
+            let message = match update {
+                Some(update) => format!("{} has an update available: {}", toolchain, update),
+                None => format!("{} has an update available", toolchain),
+            };
+            let suggestion = if toolchain == "rustup" {
+                "rustup self update".to_string()
+            } else {
+                format!("rustup update {}", toolchain)
+            };
+            Some(issue("info", message, Some(suggestion)))
+        })
+        .collect()
+}
+
+/// `flutter doctor`: "[!] Section" (warning) or "[✗] Section" (error) with indented details
+fn parse_flutter_doctor(output: &str) -> Vec<DiagnosisIssue> {
+    let mut issues: Vec<DiagnosisIssue> = Vec::new();
+    let mut in_problem = false;
+
+    for line in output.lines() {
+        let trimmed = line.trim();
+        if let Some(section) = trimmed.strip_prefix('[') {
+            let Some((mark, title)) = section.split_once(']') else {
+                continue;
+            };
+            let severity = match mark.trim() {
+                "!" => Some("warning"),
+                "✗" | "X" | "x" => Some("error"),
+                _ => None,
+            };
+            in_problem = severity.is_some();
+            if let Some(severity) = severity {
+                issues.push(issue(severity, title.trim().to_string(), None));
+            }
+        } else if in_problem && (trimmed.starts_with('✗') || trimmed.starts_with('!')) {
+            let detail = trimmed.trim_start_matches(['✗', '!']).trim();
+            if let Some(last) = issues.last_mut() {
+                last.suggestion = Some(match last.suggestion.take() {
+                    Some(existing) => format!("{} {}", existing, detail),
+                    None => detail.to_string(),
+                });
+            }
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_doctor_outputs() {
+        let npm_table = "Check                               Value   Recommendation/Notes\nnpm ping                            ok\nnpm -v                              not ok  Use npm v10.2.4\n";
+        let issues = parse_npm_doctor(npm_table);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("Use npm v10.2.4"));
+
+        let npm_lines = "Connecting to the registry\nOk\nChecking npm version\nNot ok\nUse npm v10.5.0\nChecking node version\nOk\n";
+        let issues = parse_npm_doctor(npm_lines);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].message, "Checking npm version");
+        assert_eq!(issues[0].suggestion.as_deref(), Some("Use npm v10.5.0"));
+
+        let brew = "Please note that these warnings are just used to help the Homebrew maintainers\n\nWarning: Some installed formulae are not readable:\n  python@3.11\n\nWarning: Unbrewed header files were found in /usr/local/include.\n";
+        let issues = parse_brew_doctor(brew);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].suggestion.as_deref(), Some("python@3.11"));
+
+        let pip =
+            "black 24.1.0 requires click, which is not installed.\nNo broken requirements found.\n";
+        assert_eq!(parse_pip_check(pip).len(), 1);
+
+        let rustup = "stable-x86_64-unknown-linux-gnu - Update available : 1.75.0 (82e1608df 2023-12-21) -> 1.76.0 (07dca489a 2024-02-04)\nrustup - Up to date : 1.26.0\n";
+        let issues = parse_rustup_check(rustup);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("rustup update stable-x86_64-unknown-linux-gnu")
+        );
+
+        let flutter = "[✓] Flutter (Channel stable, 3.16.0)\n[!] Android toolchain - develop for Android devices\n    ✗ cmdline-tools component is missing\n[✗] Chrome - develop for the web (Cannot find Chrome executable)\n";
+        let issues = parse_flutter_doctor(flutter);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].severity, "warning");
+        assert_eq!(
+            issues[0].suggestion.as_deref(),
+            Some("cmdline-tools component is missing")
+        );
+        assert_eq!(issues[1].severity, "error");
+    }
+}
@@ -0,0 +1,198 @@
+//! JAVA_HOME diagnostics
+//! Compares `JAVA_HOME` with the JDK behind the `java` on PATH and lists every JDK
+//! found on the machine
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::DiagnosisIssue;
+use crate::detection::jdks::{discover_jdks, is_jdk_home, read_release, JdkInstall};
+
+/// JAVA_HOME, the JDK that PATH runs, and all installed JDKs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaHomeReport {
+    pub java_home: Option<String>,
+    pub java_home_version: Option<String>,
+    pub path_java: Option<String>, // `java` on PATH with symlinks resolved
+    pub path_java_home: Option<String>, // the JDK home that binary belongs to
+    pub path_java_version: Option<String>,
+    pub jdks: Vec<JdkInstall>,
+    pub issues: Vec<DiagnosisIssue>,
+}
+
+/// Inspect JAVA_HOME and the JDK inventory
+pub fn check_java_home() -> JavaHomeReport {
+    JavaHomeReport {
+        jdks: discover_jdks(),
+        ..inspect_java_home()
+    }
+}
+
+/// JAVA_HOME and PATH state without the (slow to measure) JDK inventory
+pub(super) fn inspect_java_home() -> JavaHomeReport {
+    let java_home = env::var_os("JAVA_HOME")
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let path_java = which::which("java")
+        .ok()
+        .map(|java| fs::canonicalize(&java).unwrap_or(java));
+    let path_java_home = path_java.as_deref().and_then(home_of_java);
+
+    let version_of =
+        |home: &Option<PathBuf>| home.as_deref().and_then(|home| read_release(home).version);
+    let java_home_version = version_of(&java_home);
+    let path_java_version = version_of(&path_java_home);
+
+    let issues = java_home_issues(
+        java_home.as_deref(),
+        path_java.as_deref(),
+        path_java_home.as_deref(),
+    );
+    let to_string = |path: Option<PathBuf>| path.map(|path| path.to_string_lossy().to_string());
+
+    JavaHomeReport {
+        java_home: to_string(java_home),
+        java_home_version,
+        path_java: to_string(path_java),
+        path_java_home: to_string(path_java_home),
+        path_java_version,
+        jdks: Vec::new(),
+        issues,
+    }
+}
+
+/// JDK home of a resolved `java` binary: `<home>/bin/java` or Java 8's `<home>/jre/bin/java`
+fn home_of_java(java: &Path) -> Option<PathBuf> {
+    java.ancestors()
+        .skip(2)
+        .take(2)
+        .find(|home| is_jdk_home(home))
+        .map(Path::to_path_buf)
+}
+
+fn java_home_issues(
+    java_home: Option<&Path>,
+    path_java: Option<&Path>,
+    path_java_home: Option<&Path>,
+) -> Vec<DiagnosisIssue> {
+    let issue = |severity: &str, message: String, suggestion: Option<String>| DiagnosisIssue {
+        severity: severity.to_string(),
+        category: "Java".to_string(),
+        message,
+        suggestion,
+    };
+
+    let Some(java_home) = java_home else {
+        return match path_java_home {
+            Some(home) => vec![issue(
+                "info",
+                "JAVA_HOME is not set; Maven, Gradle and IDEs may pick a different JDK than `java` on PATH".to_string(),
+                Some(format!("export JAVA_HOME=\"{}\"", home.display())),
+            )],
+            None => Vec::new(),
+        };
+    };
+
+    if !java_home.exists() {
+        return vec![issue(
+            "error",
+            format!(
+                "JAVA_HOME points to {}, which does not exist",
+                java_home.display()
+            ),
+            path_java_home.map(|home| format!("export JAVA_HOME=\"{}\"", home.display())),
+        )];
+    }
+    if !java_home.join("bin").join("java").exists()
+        && !java_home.join("bin").join("java.exe").exists()
+    {
+        return vec![issue(
+            "error",
+            format!(
+                "JAVA_HOME ({}) is not a JDK: bin/java is missing",
+                java_home.display()
+            ),
+            Some("Point JAVA_HOME at the JDK directory that contains bin/java".to_string()),
+        )];
+    }
+
+    match (path_java, path_java_home) {
+        (None, _) => vec![issue(
+            "warning",
+            "JAVA_HOME is set but `java` is not on PATH".to_string(),
+            Some(format!("Add {} to PATH", java_home.join("bin").display())),
+        )],
+        (Some(_), Some(home)) if !same_dir(home, java_home) => {
+            let version = |home: &Path| {
+                read_release(home)
+                    .version
+                    .map(|version| format!(" ({})", version))
+                    .unwrap_or_default()
+            };
+            vec![issue(
+                "warning",
+                format!(
+                    "JAVA_HOME is {}{} but `java` on PATH runs {}{}",
+                    java_home.display(),
+                    version(java_home),
+                    home.display(),
+                    version(home)
+                ),
+                Some(format!(
+                    "Put {} first on PATH or point JAVA_HOME at {}",
+                    java_home.join("bin").display(),
+                    home.display()
+                )),
+            )]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_java_home_mismatches() {
+        let dir = env::temp_dir().join(format!("dev-janitor-java-home-{}", std::process::id()));
+        let make_jdk = |name: &str, version: &str| {
+            let home = dir.join(name);
+            fs::create_dir_all(home.join("bin")).unwrap();
+            fs::write(home.join("bin/java"), "").unwrap();
+            fs::write(
+                home.join("release"),
+                format!("JAVA_VERSION=\"{}\"\n", version),
+            )
+            .unwrap();
+            home
+        };
+        let jdk17 = make_jdk("jdk-17", "17.0.9");
+        let jdk21 = make_jdk("jdk-21", "21.0.2");
+
+        assert_eq!(home_of_java(&jdk21.join("bin/java")), Some(jdk21.clone()));
+        assert!(
+            java_home_issues(Some(&jdk21), Some(&jdk21.join("bin/java")), Some(&jdk21)).is_empty()
+        );
+
+        let mismatch = java_home_issues(Some(&jdk17), Some(&jdk21.join("bin/java")), Some(&jdk21));
+        assert_eq!(mismatch[0].severity, "warning");
+        assert!(mismatch[0].message.contains("(17.0.9)"));
+        assert!(mismatch[0].message.contains("(21.0.2)"));
+
+        let missing = java_home_issues(Some(&dir.join("gone")), None, None);
+        assert_eq!(missing[0].severity, "error");
+        assert_eq!(java_home_issues(None, None, None).len(), 0);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,5 +1,11 @@
 //! Environment configuration diagnostics module for Dev Janitor v2
-//! PATH and Shell configuration analysis
+//! PATH and Shell configuration analysis, plus package manager health checks
+//! and global npm prefix diagnostics
+
+pub mod health;
+pub mod java_home;
+pub mod npm_prefix;
+pub mod requirements;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -7,6 +13,8 @@ use std::env;
 use std::fs;
 use std::path::PathBuf;
 
+use health::{run_health_checks, HealthCheckResult};
+
 /// Represents a PATH entry with analysis
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PathEntry {
@@ -35,6 +43,8 @@ pub struct EnvDiagnosis {
     pub shell_configs: Vec<ShellConfig>,
     pub issues: Vec<DiagnosisIssue>,
     pub suggestions: Vec<String>,
+    #[serde(default)]
+    pub health_checks: Vec<HealthCheckResult>,
 }
 
 /// A diagnosis issue
@@ -327,11 +337,24 @@ pub fn diagnose_environment() -> EnvDiagnosis {
         }
     }
 
+    // Manager self-checks (npm doctor, brew doctor, pip check, ...)
+    let (health_checks, health_issues) = run_health_checks();
+    issues.extend(health_issues);
+
+    // Root-owned npm prefix, its bin directory missing from PATH, shadowed globals
+    if let Some(npm) = npm_prefix::check_npm_prefix() {
+        issues.extend(npm.issues);
+    }
+
+    // JAVA_HOME missing, broken or disagreeing with `java` on PATH
+    issues.extend(java_home::inspect_java_home().issues);
+
     EnvDiagnosis {
         path_entries,
         shell_configs,
         issues,
         suggestions,
+        health_checks,
     }
 }
 
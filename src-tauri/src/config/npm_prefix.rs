@@ -0,0 +1,404 @@
+//! Global npm prefix diagnostics
+//! Detects a root-owned prefix, a prefix bin directory missing from PATH and global
+//! commands shadowed by other installs, and moves globals to a user-level prefix
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use super::{analyze_path, DiagnosisIssue};
+use crate::error::DevJanitorError;
+use crate::package_manager::npm::{npm_global_root, npm_prefix, NpmManager};
+use crate::package_manager::{run_operation, PackageManager};
+use crate::utils::command::CommandOutcome;
+
+/// User-level prefix the fix switches to, relative to the home directory
+const USER_PREFIX: &str = ".npm-global";
+
+/// A global npm command that resolves to a different executable on PATH
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShadowedBinary {
+    pub name: String,
+    pub package: String,
+    pub npm_path: String,
+    pub resolved_path: String,
+}
+
+/// State of the global npm prefix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpmPrefixReport {
+    pub prefix: String,
+    pub bin_dir: String,
+    pub global_root: String,
+    pub prefix_writable: bool,
+    pub bin_on_path: bool,
+    pub global_packages: Vec<String>, // "name@version"
+    pub shadowed: Vec<ShadowedBinary>,
+    pub issues: Vec<DiagnosisIssue>,
+    pub suggested_prefix: Option<String>, // set when the prefix needs sudo
+    pub fix_available: bool,
+}
+
+/// What the automated fix changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NpmPrefixFix {
+    pub previous_prefix: String,
+    pub prefix: String,
+    pub migrated: Vec<String>,
+    pub failed: Vec<CommandOutcome>,
+    pub shell_config: Option<String>, // rc file that now adds the bin directory to PATH
+    pub path_instruction: Option<String>, // set when the PATH has to be edited by hand
+    pub cleanup_command: Option<String>, // removes the copies left in the old prefix
+}
+
+/// Inspect the global prefix; `None` when npm is not installed
+pub fn check_npm_prefix() -> Option<NpmPrefixReport> {
+    let manager = NpmManager::new()?;
+    let prefix = npm_prefix()?;
+    let global_root = npm_global_root().unwrap_or_else(|| default_global_root(&prefix));
+    let bin_dir = bin_dir_for(&prefix);
+
+    let packages = manager.list_packages();
+    let prefix_writable = is_writable(if global_root.exists() {
+        &global_root
+    } else {
+        &prefix
+    });
+    let bin_on_path = analyze_path()
+        .iter()
+        .any(|entry| same_dir(Path::new(&entry.path), &bin_dir));
+
+    let shadowed: Vec<ShadowedBinary> = packages
+        .iter()
+        .flat_map(|package| {
+            package_bins(&global_root, &package.name)
+                .into_iter()
+                .map(move |bin| (package.name.clone(), bin))
+        })
+        .filter_map(|(package, name)| {
+            let resolved = which::which(&name).ok()?;
+            let resolved_dir = resolved.parent()?;
+            (!same_dir(resolved_dir, &bin_dir)).then(|| ShadowedBinary {
+                npm_path: bin_dir.join(&name).to_string_lossy().to_string(),
+                resolved_path: resolved.to_string_lossy().to_string(),
+                name,
+                package,
+            })
+        })
+        .collect();
+
+    let suggested_prefix = (!prefix_writable)
+        .then(user_prefix)
+        .flatten()
+        .map(|dir| dir.to_string_lossy().to_string());
+
+    let mut issues = Vec::new();
+    if !prefix_writable {
+        issues.push(DiagnosisIssue {
+            severity: "error".to_string(),
+            category: "npm".to_string(),
+            message: format!(
+                "Global npm prefix {} is not writable, so `npm install -g` needs sudo",
+                prefix.display()
+            ),
+            suggestion: Some(match &suggested_prefix {
+                Some(user) => format!("Move global packages to a user-level prefix ({})", user),
+                None => "Configure a user-level prefix with `npm config set prefix`".to_string(),
+            }),
+        });
+    }
+    if !bin_on_path {
+        issues.push(DiagnosisIssue {
+            severity: "warning".to_string(),
+            category: "npm".to_string(),
+            message: format!(
+                "npm global bin directory {} is not on PATH; globally installed commands cannot be run",
+                bin_dir.display()
+            ),
+            suggestion: Some(format!("Add {} to PATH in your shell config", bin_dir.display())),
+        });
+    }
+    for binary in &shadowed {
+        issues.push(DiagnosisIssue {
+            severity: "warning".to_string(),
+            category: "npm".to_string(),
+            message: format!(
+                "`{}` from npm package {} is shadowed by {}",
+                binary.name, binary.package, binary.resolved_path
+            ),
+            suggestion: Some(format!(
+                "Remove the other copy or move {} earlier in PATH",
+                bin_dir.display()
+            )),
+        });
+    }
+
+    Some(NpmPrefixReport {
+        prefix: prefix.to_string_lossy().to_string(),
+        bin_dir: bin_dir.to_string_lossy().to_string(),
+        global_root: global_root.to_string_lossy().to_string(),
+        prefix_writable,
+        bin_on_path,
+        global_packages: packages
+            .iter()
+            .map(|package| format!("{}@{}", package.name, package.version))
+            .collect(),
+        shadowed,
+        fix_available: !prefix_writable || !bin_on_path,
+        issues,
+        suggested_prefix,
+    })
+}
+
+/// Switch to a user-level prefix (when the current one needs sudo or `prefix` is given),
+/// reinstall the existing globals there and put its bin directory on PATH in the shell rc
+pub fn fix_npm_prefix(prefix: Option<&str>) -> Result<NpmPrefixFix, DevJanitorError> {
+    let report =
+        check_npm_prefix().ok_or_else(|| DevJanitorError::ToolNotFound("npm".to_string()))?;
+    let previous_prefix = PathBuf::from(&report.prefix);
+
+    let target = match prefix {
+        Some(prefix) => Some(PathBuf::from(prefix)),
+        None if !report.prefix_writable => {
+            Some(user_prefix().ok_or_else(|| "Cannot determine the home directory".to_string())?)
+        }
+        None => None,
+    };
+
+    let mut migrated = Vec::new();
+    let mut failed = Vec::new();
+    let mut cleanup_command = None;
+    let prefix = match target {
+        Some(target) if !same_dir(&target, &previous_prefix) => {
+            fs::create_dir_all(&target)?;
+            let target_str = target.to_string_lossy().to_string();
+            run_operation("npm", &["config", "set", "prefix", &target_str])?;
+
+            for spec in &report.global_packages {
+                match run_operation("npm", &["install", "-g", spec]) {
+                    Ok(_) => migrated.push(spec.clone()),
+                    Err(DevJanitorError::PackageOperation(outcome)) => failed.push(*outcome),
+                    Err(error) => failed.push(CommandOutcome {
+                        command: format!("npm install -g {}", spec),
+                        stderr: error.to_string(),
+                        ..Default::default()
+                    }),
+                }
+            }
+
+            // The old copies are root-owned; removing them is left to the user
+            if !report.prefix_writable && !migrated.is_empty() {
+                let names: Vec<&str> = migrated
+                    .iter()
+                    .map(|spec| {
+                        spec.rsplit_once('@')
+                            .map_or(spec.as_str(), |(name, _)| name)
+                    })
+                    .collect();
+                cleanup_command = Some(format!(
+                    "sudo npm uninstall -g --prefix {} {}",
+                    previous_prefix.display(),
+                    names.join(" ")
+                ));
+            }
+            target
+        }
+        _ => previous_prefix.clone(),
+    };
+
+    let bin_dir = bin_dir_for(&prefix);
+    let on_path = analyze_path()
+        .iter()
+        .any(|entry| same_dir(Path::new(&entry.path), &bin_dir));
+    // The prefix is already switched at this point, so a failed PATH edit is reported
+    // alongside the migration instead of replacing it
+    let (shell_config, path_instruction) = if on_path {
+        (None, None)
+    } else {
+        match add_to_shell_path(&bin_dir) {
+            Ok(shell_config) => (shell_config, None),
+            Err(instruction) => (None, Some(instruction)),
+        }
+    };
+
+    Ok(NpmPrefixFix {
+        previous_prefix: previous_prefix.to_string_lossy().to_string(),
+        prefix: prefix.to_string_lossy().to_string(),
+        migrated,
+        failed,
+        shell_config,
+        path_instruction,
+        cleanup_command,
+    })
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+fn user_prefix() -> Option<PathBuf> {
+    user_home_dir().map(|home| home.join(USER_PREFIX))
+}
+
+/// Global executables live in `<prefix>/bin`, or directly in the prefix on Windows
+fn bin_dir_for(prefix: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        prefix.to_path_buf()
+    } else {
+        prefix.join("bin")
+    }
+}
+
+fn default_global_root(prefix: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        prefix.join("node_modules")
+    } else {
+        prefix.join("lib").join("node_modules")
+    }
+}
+
+/// Compare directories by their resolved location, falling back to the literal path
+fn same_dir(left: &Path, right: &Path) -> bool {
+    let normalize = |path: &Path| {
+        path.canonicalize()
+            .unwrap_or_else(|_| PathBuf::from(path.to_string_lossy().trim_end_matches(['/', '\\'])))
+    };
+    normalize(left) == normalize(right)
+}
+
+/// Probe by creating (and removing) a file; permission bits alone miss ACLs and ownership
+fn is_writable(dir: &Path) -> bool {
+    let probe = dir.join(".dev-janitor-write-test");
+    match OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = fs::remove_file(&probe);
+            true
+        }
+        Err(_) => false,
+    }
+}
+
+/// Command names a global package installs, from the `bin` field of its package.json
+fn package_bins(global_root: &Path, package: &str) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(global_root.join(package).join("package.json")) else {
+        return Vec::new();
+    };
+    let Ok(manifest) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return Vec::new();
+    };
+    parse_package_bins(package, manifest.get("bin"))
+}
+
+fn parse_package_bins(package: &str, bin: Option<&serde_json::Value>) -> Vec<String> {
+    match bin {
+        // A string `bin` is installed under the unscoped package name
+        Some(serde_json::Value::String(_)) => {
+            vec![package.rsplit('/').next().unwrap_or(package).to_string()]
+        }
+        Some(serde_json::Value::Object(bins)) => bins.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Append a PATH entry for `bin_dir` to the current shell's rc file; the error is
+/// an instruction for adding it by hand
+fn add_to_shell_path(bin_dir: &Path) -> Result<Option<String>, String> {
+    if cfg!(target_os = "windows") {
+        return Err(format!(
+            "Add {} to your user PATH in System Settings > Environment Variables",
+            bin_dir.display()
+        ));
+    }
+    let manual = |reason: String| {
+        format!(
+            "Add {} to your PATH manually ({})",
+            bin_dir.display(),
+            reason
+        )
+    };
+    let home =
+        user_home_dir().ok_or_else(|| manual("cannot determine the home directory".to_string()))?;
+    let shell = env::var("SHELL").unwrap_or_default();
+    let (rc, line) = shell_rc_entry(&home, &shell, bin_dir);
+
+    let existing = fs::read_to_string(&rc).unwrap_or_default();
+    if existing.contains(&bin_dir.to_string_lossy().to_string()) {
+        return Ok(None);
+    }
+
+    if let Some(parent) = rc.parent() {
+        fs::create_dir_all(parent).map_err(|error| manual(error.to_string()))?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&rc)
+        .and_then(|mut file| {
+            writeln!(
+                file,
+                "\n# Added by Dev Janitor: user-level npm global prefix\n{}",
+                line
+            )
+        })
+        .map_err(|error| manual(error.to_string()))?;
+    Ok(Some(rc.to_string_lossy().to_string()))
+}
+
+/// The rc file of `shell` and the line that prepends `bin_dir` to PATH in it
+fn shell_rc_entry(home: &Path, shell: &str, bin_dir: &Path) -> (PathBuf, String) {
+    let bin = bin_dir.to_string_lossy();
+    let posix = format!("export PATH=\"{}:$PATH\"", bin);
+    match shell.rsplit('/').next().unwrap_or_default() {
+        "zsh" => (home.join(".zshrc"), posix),
+        "fish" => (
+            home.join(".config/fish/config.fish"),
+            format!("fish_add_path {}", bin),
+        ),
+        // macOS terminals start bash as a login shell, which reads .bash_profile
+        "bash" if cfg!(target_os = "macos") => (home.join(".bash_profile"), posix),
+        "bash" => (home.join(".bashrc"), posix),
+        _ => (home.join(".profile"), posix),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_package_bins_and_shell_entries() {
+        let bins: serde_json::Value =
+            serde_json::json!({"tsc": "bin/tsc", "tsserver": "bin/tsserver"});
+        let mut names = parse_package_bins("typescript", Some(&bins));
+        names.sort();
+        assert_eq!(names, vec!["tsc", "tsserver"]);
+        assert_eq!(
+            parse_package_bins("@angular/cli", Some(&serde_json::json!("bin/ng"))),
+            vec!["cli"]
+        );
+        assert!(parse_package_bins("left-pad", None).is_empty());
+
+        let home = Path::new("/home/dev");
+        let bin = Path::new("/home/dev/.npm-global/bin");
+        assert_eq!(
+            shell_rc_entry(home, "/usr/bin/zsh", bin),
+            (
+                home.join(".zshrc"),
+                "export PATH=\"/home/dev/.npm-global/bin:$PATH\"".to_string()
+            )
+        );
+        assert_eq!(
+            shell_rc_entry(home, "/usr/bin/fish", bin).1,
+            "fish_add_path /home/dev/.npm-global/bin"
+        );
+        assert!(same_dir(
+            Path::new("/nonexistent/prefix/bin/"),
+            Path::new("/nonexistent/prefix/bin")
+        ));
+    }
+}
@@ -0,0 +1,337 @@
+//! Per-project toolchain requirements
+//! Reads what a project declares (`engines`, `.nvmrc`, `requires-python`, `go.mod`,
+//! `global.json`, `.tool-versions`, ...) and checks it against the tools that are
+//! installed and the versions that actually run inside the project directory
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use super::DiagnosisIssue;
+use crate::detection::pins::parse_pin_file;
+use crate::detection::{active_version_in, scan_all_tools, ToolInfo};
+use crate::utils::version::satisfies_constraint;
+
+/// Files holding a single version pin, read with the detection pin parser
+const PIN_FILES: &[&str] = &[
+    ".nvmrc",
+    ".node-version",
+    ".python-version",
+    ".java-version",
+    ".go-version",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+    ".tool-versions",
+];
+
+/// `engines` keys that have a detection rule
+const ENGINE_TOOLS: &[&str] = &["node", "npm", "pnpm", "yarn", "bun", "deno"];
+
+/// One declared requirement and how the machine measures up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRequirement {
+    pub tool_id: String,
+    pub constraint: String,
+    pub file: String,
+    pub active_version: Option<String>,
+    pub installed_versions: Vec<String>,
+    pub satisfied: Option<bool>, // None for constraints like "lts/*" or "stable"
+}
+
+/// Requirements of one project directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectRequirementReport {
+    pub path: String,
+    pub requirements: Vec<ToolRequirement>,
+    pub issues: Vec<DiagnosisIssue>,
+}
+
+/// Check the toolchain `dir` declares against what is installed and active there
+pub fn check_project_requirements(dir: &Path) -> ProjectRequirementReport {
+    let declared = declared_requirements(dir);
+    let tools = if declared.is_empty() {
+        Vec::new()
+    } else {
+        scan_all_tools()
+    };
+
+    let mut requirements = Vec::new();
+    let mut issues = Vec::new();
+    for (tool_id, constraint, file) in declared {
+        let tool = tools.iter().find(|tool| tool.id == tool_id);
+        let installed_versions: Vec<String> = tool
+            .map(|tool| {
+                tool.versions
+                    .iter()
+                    .map(|version| version.version.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let active_version = tool.and_then(|_| active_version_in(&tool_id, dir));
+        let satisfied = evaluable(&constraint).then(|| {
+            active_version
+                .as_deref()
+                .is_some_and(|version| satisfies_constraint(version, &constraint))
+        });
+
+        let requirement = ToolRequirement {
+            tool_id,
+            constraint,
+            file,
+            active_version,
+            installed_versions,
+            satisfied,
+        };
+        issues.extend(requirement_issue(&requirement, tool));
+        requirements.push(requirement);
+    }
+
+    ProjectRequirementReport {
+        path: dir.to_string_lossy().to_string(),
+        requirements,
+        issues,
+    }
+}
+
+/// (tool id, constraint, file) for everything `dir` declares
+fn declared_requirements(dir: &Path) -> Vec<(String, String, String)> {
+    let mut declared = Vec::new();
+    let mut add = |tool_id: &str, constraint: String, file: &Path| {
+        declared.push((
+            tool_id.to_string(),
+            constraint,
+            file.to_string_lossy().to_string(),
+        ));
+    };
+
+    for name in PIN_FILES {
+        let file = dir.join(name);
+        if let Ok(content) = fs::read_to_string(&file) {
+            for (tool_id, version) in parse_pin_file(name, &content) {
+                add(tool_id, version, &file);
+            }
+        }
+    }
+
+    let package_json = dir.join("package.json");
+    if let Some(engines) = read_json(&package_json)
+        .as_ref()
+        .and_then(|package| package.get("engines"))
+        .and_then(Value::as_object)
+    {
+        for (name, range) in engines {
+            if let (true, Some(range)) = (ENGINE_TOOLS.contains(&name.as_str()), range.as_str()) {
+                add(name, range.to_string(), &package_json);
+            }
+        }
+    }
+
+    let pyproject = dir.join("pyproject.toml");
+    if let Some(range) = fs::read_to_string(&pyproject)
+        .ok()
+        .and_then(|content| requires_python(&content))
+    {
+        add("python", range, &pyproject);
+    }
+
+    let go_mod = dir.join("go.mod");
+    if let Some(version) = fs::read_to_string(&go_mod)
+        .ok()
+        .and_then(|content| go_directive(&content))
+    {
+        // The go directive is the minimum version
+        add("go", format!(">={}", version), &go_mod);
+    }
+
+    let global_json = dir.join("global.json");
+    if let Some(range) = read_json(&global_json).and_then(|global| dotnet_sdk_range(&global)) {
+        add("dotnet", range, &global_json);
+    }
+
+    declared
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// PEP 621 `requires-python` or Poetry's `python` dependency
+fn requires_python(content: &str) -> Option<String> {
+    let pyproject: toml::Table = content.parse().ok()?;
+    let range = pyproject
+        .get("project")
+        .and_then(|project| project.get("requires-python"))
+        .or_else(|| {
+            pyproject
+                .get("tool")?
+                .get("poetry")?
+                .get("dependencies")?
+                .get("python")
+        })?
+        .as_str()?;
+    Some(range.to_string())
+}
+
+/// `go 1.21` line of a go.mod
+fn go_directive(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let version = line.trim().strip_prefix("go ")?.trim();
+        Some(version.to_string())
+    })
+}
+
+/// global.json `sdk.version` widened by its `rollForward` policy
+fn dotnet_sdk_range(global: &Value) -> Option<String> {
+    let sdk = global.get("sdk")?;
+    let version = sdk.get("version")?.as_str()?;
+    let roll_forward = sdk
+        .get("rollForward")
+        .and_then(Value::as_str)
+        .unwrap_or("latestPatch");
+    Some(match roll_forward {
+        "disable" => version.to_string(),
+        "major" | "latestMajor" => format!(">={}", version),
+        "minor" | "latestMinor" => format!("^{}", version),
+        // patch and feature policies stay on the same major.minor
+        _ => {
+            let mut parts = version.split('.');
+            let major = parts.next()?;
+            let minor: u64 = parts.next()?.parse().ok()?;
+            format!(">={} <{}.{}", version, major, minor + 1)
+        }
+    })
+}
+
+/// Whether a constraint names concrete versions ("lts/iron", "stable" and
+/// "system" cannot be checked)
+fn evaluable(constraint: &str) -> bool {
+    let trimmed = constraint.trim_start_matches(['<', '>', '=', '^', '~', '!', ' ', 'v']);
+    constraint.trim() == "*" || trimmed.starts_with(|c: char| c.is_ascii_digit())
+}
+
+fn requirement_issue(
+    requirement: &ToolRequirement,
+    tool: Option<&ToolInfo>,
+) -> Option<DiagnosisIssue> {
+    let name = tool.map_or(requirement.tool_id.as_str(), |tool| tool.name.as_str());
+    let declared = format!(
+        "{} {} (from {})",
+        name, requirement.constraint, requirement.file
+    );
+
+    let Some(tool) = tool else {
+        return Some(DiagnosisIssue {
+            severity: "error".to_string(),
+            category: requirement.tool_id.clone(),
+            message: format!("{} is required but not installed", declared),
+            suggestion: Some(format!(
+                "Install {} {}",
+                requirement.tool_id, requirement.constraint
+            )),
+        });
+    };
+
+    let matching: Vec<&str> = requirement
+        .installed_versions
+        .iter()
+        .map(String::as_str)
+        .filter(|version| {
+            !evaluable(&requirement.constraint)
+                || satisfies_constraint(version, &requirement.constraint)
+        })
+        .collect();
+
+    let (severity, message) = match (&requirement.active_version, requirement.satisfied) {
+        (None, _) => (
+            "error",
+            format!("{} is required but fails to run in this project", declared),
+        ),
+        (Some(active), Some(false)) => (
+            if matching.is_empty() {
+                "error"
+            } else {
+                "warning"
+            },
+            format!("{} is required but {} is active", declared, active),
+        ),
+        _ => return None,
+    };
+
+    let suggestion = if matching.is_empty() {
+        format!(
+            "Install a {} version matching {}",
+            tool.name, requirement.constraint
+        )
+    } else {
+        format!(
+            "Installed {} {} matches; switch to it for this project",
+            tool.name,
+            matching.join(", ")
+        )
+    };
+
+    Some(DiagnosisIssue {
+        severity: severity.to_string(),
+        category: tool.id.clone(),
+        message,
+        suggestion: Some(suggestion),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_declared_requirements() {
+        let dir = std::env::temp_dir().join(format!("dev-janitor-reqs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(".nvmrc"), "lts/iron\n").unwrap();
+        fs::write(
+            dir.join("package.json"),
+            r#"{"engines": {"node": ">=18 <21", "vscode": "^1.80.0"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("pyproject.toml"),
+            "[project]\nname = \"app\"\nrequires-python = \">=3.9,<3.13\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("go.mod"), "module example.com/app\n\ngo 1.21\n").unwrap();
+        fs::write(
+            dir.join("global.json"),
+            r#"{"sdk": {"version": "8.0.100", "rollForward": "latestFeature"}}"#,
+        )
+        .unwrap();
+
+        let declared: Vec<(String, String)> = declared_requirements(&dir)
+            .into_iter()
+            .map(|(tool_id, constraint, _)| (tool_id, constraint))
+            .collect();
+        let expect =
+            |tool_id: &str, constraint: &str| (tool_id.to_string(), constraint.to_string());
+        assert_eq!(
+            declared,
+            vec![
+                expect("node", "lts/iron"),
+                expect("node", ">=18 <21"),
+                expect("python", ">=3.9,<3.13"),
+                expect("go", ">=1.21"),
+                expect("dotnet", ">=8.0.100 <8.1"),
+            ]
+        );
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(!evaluable("lts/iron"));
+        assert!(evaluable(">=18 <21"));
+        assert!(satisfies_constraint("3.12.1", ">=3.9,<3.13"));
+        assert!(!satisfies_constraint("3.13.0", ">=3.9,<3.13"));
+        assert!(satisfies_constraint("8.0.204", ">=8.0.100 <8.1"));
+
+        let range = requires_python("[project]\nrequires-python = \">=3.8,!=3.9.*\"\n").unwrap();
+        assert!(satisfies_constraint("3.12.1", &range));
+        assert!(!satisfies_constraint("3.9.2", &range));
+    }
+}
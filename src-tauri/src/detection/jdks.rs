@@ -0,0 +1,196 @@
+//! JDK inventory
+//! Finds JDKs in system locations, SDKMAN, Gradle toolchains, IntelliJ downloads and
+//! asdf/mise, reading vendor, version and architecture from each `release` file
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{format_size, get_dir_size};
+
+/// One installed JDK
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JdkInstall {
+    pub path: String,   // the JDK home (what JAVA_HOME should point at)
+    pub source: String, // "system", "sdkman", "gradle", "intellij", "asdf" or "mise"
+    pub vendor: Option<String>,
+    pub version: Option<String>,
+    pub arch: Option<String>,
+    pub size: u64,
+    pub size_display: String,
+}
+
+/// Fields of a JDK `release` file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JdkRelease {
+    pub vendor: Option<String>,
+    pub version: Option<String>,
+    pub arch: Option<String>,
+}
+
+/// Every JDK found in the known install locations, measured in parallel
+pub fn discover_jdks() -> Vec<JdkInstall> {
+    let mut roots: Vec<(&str, PathBuf)> = vec![
+        ("system", PathBuf::from("/usr/lib/jvm")),
+        ("system", PathBuf::from("/usr/lib64/jvm")),
+        ("system", PathBuf::from("/Library/Java/JavaVirtualMachines")),
+    ];
+    if let Some(home) = user_home_dir() {
+        let sdkman = env::var_os("SDKMAN_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".sdkman"));
+        let gradle = env::var_os("GRADLE_USER_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".gradle"));
+        let asdf = env::var_os("ASDF_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".asdf"));
+        let mise = env::var_os("MISE_DATA_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share/mise"));
+        roots.extend([
+            ("sdkman", sdkman.join("candidates/java")),
+            ("gradle", gradle.join("jdks")),
+            ("intellij", home.join(".jdks")),
+            ("asdf", asdf.join("installs/java")),
+            ("mise", mise.join("installs/java")),
+        ]);
+    }
+
+    // Aliases (default-java, SDKMAN's current, mise's "21") point at JDKs listed anyway
+    let mut seen = HashSet::new();
+    let mut homes: Vec<(&str, PathBuf)> = Vec::new();
+    for (source, root) in roots {
+        let Ok(entries) = fs::read_dir(&root) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false);
+            if !is_dir {
+                continue;
+            }
+            if let Some(home) = jdk_home(&entry.path()) {
+                let canonical = fs::canonicalize(&home).unwrap_or_else(|_| home.clone());
+                if seen.insert(canonical) {
+                    homes.push((source, home));
+                }
+            }
+        }
+    }
+
+    let mut jdks: Vec<JdkInstall> = homes
+        .into_par_iter()
+        .map(|(source, home)| {
+            let release = read_release(&home);
+            let size = get_dir_size(&home);
+            JdkInstall {
+                path: home.to_string_lossy().to_string(),
+                source: source.to_string(),
+                vendor: release.vendor,
+                version: release.version,
+                arch: release.arch,
+                size,
+                size_display: format_size(size),
+            }
+        })
+        .collect();
+    jdks.sort_by(|a, b| a.path.cmp(&b.path));
+    jdks
+}
+
+/// The JDK home inside `dir`: the directory itself, a macOS bundle's `Contents/Home`,
+/// or one level down (Gradle unpacks into `<toolchain>/<jdk-dir>`)
+pub fn jdk_home(dir: &Path) -> Option<PathBuf> {
+    let direct = [dir.to_path_buf(), dir.join("Contents/Home")];
+    if let Some(home) = direct.into_iter().find(|home| is_jdk_home(home)) {
+        return Some(home);
+    }
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .flat_map(|child| [child.join("Contents/Home"), child])
+        .find(|home| is_jdk_home(home))
+}
+
+/// A directory with `bin/java` and a `release` file
+pub fn is_jdk_home(dir: &Path) -> bool {
+    let java = if cfg!(target_os = "windows") {
+        "java.exe"
+    } else {
+        "java"
+    };
+    dir.join("bin").join(java).is_file() && dir.join("release").is_file()
+}
+
+/// Read `IMPLEMENTOR`, `JAVA_VERSION` and `OS_ARCH` from `<home>/release`
+pub fn read_release(home: &Path) -> JdkRelease {
+    fs::read_to_string(home.join("release"))
+        .map(|content| parse_release(&content))
+        .unwrap_or_default()
+}
+
+fn parse_release(content: &str) -> JdkRelease {
+    let mut release = JdkRelease::default();
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = Some(value.trim().trim_matches('"').to_string()).filter(|v| !v.is_empty());
+        match key.trim() {
+            "IMPLEMENTOR" => release.vendor = value,
+            "JAVA_VERSION" => release.version = value,
+            "OS_ARCH" => release.arch = value,
+            _ => {}
+        }
+    }
+    release
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_release_and_finds_nested_homes() {
+        assert_eq!(
+            parse_release(
+                "IMPLEMENTOR=\"Eclipse Adoptium\"\nJAVA_VERSION=\"17.0.9\"\nOS_ARCH=\"x86_64\"\nMODULES=\"java.base\"\n"
+            ),
+            JdkRelease {
+                vendor: Some("Eclipse Adoptium".to_string()),
+                version: Some("17.0.9".to_string()),
+                arch: Some("x86_64".to_string()),
+            }
+        );
+
+        let dir = env::temp_dir().join(format!("dev-janitor-jdks-{}", std::process::id()));
+        let home = dir.join("eclipse_adoptium-17-amd64-linux/jdk-17.0.9+9");
+        fs::create_dir_all(home.join("bin")).unwrap();
+        fs::write(
+            home.join("bin").join(if cfg!(target_os = "windows") {
+                "java.exe"
+            } else {
+                "java"
+            }),
+            "",
+        )
+        .unwrap();
+        fs::write(home.join("release"), "JAVA_VERSION=\"17.0.9\"\n").unwrap();
+        assert_eq!(
+            jdk_home(&dir.join("eclipse_adoptium-17-amd64-linux")),
+            Some(home)
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,21 +1,58 @@
 //! Tool detection engine for Dev Janitor v2
-//! Supports 39+ development tools with multi-version detection
+//! Supports 39+ development tools with multi-version detection, including versions
+//! installed by nvm, fnm, volta, pyenv, goenv, asdf, mise, rustup and SDKMAN
+
+pub mod jdks;
+pub mod pins;
+pub mod probe;
+pub mod rules;
+pub mod shims;
+pub mod uninstall;
+pub mod version_managers;
 
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::Duration;
 
-use crate::ai_tools::ai_tools;
-use crate::utils::command::command_output_with_timeout;
+use crate::cache::format_size;
+use crate::utils::command::command_output_in_dir;
+use pins::{pin_matches, ProjectPin};
+use probe::ProbeCache;
+use regex::Regex;
+use rules::{load_tool_rules, ToolRule};
+use shims::resolve_shim;
+use version_managers::{discover_managed_versions, ManagedVersion};
 
 /// Represents a detected tool version
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ToolVersion {
     pub version: String,
     pub path: String,
     pub is_active: bool,
+    #[serde(default)]
+    pub manager: Option<String>, // version manager that installed it (nvm, pyenv, rustup, ...)
+    #[serde(default)]
+    pub install_path: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub size_display: Option<String>,
+    #[serde(default)]
+    pub is_default: bool, // the manager's global default
+    #[serde(default)]
+    pub pinned_by: Vec<String>, // project files that request this version
+    #[serde(default)]
+    pub resolved_path: Option<String>, // symlink target when it differs from `path`
+    #[serde(default)]
+    pub shadowed_by: Option<String>, // earlier PATH entry that runs instead of this one
+    #[serde(default)]
+    pub shim_path: Option<String>, // the shim on PATH when `path` is the binary it selects
+    #[serde(default)]
+    pub config_file: Option<String>, // .python-version, .tool-versions, ... that chose it
 }
 
 /// Represents a detected development tool
@@ -28,402 +65,137 @@ pub struct ToolInfo {
     pub status: String, // "installed", "not_in_path", "multiple_versions"
 }
 
-/// Tool detection rule
-#[derive(Debug, Clone)]
-struct ToolRule {
-    id: &'static str,
-    name: &'static str,
-    category: &'static str,
-    commands: &'static [&'static str],
-    version_args: &'static [&'static str],
-    version_regex: Option<&'static str>,
-}
-
 /// Get all tool detection rules
 fn get_tool_rules() -> Vec<ToolRule> {
-    let mut rules = vec![
-        // === Runtimes ===
-        ToolRule {
-            id: "node",
-            name: "Node.js",
-            category: "runtime",
-            commands: &["node"],
-            version_args: &["--version"],
-            version_regex: Some(r"v?(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "python",
-            name: "Python",
-            category: "runtime",
-            commands: &["python", "python3", "py"],
-            version_args: &["--version"],
-            version_regex: Some(r"Python (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "java",
-            name: "Java",
-            category: "runtime",
-            commands: &["java"],
-            version_args: &["-version"],
-            version_regex: Some(r#"version "(\d+[\.\d+]*)""#),
-        },
-        ToolRule {
-            id: "go",
-            name: "Go",
-            category: "runtime",
-            commands: &["go"],
-            version_args: &["version"],
-            version_regex: Some(r"go(\d+\.\d+\.?\d*)"),
-        },
-        ToolRule {
-            id: "rust",
-            name: "Rust",
-            category: "runtime",
-            commands: &["rustc"],
-            version_args: &["--version"],
-            version_regex: Some(r"rustc (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "ruby",
-            name: "Ruby",
-            category: "runtime",
-            commands: &["ruby"],
-            version_args: &["--version"],
-            version_regex: Some(r"ruby (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "php",
-            name: "PHP",
-            category: "runtime",
-            commands: &["php"],
-            version_args: &["--version"],
-            version_regex: Some(r"PHP (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "dotnet",
-            name: ".NET",
-            category: "runtime",
-            commands: &["dotnet"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.?\d*)"),
-        },
-        ToolRule {
-            id: "deno",
-            name: "Deno",
-            category: "runtime",
-            commands: &["deno"],
-            version_args: &["--version"],
-            version_regex: Some(r"deno (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "bun",
-            name: "Bun",
-            category: "runtime",
-            commands: &["bun"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        // === Package Managers ===
-        ToolRule {
-            id: "npm",
-            name: "npm",
-            category: "package_manager",
-            commands: &["npm"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "pnpm",
-            name: "pnpm",
-            category: "package_manager",
-            commands: &["pnpm"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "yarn",
-            name: "Yarn",
-            category: "package_manager",
-            commands: &["yarn"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "pip",
-            name: "pip",
-            category: "package_manager",
-            commands: &["pip", "pip3"],
-            version_args: &["--version"],
-            version_regex: Some(r"pip (\d+\.\d+\.?\d*)"),
-        },
-        ToolRule {
-            id: "cargo",
-            name: "Cargo",
-            category: "package_manager",
-            commands: &["cargo"],
-            version_args: &["--version"],
-            version_regex: Some(r"cargo (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "composer",
-            name: "Composer",
-            category: "package_manager",
-            commands: &["composer"],
-            version_args: &["--version"],
-            version_regex: Some(r"Composer version (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "maven",
-            name: "Maven",
-            category: "package_manager",
-            commands: &["mvn"],
-            version_args: &["--version"],
-            version_regex: Some(r"Apache Maven (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "gradle",
-            name: "Gradle",
-            category: "package_manager",
-            commands: &["gradle"],
-            version_args: &["--version"],
-            version_regex: Some(r"Gradle (\d+\.\d+\.?\d*)"),
-        },
-        ToolRule {
-            id: "uv",
-            name: "uv",
-            category: "package_manager",
-            commands: &["uv"],
-            version_args: &["--version"],
-            version_regex: Some(r"uv (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "pipx",
-            name: "pipx",
-            category: "package_manager",
-            commands: &["pipx"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "poetry",
-            name: "Poetry",
-            category: "package_manager",
-            commands: &["poetry"],
-            version_args: &["--version"],
-            version_regex: Some(r"Poetry \(version (\d+\.\d+\.\d+)\)"),
-        },
-        // === Version Managers ===
-        ToolRule {
-            id: "nvm",
-            name: "nvm",
-            category: "version_manager",
-            commands: &["nvm"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "pyenv",
-            name: "pyenv",
-            category: "version_manager",
-            commands: &["pyenv"],
-            version_args: &["--version"],
-            version_regex: Some(r"pyenv (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "rustup",
-            name: "rustup",
-            category: "version_manager",
-            commands: &["rustup"],
-            version_args: &["--version"],
-            version_regex: Some(r"rustup (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "sdkman",
-            name: "SDKMAN",
-            category: "version_manager",
-            commands: &["sdk"],
-            version_args: &["version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        // === Build Tools ===
-        ToolRule {
-            id: "cmake",
-            name: "CMake",
-            category: "build_tool",
-            commands: &["cmake"],
-            version_args: &["--version"],
-            version_regex: Some(r"cmake version (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "make",
-            name: "Make",
-            category: "build_tool",
-            commands: &["make"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "ninja",
-            name: "Ninja",
-            category: "build_tool",
-            commands: &["ninja"],
-            version_args: &["--version"],
-            version_regex: Some(r"(\d+\.\d+\.\d+)"),
-        },
-        // === Version Control ===
-        ToolRule {
-            id: "git",
-            name: "Git",
-            category: "version_control",
-            commands: &["git"],
-            version_args: &["--version"],
-            version_regex: Some(r"git version (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "svn",
-            name: "SVN",
-            category: "version_control",
-            commands: &["svn"],
-            version_args: &["--version"],
-            version_regex: Some(r"svn, version (\d+\.\d+\.\d+)"),
-        },
-        // === Containers ===
-        ToolRule {
-            id: "docker",
-            name: "Docker",
-            category: "container",
-            commands: &["docker"],
-            version_args: &["--version"],
-            version_regex: Some(r"Docker version (\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "kubectl",
-            name: "kubectl",
-            category: "container",
-            commands: &["kubectl"],
-            version_args: &["version", "--client", "--output=yaml"],
-            version_regex: Some(r"gitVersion:\s*v(\d+\.\d+\.\d+)"),
-        },
-        ToolRule {
-            id: "podman",
-            name: "Podman",
-            category: "container",
-            commands: &["podman"],
-            version_args: &["--version"],
-            version_regex: Some(r"podman version (\d+\.\d+\.\d+)"),
-        },
-    ];
-
-    rules.extend(ai_tools().iter().map(|tool| ToolRule {
-        id: tool.id,
-        name: tool.name,
-        category: "ai_cli",
-        commands: tool.commands,
-        version_args: tool.version_args,
-        version_regex: tool.version_regex,
-    }));
-
-    rules
+    load_tool_rules().rules
 }
 
-/// Execute a command and capture output
-fn execute_command(cmd: &str, args: &[&str]) -> Option<(String, String)> {
-    let output = command_output_with_timeout(cmd, args, Duration::from_secs(6)).ok()?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-
-    Some((stdout, stderr))
+/// Every match for `cmd` on PATH, in PATH order
+fn find_command_paths(cmd: &str) -> Vec<PathBuf> {
+    which::which_all(cmd)
+        .map(|paths| paths.collect())
+        .unwrap_or_default()
 }
 
-/// Find command path using which
-fn find_command_path(cmd: &str) -> Option<PathBuf> {
-    which::which(cmd).ok()
+/// Extract version from output using the rule's precompiled regex
+fn extract_version(output: &str, regex: Option<&Regex>) -> Option<String> {
+    // Try to find any version-like pattern when the rule has none
+    static FALLBACK: OnceLock<Regex> = OnceLock::new();
+    let regex = regex.unwrap_or_else(|| {
+        FALLBACK.get_or_init(|| Regex::new(r"(\d+\.\d+\.?\d*)").expect("valid fallback regex"))
+    });
+    let captures = regex.captures(output)?;
+    captures.get(1).map(|m| m.as_str().to_string())
 }
 
-/// Extract version from output using regex
-fn extract_version(output: &str, pattern: Option<&str>) -> Option<String> {
-    use regex::Regex;
-
-    if let Some(pattern) = pattern {
-        let re = Regex::new(pattern).ok()?;
-        let captures = re.captures(output)?;
-        captures.get(1).map(|m| m.as_str().to_string())
-    } else {
-        // Try to find any version-like pattern
-        let re = Regex::new(r"(\d+\.\d+\.?\d*)").ok()?;
-        let captures = re.captures(output)?;
-        captures.get(1).map(|m| m.as_str().to_string())
-    }
+/// A binary to probe for one tool
+struct Candidate {
+    binary: PathBuf,   // what to run
+    path: String,      // what to report
+    resolved: PathBuf, // the real file after following symlinks
+    on_path: bool,
+    shadowed_by: Option<String>,
+    shim_path: Option<String>,
+    config_file: Option<String>,
 }
 
-/// Detect a single tool
-fn detect_tool(rule: &ToolRule) -> Option<ToolInfo> {
-    let mut versions: Vec<ToolVersion> = Vec::new();
-    let mut found_paths: HashMap<String, bool> = HashMap::new();
-
-    for cmd in rule.commands {
-        // Try to find the command
-        if let Some(path) = find_command_path(cmd) {
+/// Detect a single tool, probing every distinct binary in parallel; shims are resolved
+/// as seen from `project_root`
+fn detect_tool(
+    rule: &ToolRule,
+    cache: &ProbeCache,
+    project_root: Option<&Path>,
+) -> Option<ToolInfo> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    // Real binaries already listed, so /bin/python3 and /usr/bin/python3 on a merged
+    // /usr or python -> python3.12 symlinks are reported once
+    let mut found_paths: HashSet<PathBuf> = HashSet::new();
+
+    for cmd in &rule.commands {
+        let mut winner: Option<String> = None;
+        for path in find_command_paths(cmd) {
             let path_str = path.to_string_lossy().to_string();
-
-            // Skip if we already found this path
-            if found_paths.contains_key(&path_str) {
-                continue;
-            }
-            found_paths.insert(path_str.clone(), true);
-
-            // Try to get version
-            if let Some((stdout, stderr)) = execute_command(cmd, rule.version_args) {
-                let output = if stdout.trim().is_empty() {
-                    &stderr
-                } else {
-                    &stdout
-                };
-                let version = extract_version(output, rule.version_regex)
-                    .unwrap_or_else(|| "unknown".to_string());
-
-                versions.push(ToolVersion {
-                    version,
-                    path: path_str,
-                    is_active: versions.is_empty(), // First found is active
+            let shim = resolve_shim(&path, cmd, project_root);
+            let target = shim.as_ref().map_or(&path, |shim| &shim.binary);
+            let resolved = fs::canonicalize(target).unwrap_or_else(|_| target.clone());
+            if found_paths.insert(resolved.clone()) {
+                candidates.push(Candidate {
+                    // Homebrew wrappers set up the environment, so they still run as-is
+                    binary: match &shim {
+                        Some(shim) if shim.manager != "homebrew" => shim.binary.clone(),
+                        _ => path.clone(),
+                    },
+                    path: target.to_string_lossy().to_string(),
+                    resolved,
+                    on_path: true,
+                    shadowed_by: winner.clone(),
+                    shim_path: shim.as_ref().map(|_| path_str.clone()),
+                    config_file: shim
+                        .and_then(|shim| shim.config_file)
+                        .map(|file| file.to_string_lossy().to_string()),
                 });
             }
+            // Later matches for the same command never run from a plain `cmd`
+            winner.get_or_insert(path_str);
         }
     }
 
-    // Check common installation paths for multiple versions
-    #[cfg(target_os = "windows")]
-    {
-        let extra_paths = get_windows_extra_paths(rule.id);
-        for extra_path in extra_paths {
-            if extra_path.exists()
-                && !found_paths.contains_key(&extra_path.to_string_lossy().to_string())
-            {
-                let cmd = extra_path.join(rule.commands[0]);
-                if cmd.exists() {
-                    if let Some((stdout, stderr)) =
-                        execute_command(&cmd.to_string_lossy(), rule.version_args)
-                    {
-                        let output = if stdout.trim().is_empty() {
-                            &stderr
-                        } else {
-                            &stdout
-                        };
-                        let version = extract_version(output, rule.version_regex)
-                            .unwrap_or_else(|| "unknown".to_string());
-
-                        versions.push(ToolVersion {
-                            version,
-                            path: extra_path.to_string_lossy().to_string(),
-                            is_active: false,
-                        });
-                    }
-                }
-            }
+    // Check the rule's extra installation paths for multiple versions
+    for extra_path in rule.extra_paths() {
+        let binary = extra_path.join(&rule.commands[0]);
+        if !binary.exists() {
+            continue;
+        }
+        let resolved = fs::canonicalize(&binary).unwrap_or_else(|_| binary.clone());
+        if found_paths.insert(resolved.clone()) {
+            candidates.push(Candidate {
+                binary,
+                path: extra_path.to_string_lossy().to_string(),
+                resolved,
+                on_path: false,
+                shadowed_by: None,
+                shim_path: None,
+                config_file: None,
+            });
         }
     }
 
+    // Run the resolved binary itself so a shim earlier on PATH cannot answer for it
+    let probed: Vec<Option<String>> = candidates
+        .par_iter()
+        .map(|candidate| {
+            let (stdout, stderr) = cache.probe(&candidate.binary, &rule.version_args)?;
+            let output = if stdout.trim().is_empty() {
+                &stderr
+            } else {
+                &stdout
+            };
+            Some(
+                extract_version(output, rule.version_regex.as_ref())
+                    .unwrap_or_else(|| "unknown".to_string()),
+            )
+        })
+        .collect();
+
+    let mut versions: Vec<ToolVersion> = Vec::new();
+    for (candidate, version) in candidates.into_iter().zip(probed) {
+        let Some(version) = version else {
+            continue;
+        };
+        let resolved_path = candidate.resolved.to_string_lossy().to_string();
+        versions.push(ToolVersion {
+            version,
+            resolved_path: (resolved_path != candidate.path).then_some(resolved_path),
+            path: candidate.path,
+            // First found on PATH is active
+            is_active: candidate.on_path && versions.is_empty(),
+            shadowed_by: candidate.shadowed_by,
+            shim_path: candidate.shim_path,
+            config_file: candidate.config_file,
+            ..Default::default()
+        });
+    }
+
     if versions.is_empty() {
         return None;
     }
@@ -435,91 +207,153 @@ fn detect_tool(rule: &ToolRule) -> Option<ToolInfo> {
     };
 
     Some(ToolInfo {
-        id: rule.id.to_string(),
-        name: rule.name.to_string(),
-        category: rule.category.to_string(),
+        id: rule.id.clone(),
+        name: rule.name.clone(),
+        category: rule.category.clone(),
         versions,
         status,
     })
 }
 
-/// Get extra paths to check on Windows for multi-version detection
-#[cfg(target_os = "windows")]
-fn get_windows_extra_paths(tool_id: &str) -> Vec<PathBuf> {
-    use std::env;
-
-    let mut paths = Vec::new();
-    let home = env::var("USERPROFILE").unwrap_or_default();
-    let program_files =
-        env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
-    let local_app_data = env::var("LOCALAPPDATA").unwrap_or_default();
-
-    match tool_id {
-        "python" => {
-            // Check Python launcher paths
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python39",
-                local_app_data
-            )));
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python310",
-                local_app_data
-            )));
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python311",
-                local_app_data
-            )));
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python312",
-                local_app_data
-            )));
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python313",
-                local_app_data
-            )));
-            paths.push(PathBuf::from(format!(
-                "{}\\Python\\Python314",
-                local_app_data
-            )));
-            // Anaconda/Miniconda
-            paths.push(PathBuf::from(format!("{}\\Anaconda3", home)));
-            paths.push(PathBuf::from(format!("{}\\Miniconda3", home)));
-            // pyenv
-            paths.push(PathBuf::from(format!(
-                "{}\\.pyenv\\pyenv-win\\versions",
-                home
-            )));
-        }
-        "node" => {
-            // nvm for Windows
-            paths.push(PathBuf::from(format!(
-                "{}\\nvm",
-                env::var("NVM_HOME").unwrap_or_else(|_| format!("{}\\nvm", local_app_data))
-            )));
-        }
-        "java" => {
-            // Multiple Java installations
-            let java_dir = PathBuf::from(format!("{}\\Java", program_files));
-            if java_dir.exists() {
-                if let Ok(entries) = std::fs::read_dir(&java_dir) {
-                    for entry in entries.flatten() {
-                        paths.push(entry.path().join("bin"));
-                    }
-                }
+/// Scan for all development tools
+pub fn scan_all_tools() -> Vec<ToolInfo> {
+    scan_all_tools_in(None)
+}
+
+/// Scan for all development tools, resolving version manager shims as they would run
+/// in `project_root`
+pub fn scan_all_tools_in(project_root: Option<&Path>) -> Vec<ToolInfo> {
+    let rules = get_tool_rules();
+    let cache = ProbeCache::load();
+
+    // Use parallel scanning for better performance
+    let (mut tools, managed) = rayon::join(
+        || {
+            rules
+                .par_iter()
+                .filter_map(|rule| detect_tool(rule, &cache, project_root))
+                .collect::<Vec<_>>()
+        },
+        || {
+            if cfg!(target_os = "windows") {
+                Vec::new()
+            } else {
+                discover_managed_versions()
+            }
+        },
+    );
+    cache.save();
+    merge_managed_versions(&mut tools, &rules, managed);
+    tools
+}
+
+/// Tag PATH versions that live inside a version manager's install directory and add
+/// the manager's other versions; tools only a manager knows about are "not_in_path"
+fn merge_managed_versions(
+    tools: &mut Vec<ToolInfo>,
+    rules: &[ToolRule],
+    managed: Vec<ManagedVersion>,
+) {
+    for version in managed {
+        let index = match tools.iter().position(|tool| tool.id == version.tool_id) {
+            Some(index) => index,
+            None => {
+                let Some(rule) = rules.iter().find(|rule| rule.id == version.tool_id) else {
+                    continue;
+                };
+                tools.push(ToolInfo {
+                    id: rule.id.to_string(),
+                    name: rule.name.to_string(),
+                    category: rule.category.to_string(),
+                    versions: Vec::new(),
+                    status: "not_in_path".to_string(),
+                });
+                tools.len() - 1
+            }
+        };
+        let tool = &mut tools[index];
+
+        let install_dir = version
+            .install_dir
+            .canonicalize()
+            .unwrap_or_else(|_| version.install_dir.clone());
+        let existing = tool.versions.iter_mut().find(|existing| {
+            Path::new(&existing.path)
+                .canonicalize()
+                .map(|path| path.starts_with(&install_dir))
+                .unwrap_or(false)
+        });
+
+        let entry = match existing {
+            Some(existing) => existing,
+            None => {
+                tool.versions.push(ToolVersion {
+                    version: version.version.clone(),
+                    path: version
+                        .executable
+                        .as_ref()
+                        .unwrap_or(&version.install_dir)
+                        .to_string_lossy()
+                        .to_string(),
+                    ..Default::default()
+                });
+                tool.versions.last_mut().expect("version was just pushed")
             }
+        };
+        entry.manager = Some(version.manager.to_string());
+        entry.install_path = Some(version.install_dir.to_string_lossy().to_string());
+        entry.size = Some(version.size);
+        entry.size_display = Some(format_size(version.size));
+        entry.is_default = version.is_default;
+
+        if tool.status == "installed" && tool.versions.len() > 1 {
+            tool.status = "multiple_versions".to_string();
         }
-        _ => {}
     }
+}
 
-    paths
+/// Record which project files pin each detected version
+pub fn apply_project_pins(tools: &mut [ToolInfo], pins: &[ProjectPin]) {
+    for tool in tools {
+        for version in &mut tool.versions {
+            version.pinned_by = pins
+                .iter()
+                .filter(|pin| pin.tool_id == tool.id && pin_matches(&pin.version, &version.version))
+                .map(|pin| pin.file.clone())
+                .collect();
+        }
+    }
 }
 
-/// Scan for all development tools
-pub fn scan_all_tools() -> Vec<ToolInfo> {
-    let rules = get_tool_rules();
+/// Version of `id` that runs when invoked from `dir`, where pyenv, asdf, mise, volta
+/// and global.json pick per-directory versions; `None` if it fails to start there
+pub fn active_version_in(id: &str, dir: &Path) -> Option<String> {
+    let rule = get_tool_rules().into_iter().find(|rule| rule.id == id)?;
+    rule.commands.iter().find_map(|cmd| {
+        let output =
+            command_output_in_dir(cmd, &rule.version_args, Some(dir), Duration::from_secs(6))
+                .ok()
+                .filter(|output| output.status.success())?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let text = if stdout.trim().is_empty() {
+            &stderr
+        } else {
+            &stdout
+        };
+        extract_version(text, rule.version_regex.as_ref())
+    })
+}
 
-    // Use parallel scanning for better performance
-    rules.par_iter().filter_map(detect_tool).collect()
+/// Detect a single tool by its rule id (e.g. "php")
+pub fn detect_tool_by_id(id: &str) -> Option<ToolInfo> {
+    let cache = ProbeCache::load();
+    let tool = get_tool_rules()
+        .iter()
+        .find(|rule| rule.id == id)
+        .and_then(|rule| detect_tool(rule, &cache, None));
+    cache.save();
+    tool
 }
 
 #[cfg(test)]
@@ -529,7 +363,8 @@ mod tests {
     #[test]
     fn test_extract_version() {
         let output = "node v20.11.0";
-        let version = extract_version(output, Some(r"v?(\d+\.\d+\.\d+)"));
+        let regex = Regex::new(r"v?(\d+\.\d+\.\d+)").ok();
+        let version = extract_version(output, regex.as_ref());
         assert_eq!(version, Some("20.11.0".to_string()));
     }
 
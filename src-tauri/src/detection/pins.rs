@@ -0,0 +1,204 @@
+//! Project version pins
+//! Finds `.nvmrc`, `.python-version`, `.java-version`, `rust-toolchain.toml`,
+//! `.tool-versions`, `mise.toml` and similar files under a project root so pinned runtime versions
+//! are never removed
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::version_managers::{parse_mise_tools, parse_tool_versions, plugin_tool_id};
+
+/// How deep to look for pin files under a project root
+pub const PIN_SCAN_DEPTH: usize = 6;
+
+/// Directories that never contain project pins worth reporting
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "venv", "dist", "build"];
+
+/// A runtime version requested by a project file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectPin {
+    pub tool_id: String,
+    pub version: String, // as written, e.g. "20", "lts/iron", "stable"
+    pub file: String,
+}
+
+/// Where to look for pins: the given project root, or the home directory so that
+/// callers without a configured root still see every project's pins
+pub fn pin_scan_root(root: Option<&Path>) -> Option<PathBuf> {
+    root.map(Path::to_path_buf).or_else(|| {
+        env::var("HOME")
+            .or_else(|_| env::var("USERPROFILE"))
+            .ok()
+            .map(PathBuf::from)
+    })
+}
+
+/// Walk `root` and collect every version pin file
+pub fn find_project_pins(root: &Path, max_depth: usize) -> Vec<ProjectPin> {
+    WalkDir::new(root)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !(name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .flat_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let file = entry.path().to_string_lossy().to_string();
+            fs::read_to_string(entry.path())
+                .map(|content| parse_pin_file(&file_name, &content))
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |(tool_id, version)| ProjectPin {
+                    tool_id: tool_id.to_string(),
+                    version,
+                    file: file.clone(),
+                })
+        })
+        .collect()
+}
+
+/// (tool id, requested version) pairs from a pin file, empty for unrelated files
+pub fn parse_pin_file(file_name: &str, content: &str) -> Vec<(&'static str, String)> {
+    let first_line = || {
+        content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+    };
+
+    match file_name {
+        ".nvmrc" | ".node-version" => first_line()
+            .map(|version| vec![("node", version.trim_start_matches('v').to_string())])
+            .unwrap_or_default(),
+        // pyenv allows several versions, one per line
+        ".python-version" => content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|version| ("python", version.to_string()))
+            .collect(),
+        ".go-version" => first_line()
+            .map(|version| vec![("go", version)])
+            .unwrap_or_default(),
+        // jenv
+        ".java-version" => first_line()
+            .map(|version| vec![("java", version)])
+            .unwrap_or_default(),
+        "rust-toolchain" | "rust-toolchain.toml" => content
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|table| {
+                table
+                    .get("toolchain")?
+                    .get("channel")?
+                    .as_str()
+                    .map(str::to_string)
+            })
+            // The legacy `rust-toolchain` file may hold just the channel name
+            .or_else(|| first_line().filter(|line| !line.contains('=') && !line.starts_with('[')))
+            .map(|channel| vec![("rust", channel)])
+            .unwrap_or_default(),
+        ".tool-versions" => parse_tool_versions(content)
+            .into_iter()
+            .filter_map(|(plugin, versions)| {
+                Some((plugin_tool_id(&plugin)?, versions.into_iter().next()?))
+            })
+            .collect(),
+        // mise installs every version listed for a tool
+        "mise.toml" | ".mise.toml" | "mise.local.toml" | ".mise.local.toml" => {
+            parse_mise_tools(content)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|(plugin, versions)| Some((plugin_tool_id(&plugin)?, versions)))
+                .flat_map(|(tool_id, versions)| {
+                    versions.into_iter().map(move |version| (tool_id, version))
+                })
+                .collect()
+        }
+        // SDKMAN: "java=21.0.2-tem"
+        ".sdkmanrc" => content
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .filter_map(|(candidate, version)| {
+                let tool_id = match candidate.trim() {
+                    "java" => "java",
+                    "maven" => "maven",
+                    "gradle" => "gradle",
+                    _ => return None,
+                };
+                Some((tool_id, version.trim().to_string()))
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether a pin such as "20", "3.12" or "stable" selects this installed version
+pub fn pin_matches(pin: &str, version: &str) -> bool {
+    let pin = pin.trim().trim_start_matches('v');
+    let version = version.trim_start_matches('v');
+    version == pin
+        || version.starts_with(&format!("{}.", pin))
+        // rustup toolchains carry the host triple: "stable-x86_64-unknown-linux-gnu"
+        || version.starts_with(&format!("{}-", pin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_pin_files() {
+        assert_eq!(
+            parse_pin_file(".nvmrc", "v20.11.0\n"),
+            vec![("node", "20.11.0".to_string())]
+        );
+        assert_eq!(
+            parse_pin_file(".python-version", "3.12.1\n3.11\n"),
+            vec![
+                ("python", "3.12.1".to_string()),
+                ("python", "3.11".to_string())
+            ]
+        );
+        assert_eq!(
+            parse_pin_file(
+                "rust-toolchain.toml",
+                "[toolchain]\nchannel = \"1.75.0\"\ncomponents = [\"clippy\"]\n"
+            ),
+            vec![("rust", "1.75.0".to_string())]
+        );
+        assert_eq!(
+            parse_pin_file("rust-toolchain", "nightly-2024-01-01\n"),
+            vec![("rust", "nightly-2024-01-01".to_string())]
+        );
+        assert_eq!(
+            parse_pin_file(".tool-versions", "nodejs 18.19.0\nterraform 1.6.0\n"),
+            vec![("node", "18.19.0".to_string())]
+        );
+        assert_eq!(
+            parse_pin_file(
+                ".mise.toml",
+                "[tools]\nnode = [\"20\", \"18\"]\nterraform = \"1.6\"\n"
+            ),
+            vec![("node", "20".to_string()), ("node", "18".to_string())]
+        );
+        assert_eq!(
+            parse_pin_file(".sdkmanrc", "java=21.0.2-tem\n"),
+            vec![("java", "21.0.2-tem".to_string())]
+        );
+
+        assert!(pin_matches("20", "20.11.0"));
+        assert!(pin_matches("3.12", "3.12.1"));
+        assert!(pin_matches("stable", "stable-x86_64-unknown-linux-gnu"));
+        assert!(!pin_matches("3.1", "3.12.1"));
+    }
+}
@@ -0,0 +1,207 @@
+//! Version probing with a persisted cache
+//! A binary whose path, size and mtime are unchanged since the last scan is not
+//! executed again; its previous output is reused
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, UNIX_EPOCH};
+
+use crate::utils::command::command_output_with_timeout_vec;
+use crate::utils::storage::{app_data_file, load_json, save_json};
+
+const PROBE_CACHE_FILE: &str = "probe_cache.json";
+const PROBE_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Launchers that pick the real binary at run time; their own size and mtime say
+/// nothing about the version they report, so they are always probed
+/// (`py` is the Windows Python launcher)
+const SHIM_BINARIES: &[&str] = &["volta-shim", "mise", "proto-shim", "py"];
+
+/// Output of one probe, keyed by the probed path
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ProbeEntry {
+    size: u64,
+    modified: u64, // seconds since the Unix epoch
+    args: Vec<String>,
+    stdout: String,
+    stderr: String,
+}
+
+/// Probe results shared by all detection threads during one scan
+#[derive(Debug, Default)]
+pub struct ProbeCache {
+    path: Option<PathBuf>,
+    entries: Mutex<HashMap<String, ProbeEntry>>,
+    dirty: AtomicBool,
+}
+
+impl ProbeCache {
+    /// Load the cache from the app data directory (empty when missing)
+    pub fn load() -> Self {
+        let path = app_data_file(PROBE_CACHE_FILE);
+        let entries = path
+            .as_deref()
+            .and_then(load_json::<HashMap<String, ProbeEntry>>)
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Run `path args` and return (stdout, stderr), reusing the cached output when the
+    /// binary has not changed
+    pub fn probe(&self, path: &Path, args: &[String]) -> Option<(String, String)> {
+        let key = path.to_string_lossy().to_string();
+        let fingerprint = fingerprint(path);
+
+        if let Some((size, modified)) = fingerprint {
+            let entries = self.entries.lock().ok()?;
+            if let Some(entry) = entries.get(&key) {
+                if entry.size == size && entry.modified == modified && entry.args == args {
+                    return Some((entry.stdout.clone(), entry.stderr.clone()));
+                }
+            }
+        }
+
+        let output = command_output_with_timeout_vec(&key, args, PROBE_TIMEOUT).ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+
+        if let Some((size, modified)) = fingerprint {
+            if let Ok(mut entries) = self.entries.lock() {
+                entries.insert(
+                    key,
+                    ProbeEntry {
+                        size,
+                        modified,
+                        args: args.to_vec(),
+                        stdout: stdout.clone(),
+                        stderr: stderr.clone(),
+                    },
+                );
+                self.dirty.store(true, Ordering::Relaxed);
+            }
+        }
+
+        Some((stdout, stderr))
+    }
+
+    /// Persist the cache if any probe added or replaced an entry
+    pub fn save(&self) {
+        if !self.dirty.load(Ordering::Relaxed) {
+            return;
+        }
+        let (Some(path), Ok(entries)) = (&self.path, self.entries.lock()) else {
+            return;
+        };
+        // Drop entries for binaries that no longer exist
+        let entries: HashMap<&String, &ProbeEntry> = entries
+            .iter()
+            .filter(|(key, _)| Path::new(key).exists())
+            .collect();
+        let _ = save_json(path, &entries);
+    }
+}
+
+/// (size, mtime) of the binary `path` runs; `None` for shims, which are never cached
+fn fingerprint(path: &Path) -> Option<(u64, u64)> {
+    if is_shim(path) {
+        return None;
+    }
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some((metadata.len(), modified))
+}
+
+/// pyenv/rbenv/asdf `shims/` scripts, launcher binaries such as volta's, corepack
+/// shims and rustup proxies
+fn is_shim(path: &Path) -> bool {
+    let in_shims_dir = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| name == "shims");
+    let target = fs::canonicalize(path).ok();
+    let launcher = target
+        .as_deref()
+        .and_then(Path::file_stem)
+        .is_some_and(|stem| SHIM_BINARIES.contains(&stem.to_string_lossy().as_ref()));
+    // Node's yarn/pnpm links into corepack, which runs whichever version a project asks for
+    let corepack = target.as_deref().is_some_and(|target| {
+        target
+            .components()
+            .any(|component| component.as_os_str() == "corepack")
+    });
+    in_shims_dir || launcher || corepack || is_rustup_proxy(path)
+}
+
+/// `~/.cargo/bin/rustc`, `cargo`, ... are copies of (or hard links to) the rustup
+/// binary next to them, forwarding to the active toolchain
+fn is_rustup_proxy(path: &Path) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    let rustup = ["rustup", "rustup.exe"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|rustup| rustup.is_file());
+    match (rustup, fs::metadata(path)) {
+        (Some(rustup), Ok(metadata)) => {
+            fs::metadata(rustup).is_ok_and(|rustup| rustup.len() == metadata.len())
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_output_for_unchanged_binaries() {
+        let dir = std::env::temp_dir().join(format!("dev-janitor-probe-{}", std::process::id()));
+        let shims = dir.join("shims");
+        fs::create_dir_all(&shims).unwrap();
+        let binary = dir.join("tool");
+        fs::write(&binary, "binary").unwrap();
+        fs::write(shims.join("tool"), "shim").unwrap();
+
+        let cache = ProbeCache::default();
+        let (size, modified) = fingerprint(&binary).unwrap();
+        cache.entries.lock().unwrap().insert(
+            binary.to_string_lossy().to_string(),
+            ProbeEntry {
+                size,
+                modified,
+                args: vec!["--version".to_string()],
+                stdout: "tool 1.2.3".to_string(),
+                stderr: String::new(),
+            },
+        );
+
+        // The cached output is returned without running the (non-executable) file
+        assert_eq!(
+            cache.probe(&binary, &["--version".to_string()]),
+            Some(("tool 1.2.3".to_string(), String::new()))
+        );
+        assert!(fingerprint(&shims.join("tool")).is_none());
+
+        // A rustup proxy is the rustup binary under another name
+        fs::write(dir.join("rustup"), "rustup-init").unwrap();
+        fs::write(dir.join("rustc"), "rustup-init").unwrap();
+        assert!(fingerprint(&dir.join("rustc")).is_none());
+        assert!(fingerprint(&binary).is_some());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,369 @@
+//! Tool detection rules
+//! Built-in rules ship in `tools.toml`; extra `.toml` files in the app data `tool_rules`
+//! directory add tools or replace built-in ones by id
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ai_tools::ai_tools;
+use crate::utils::storage::app_data_file;
+
+/// The rules compiled into the binary
+const BUILTIN_RULES: &str = include_str!("tools.toml");
+
+/// Where user rule files live, under the app data directory
+const USER_RULES_DIR: &str = "tool_rules";
+
+/// Tool detection rule, validated and with its version regex compiled
+#[derive(Debug, Clone)]
+pub struct ToolRule {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub commands: Vec<String>,
+    pub version_args: Vec<String>,
+    pub version_regex: Option<Regex>,
+    pub extra_paths: Vec<String>, // patterns for the current OS, expanded by `extra_paths`
+    pub source: String,           // "builtin", "ai_tools" or the user file path
+}
+
+/// A rule that was rejected while loading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleError {
+    pub source: String,
+    pub id: Option<String>,
+    pub message: String,
+}
+
+/// Loaded rules plus everything that failed validation
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub rules: Vec<ToolRule>,
+    pub errors: Vec<RuleError>,
+}
+
+/// What the rules view shows for one loaded rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRuleSummary {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub source: String,
+}
+
+/// Loaded rules and validation errors, for checking user rule files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolRuleReport {
+    pub rules_dir: Option<String>,
+    pub rules: Vec<ToolRuleSummary>,
+    pub errors: Vec<RuleError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleFile {
+    #[serde(default, rename = "tool")]
+    tools: Vec<RuleDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleDefinition {
+    id: String,
+    name: String,
+    category: String,
+    commands: Vec<String>,
+    #[serde(default)]
+    version_args: Vec<String>,
+    version_regex: Option<String>,
+    #[serde(default)]
+    extra_paths: ExtraPaths,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ExtraPaths {
+    #[serde(default)]
+    windows: Vec<String>,
+    #[serde(default)]
+    macos: Vec<String>,
+    #[serde(default)]
+    linux: Vec<String>,
+}
+
+impl ExtraPaths {
+    fn for_current_os(self) -> Vec<String> {
+        if cfg!(target_os = "windows") {
+            self.windows
+        } else if cfg!(target_os = "macos") {
+            self.macos
+        } else {
+            self.linux
+        }
+    }
+}
+
+impl ToolRule {
+    /// Existing directories named by `extra_paths`; patterns with unset variables are skipped
+    pub fn extra_paths(&self) -> Vec<PathBuf> {
+        self.extra_paths
+            .iter()
+            .filter_map(|pattern| expand_env(pattern))
+            .flat_map(|pattern| {
+                if pattern.contains('*') || pattern.contains('?') {
+                    glob::glob(&pattern)
+                        .map(|paths| paths.flatten().collect())
+                        .unwrap_or_default()
+                } else {
+                    vec![PathBuf::from(pattern)]
+                }
+            })
+            .filter(|path| path.exists())
+            .collect()
+    }
+}
+
+/// Directory scanned for user rule files
+pub fn user_rules_dir() -> Option<PathBuf> {
+    app_data_file(USER_RULES_DIR)
+}
+
+/// Built-in rules, then user files (sorted by name), then AI CLI tools
+pub fn load_tool_rules() -> RuleSet {
+    let mut set = RuleSet::default();
+    merge_rule_file(&mut set, "builtin", BUILTIN_RULES);
+
+    if let Some(dir) = user_rules_dir() {
+        for path in user_rule_files(&dir) {
+            let source = path.to_string_lossy().to_string();
+            match fs::read_to_string(&path) {
+                Ok(content) => merge_rule_file(&mut set, &source, &content),
+                Err(error) => set.errors.push(RuleError {
+                    source,
+                    id: None,
+                    message: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    for tool in ai_tools() {
+        if set.rules.iter().any(|rule| rule.id == tool.id) {
+            continue;
+        }
+        set.rules.push(ToolRule {
+            id: tool.id.to_string(),
+            name: tool.name.to_string(),
+            category: "ai_cli".to_string(),
+            commands: tool.commands.iter().map(|cmd| cmd.to_string()).collect(),
+            version_args: tool
+                .version_args
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect(),
+            version_regex: tool
+                .version_regex
+                .and_then(|pattern| Regex::new(pattern).ok()),
+            extra_paths: Vec::new(),
+            source: "ai_tools".to_string(),
+        });
+    }
+
+    set
+}
+
+/// Load every rule file and report what was accepted and rejected
+pub fn tool_rule_report() -> ToolRuleReport {
+    let set = load_tool_rules();
+    ToolRuleReport {
+        rules_dir: user_rules_dir().map(|dir| dir.to_string_lossy().to_string()),
+        rules: set
+            .rules
+            .into_iter()
+            .map(|rule| ToolRuleSummary {
+                id: rule.id,
+                name: rule.name,
+                category: rule.category,
+                source: rule.source,
+            })
+            .collect(),
+        errors: set.errors,
+    }
+}
+
+fn user_rule_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Parse one rule file into `set`; a rule with a known id replaces the earlier one
+fn merge_rule_file(set: &mut RuleSet, source: &str, content: &str) {
+    let file: RuleFile = match toml::from_str(content) {
+        Ok(file) => file,
+        Err(error) => {
+            set.errors.push(RuleError {
+                source: source.to_string(),
+                id: None,
+                message: error.to_string(),
+            });
+            return;
+        }
+    };
+
+    let mut seen = HashSet::new();
+    for definition in file.tools {
+        let id = definition.id.clone();
+        if !seen.insert(id.clone()) {
+            set.errors.push(RuleError {
+                source: source.to_string(),
+                id: Some(id),
+                message: "Duplicate id in the same file".to_string(),
+            });
+            continue;
+        }
+
+        match compile_rule(definition, source) {
+            Ok(rule) => match set.rules.iter_mut().find(|existing| existing.id == rule.id) {
+                Some(existing) => *existing = rule,
+                None => set.rules.push(rule),
+            },
+            Err(message) => set.errors.push(RuleError {
+                source: source.to_string(),
+                id: Some(id),
+                message,
+            }),
+        }
+    }
+}
+
+fn compile_rule(definition: RuleDefinition, source: &str) -> Result<ToolRule, String> {
+    let valid_id = !definition.id.is_empty()
+        && definition
+            .id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid_id {
+        return Err(format!(
+            "Invalid id \"{}\": use lowercase letters, digits, \"-\" and \"_\"",
+            definition.id
+        ));
+    }
+    if definition.name.trim().is_empty() {
+        return Err("name must not be empty".to_string());
+    }
+    if definition.category.trim().is_empty() {
+        return Err("category must not be empty".to_string());
+    }
+    if definition.commands.is_empty()
+        || definition
+            .commands
+            .iter()
+            .any(|cmd| cmd.trim().is_empty() || cmd.contains(['/', '\\']))
+    {
+        return Err("commands must be a non-empty list of executable names".to_string());
+    }
+
+    let version_regex = match &definition.version_regex {
+        Some(pattern) => {
+            let regex =
+                Regex::new(pattern).map_err(|error| format!("Invalid version_regex: {}", error))?;
+            if regex.captures_len() < 2 {
+                return Err("version_regex needs a capture group for the version".to_string());
+            }
+            Some(regex)
+        }
+        None => None,
+    };
+
+    Ok(ToolRule {
+        id: definition.id,
+        name: definition.name,
+        category: definition.category,
+        commands: definition.commands,
+        version_args: definition.version_args,
+        version_regex,
+        extra_paths: definition.extra_paths.for_current_os(),
+        source: source.to_string(),
+    })
+}
+
+/// Replace `${VAR}` with its value; `None` if any variable is unset or empty
+fn expand_env(pattern: &str) -> Option<String> {
+    let mut expanded = String::new();
+    let mut rest = pattern;
+    while let Some(start) = rest.find("${") {
+        let end = rest[start..].find('}')? + start;
+        let value = env::var(&rest[start + 2..end])
+            .ok()
+            .filter(|value| !value.is_empty())?;
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&value);
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Some(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_builtin_rules_and_validates_user_rules() {
+        let mut set = RuleSet::default();
+        merge_rule_file(&mut set, "builtin", BUILTIN_RULES);
+        assert!(set.errors.is_empty(), "{:?}", set.errors);
+        assert!(set.rules.iter().any(|rule| rule.id == "node"));
+
+        merge_rule_file(
+            &mut set,
+            "user.toml",
+            r#"
+[[tool]]
+id = "zig"
+name = "Zig"
+category = "runtime"
+commands = ["zig"]
+version_args = ["version"]
+version_regex = '(\d+\.\d+\.\d+)'
+
+[[tool]]
+id = "node"
+name = "Node (custom)"
+category = "runtime"
+commands = ["node"]
+
+[[tool]]
+id = "broken"
+name = "Broken"
+category = "runtime"
+commands = ["broken"]
+version_regex = '\d+('
+
+[[tool]]
+id = "Bad Id"
+name = "Bad"
+category = "runtime"
+commands = ["bad"]
+"#,
+        );
+
+        assert!(set.rules.iter().any(|rule| rule.id == "zig"));
+        let node = set.rules.iter().find(|rule| rule.id == "node").unwrap();
+        assert_eq!(node.name, "Node (custom)");
+        assert_eq!(node.source, "user.toml");
+        let rejected: Vec<_> = set.errors.iter().filter_map(|e| e.id.as_deref()).collect();
+        assert_eq!(rejected, vec!["broken", "Bad Id"]);
+
+        assert_eq!(expand_env("${DEV_JANITOR_UNSET_VAR}/bin"), None);
+    }
+}
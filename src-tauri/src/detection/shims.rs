@@ -0,0 +1,234 @@
+//! Shim resolution
+//! pyenv, rbenv, asdf, mise and volta put launcher shims on PATH, and Homebrew links
+//! wrapper scripts; this finds the binary a shim actually runs and the config file
+//! that selected it
+
+use regex::Regex;
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use crate::utils::command::command_output_in_dir;
+
+const WHICH_TIMEOUT: Duration = Duration::from_secs(6);
+
+/// Wrapper scripts are a few lines; anything larger is a real binary
+const WRAPPER_MAX_SIZE: u64 = 64 * 1024;
+
+/// Where a shim leads
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShimResolution {
+    pub manager: &'static str, // pyenv, rbenv, asdf, mise, volta or homebrew
+    pub binary: PathBuf,
+    pub config_file: Option<PathBuf>,
+}
+
+/// Resolve `path` (found on PATH as `cmd`) when it is a shim or a Homebrew wrapper, as
+/// seen from `project_dir` (the home directory when not given)
+pub fn resolve_shim(path: &Path, cmd: &str, project_dir: Option<&Path>) -> Option<ShimResolution> {
+    let resolved = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let manager = shim_manager(path, &resolved)?;
+
+    if manager == "homebrew" {
+        let binary = wrapper_target(&resolved)?;
+        return Some(ShimResolution {
+            manager,
+            binary,
+            config_file: None,
+        });
+    }
+
+    let dir = project_dir.map(Path::to_path_buf).or_else(user_home_dir)?;
+    let binary = manager_which(manager, cmd, &dir)?;
+    Some(ShimResolution {
+        manager,
+        binary,
+        config_file: selecting_config(manager, &dir),
+    })
+}
+
+/// Which manager owns the shim at `path` (`resolved` is its symlink target)
+fn shim_manager(path: &Path, resolved: &Path) -> Option<&'static str> {
+    let parent = path.parent()?;
+    if parent.file_name().is_some_and(|name| name == "shims") {
+        let root = parent.parent()?.file_name()?.to_string_lossy().to_string();
+        return match root.as_str() {
+            ".pyenv" => Some("pyenv"),
+            ".rbenv" => Some("rbenv"),
+            ".asdf" => Some("asdf"),
+            "mise" | ".mise" => Some("mise"),
+            // ASDF_DATA_DIR can put asdf's shims anywhere
+            _ if env::var_os("ASDF_DATA_DIR").is_some_and(|dir| parent.starts_with(dir)) => {
+                Some("asdf")
+            }
+            _ => None,
+        };
+    }
+
+    let volta_bin = parent.ends_with(".volta/bin")
+        || resolved
+            .file_stem()
+            .is_some_and(|stem| stem == "volta-shim");
+    if volta_bin {
+        return Some("volta");
+    }
+
+    resolved
+        .components()
+        .any(|component| component.as_os_str() == "Cellar")
+        .then_some("homebrew")
+}
+
+/// Ask the manager which binary it would run in `dir`: `pyenv which python3` and friends
+fn manager_which(manager: &str, cmd: &str, dir: &Path) -> Option<PathBuf> {
+    let args = ["which".to_string(), cmd.to_string()];
+    let output = command_output_in_dir(manager, &args, Some(dir), WHICH_TIMEOUT).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let binary = PathBuf::from(stdout.lines().next()?.trim());
+    binary.exists().then_some(binary)
+}
+
+/// The binary a Homebrew wrapper script execs; plain symlinked binaries are not shims
+fn wrapper_target(resolved: &Path) -> Option<PathBuf> {
+    // Cellar binaries can be hundreds of MB: check the size and the `#!` before reading
+    if fs::metadata(resolved).ok()?.len() >= WRAPPER_MAX_SIZE {
+        return None;
+    }
+    let mut magic = [0u8; 2];
+    fs::File::open(resolved).ok()?.read_exact(&mut magic).ok()?;
+    if &magic != b"#!" {
+        return None;
+    }
+    let script = fs::read(resolved).ok()?;
+    exec_target(&String::from_utf8_lossy(&script)).filter(|target| target.exists())
+}
+
+/// `exec "/opt/homebrew/Cellar/gradle/8.5/libexec/bin/gradle" "$@"` -> the quoted path
+fn exec_target(script: &str) -> Option<PathBuf> {
+    static EXEC: OnceLock<Regex> = OnceLock::new();
+    let regex = EXEC.get_or_init(|| Regex::new(r#"exec\s+"([^"$]+)""#).expect("valid regex"));
+    regex
+        .captures_iter(script)
+        .last()
+        .and_then(|captures| captures.get(1))
+        .map(|path| PathBuf::from(path.as_str()))
+}
+
+/// The file that chose the active version, searched from `dir` up
+fn selecting_config(manager: &str, dir: &Path) -> Option<PathBuf> {
+    match manager {
+        "pyenv" | "rbenv" => {
+            let args = ["version-file".to_string()];
+            let output = command_output_in_dir(manager, &args, Some(dir), WHICH_TIMEOUT).ok()?;
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let file = PathBuf::from(stdout.lines().next()?.trim());
+            file.exists().then_some(file)
+        }
+        _ => find_config_upwards(manager, dir).or_else(|| global_config(manager)),
+    }
+}
+
+/// Nearest project config for asdf, mise or volta in `start` or its ancestors
+fn find_config_upwards(manager: &str, start: &Path) -> Option<PathBuf> {
+    let names: &[&str] = match manager {
+        "asdf" => &[".tool-versions"],
+        "mise" => &[".mise.toml", "mise.toml", ".tool-versions"],
+        "volta" => &["package.json"],
+        _ => return None,
+    };
+
+    start.ancestors().find_map(|dir| {
+        names.iter().map(|name| dir.join(name)).find(|file| {
+            if manager == "volta" {
+                // Only a package.json with a "volta" section pins anything
+                fs::read_to_string(file)
+                    .ok()
+                    .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                    .is_some_and(|package| package.get("volta").is_some())
+            } else {
+                file.is_file()
+            }
+        })
+    })
+}
+
+/// The user-wide default when no project config applies
+fn global_config(manager: &str) -> Option<PathBuf> {
+    let home = user_home_dir()?;
+    let file = match manager {
+        "asdf" => home.join(".tool-versions"),
+        "mise" => home.join(".config/mise/config.toml"),
+        "volta" => env::var_os("VOLTA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".volta"))
+            .join("tools/user/platform.json"),
+        _ => return None,
+    };
+    file.exists().then_some(file)
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_shims_and_wrappers() {
+        let pyenv = Path::new("/home/dev/.pyenv/shims/python3");
+        assert_eq!(shim_manager(pyenv, pyenv), Some("pyenv"));
+        let mise = Path::new("/home/dev/.local/share/mise/shims/node");
+        assert_eq!(shim_manager(mise, mise), Some("mise"));
+        assert_eq!(
+            shim_manager(
+                Path::new("/home/dev/.volta/bin/node"),
+                Path::new("/home/dev/.volta/bin/volta-shim")
+            ),
+            Some("volta")
+        );
+        assert_eq!(
+            shim_manager(
+                Path::new("/opt/homebrew/bin/gradle"),
+                Path::new("/opt/homebrew/Cellar/gradle/8.5/bin/gradle")
+            ),
+            Some("homebrew")
+        );
+        assert_eq!(
+            shim_manager(
+                Path::new("/usr/bin/python3"),
+                Path::new("/usr/bin/python3.12")
+            ),
+            None
+        );
+
+        assert_eq!(
+            exec_target(
+                "#!/bin/bash\nJAVA_HOME=\"${JAVA_HOME:-/opt/jdk}\" exec \"/opt/homebrew/Cellar/gradle/8.5/libexec/bin/gradle\" \"$@\"\n"
+            ),
+            Some(PathBuf::from("/opt/homebrew/Cellar/gradle/8.5/libexec/bin/gradle"))
+        );
+
+        let dir = env::temp_dir().join(format!("dev-janitor-shims-{}", std::process::id()));
+        let nested = dir.join("app/src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.join(".tool-versions"), "nodejs 20.11.0\n").unwrap();
+        fs::write(dir.join("app/package.json"), r#"{"name":"app"}"#).unwrap();
+        assert_eq!(
+            find_config_upwards("asdf", &nested),
+            Some(dir.join(".tool-versions"))
+        );
+        assert_eq!(find_config_upwards("volta", &nested), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,265 @@
+//! Removing runtime versions through their version manager
+//! Refuses the manager's default and anything a project under the scan root (the home
+//! directory unless given) pins
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use super::pins::{find_project_pins, pin_matches, pin_scan_root, ProjectPin, PIN_SCAN_DEPTH};
+use super::version_managers::{discover_managed_versions, ManagedVersion};
+use crate::cache::{format_size, get_dir_size};
+use crate::error::DevJanitorError;
+use crate::package_manager::run_operation;
+use crate::utils::command::CommandOutcome;
+
+/// Result of removing one runtime version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionUninstallResult {
+    pub tool_id: String,
+    pub manager: String,
+    pub version: String,
+    pub install_path: String,
+    pub bytes_freed: u64,
+    pub bytes_freed_display: String,
+    pub outcome: CommandOutcome,
+}
+
+/// Uninstall `version` of `tool_id` with the manager that installed it
+pub fn uninstall_managed_version(
+    tool_id: &str,
+    manager: &str,
+    version: &str,
+    scan_root: Option<&Path>,
+) -> Result<VersionUninstallResult, DevJanitorError> {
+    let target = discover_managed_versions()
+        .into_iter()
+        .find(|candidate| {
+            candidate.tool_id == tool_id
+                && candidate.manager == manager
+                && candidate.version == version
+        })
+        .ok_or_else(|| {
+            DevJanitorError::Other(format!(
+                "{} {} is not installed by {}",
+                tool_id, version, manager
+            ))
+        })?;
+
+    let root = pin_scan_root(scan_root).ok_or_else(|| {
+        DevJanitorError::Other(
+            "Cannot check project pins: no project root given and no home directory".to_string(),
+        )
+    })?;
+    let pins = find_project_pins(&root, PIN_SCAN_DEPTH);
+    if let Some(reason) = refusal_reason(&target, &pins) {
+        return Err(DevJanitorError::Other(reason));
+    }
+
+    let outcome = match uninstall_command(&target) {
+        Some((program, args)) => {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run_operation(&program, &args)?
+        }
+        // volta has no command for removing a Node runtime; its docs say to delete the image
+        None if target.manager == "volta" => {
+            fs::remove_dir_all(&target.install_dir)?;
+            CommandOutcome {
+                command: format!("remove {}", target.install_dir.display()),
+                ..Default::default()
+            }
+        }
+        None => {
+            return Err(DevJanitorError::Other(format!(
+                "Uninstalling versions managed by {} is not supported",
+                target.manager
+            )))
+        }
+    };
+
+    let remaining = if target.install_dir.exists() {
+        get_dir_size(&target.install_dir)
+    } else {
+        0
+    };
+    let bytes_freed = target.size.saturating_sub(remaining);
+
+    Ok(VersionUninstallResult {
+        tool_id: target.tool_id.to_string(),
+        manager: target.manager.to_string(),
+        version: target.version.clone(),
+        install_path: target.install_dir.to_string_lossy().to_string(),
+        bytes_freed,
+        bytes_freed_display: format_size(bytes_freed),
+        outcome,
+    })
+}
+
+/// Why a version must be kept, if it must
+fn refusal_reason(version: &ManagedVersion, pins: &[ProjectPin]) -> Option<String> {
+    if version.is_default {
+        return Some(format!(
+            "{} {} is the {} default and cannot be removed",
+            version.tool_id, version.version, version.manager
+        ));
+    }
+    if version.default_unknown {
+        return Some(format!(
+            "Cannot tell which {} version {} uses by default; {} {} is kept",
+            version.tool_id, version.manager, version.tool_id, version.version
+        ));
+    }
+
+    let pinned_by: Vec<&str> = pins
+        .iter()
+        .filter(|pin| pin.tool_id == version.tool_id && pin_matches(&pin.version, &version.version))
+        .map(|pin| pin.file.as_str())
+        .collect();
+    if !pinned_by.is_empty() {
+        return Some(format!(
+            "{} {} is pinned by {}",
+            version.tool_id,
+            version.version,
+            pinned_by.join(", ")
+        ));
+    }
+
+    None
+}
+
+/// Program and arguments that remove `version`; `None` when the manager has no such command
+fn uninstall_command(version: &ManagedVersion) -> Option<(String, Vec<String>)> {
+    let dir = &version.install_dir;
+    // install_dir is <root>/.../<parent>/<version>; the parent is the asdf/mise plugin
+    // or the SDKMAN candidate
+    let parent_name = || {
+        dir.parent()
+            .and_then(Path::file_name)
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    // nvm and sdk are shell functions; the root and version are passed as positional
+    // arguments so nothing from disk is interpolated into the script
+    let ancestor = |levels: usize| {
+        dir.ancestors()
+            .nth(levels)
+            .map(|root| root.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+    let shell = |script: &str, args: Vec<String>| {
+        let mut full = vec!["-c".to_string(), script.to_string(), "bash".to_string()];
+        full.extend(args);
+        Some(("bash".to_string(), full))
+    };
+
+    match version.manager {
+        // <NVM_DIR>/versions/node/v20.11.0
+        "nvm" => shell(
+            r#"source "$1/nvm.sh" && nvm uninstall "$2""#,
+            vec![ancestor(3), version.version.clone()],
+        ),
+        "fnm" => Some((
+            "fnm".to_string(),
+            vec!["uninstall".to_string(), version.version.clone()],
+        )),
+        "volta" => None,
+        "pyenv" | "goenv" => Some((
+            version.manager.to_string(),
+            vec![
+                "uninstall".to_string(),
+                "-f".to_string(),
+                version.version.clone(),
+            ],
+        )),
+        "asdf" => Some((
+            "asdf".to_string(),
+            vec![
+                "uninstall".to_string(),
+                parent_name(),
+                version.version.clone(),
+            ],
+        )),
+        "mise" => Some((
+            "mise".to_string(),
+            vec![
+                "uninstall".to_string(),
+                format!("{}@{}", parent_name(), version.version),
+            ],
+        )),
+        "rustup" => Some((
+            "rustup".to_string(),
+            vec![
+                "toolchain".to_string(),
+                "uninstall".to_string(),
+                version.version.clone(),
+            ],
+        )),
+        // <SDKMAN_DIR>/candidates/java/21.0.2-tem
+        "sdkman" => shell(
+            r#"source "$1/bin/sdkman-init.sh" && sdk uninstall "$2" "$3""#,
+            vec![ancestor(3), parent_name(), version.version.clone()],
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn version(manager: &'static str, dir: &str, version: &str) -> ManagedVersion {
+        ManagedVersion {
+            tool_id: "node",
+            manager,
+            version: version.to_string(),
+            install_dir: PathBuf::from(dir),
+            executable: None,
+            is_default: false,
+            default_unknown: false,
+            size: 0,
+        }
+    }
+
+    #[test]
+    fn builds_commands_and_refuses_protected_versions() {
+        let nvm = version("nvm", "/home/dev/.nvm/versions/node/v18.19.0", "18.19.0");
+        let (program, args) = uninstall_command(&nvm).unwrap();
+        assert_eq!(program, "bash");
+        assert_eq!(&args[3..], ["/home/dev/.nvm", "18.19.0"]);
+
+        let mise = version(
+            "mise",
+            "/home/dev/.local/share/mise/installs/nodejs/20.1.0",
+            "20.1.0",
+        );
+        assert_eq!(
+            uninstall_command(&mise).unwrap().1,
+            vec!["uninstall".to_string(), "nodejs@20.1.0".to_string()]
+        );
+        assert!(uninstall_command(&version("volta", "/v/node/20.1.0", "20.1.0")).is_none());
+
+        let pins = vec![ProjectPin {
+            tool_id: "node".to_string(),
+            version: "18".to_string(),
+            file: "/work/app/.nvmrc".to_string(),
+        }];
+        assert!(refusal_reason(&nvm, &pins)
+            .unwrap()
+            .contains("/work/app/.nvmrc"));
+        assert!(refusal_reason(&mise, &pins).is_none());
+
+        let unknown = ManagedVersion {
+            default_unknown: true,
+            ..mise.clone()
+        };
+        assert!(refusal_reason(&unknown, &[])
+            .unwrap()
+            .contains("by default"));
+        let default = ManagedVersion {
+            is_default: true,
+            ..mise
+        };
+        assert!(refusal_reason(&default, &[]).unwrap().contains("default"));
+    }
+}
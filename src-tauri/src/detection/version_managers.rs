@@ -0,0 +1,561 @@
+//! Versions installed by runtime version managers
+//! Enumerates nvm, fnm, volta, pyenv, goenv, asdf, mise, rustup and SDKMAN install
+//! directories, including versions that are not the one currently on PATH
+
+use rayon::prelude::*;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::get_dir_size;
+use crate::utils::version::compare_versions;
+
+/// One runtime version found in a version manager's install directory
+#[derive(Debug, Clone)]
+pub struct ManagedVersion {
+    pub tool_id: &'static str,
+    pub manager: &'static str,
+    pub version: String,
+    pub install_dir: PathBuf,
+    pub executable: Option<PathBuf>,
+    pub is_default: bool,
+    pub default_unknown: bool, // the manager has a default this version might be
+    pub size: u64,
+}
+
+/// asdf/mise plugin names, the detection rule they belong to and their main executable
+const PLUGIN_TOOLS: &[(&str, &str, &str)] = &[
+    ("nodejs", "node", "node"),
+    ("node", "node", "node"),
+    ("python", "python", "python3"),
+    ("java", "java", "java"),
+    ("golang", "go", "go"),
+    ("go", "go", "go"),
+    ("ruby", "ruby", "ruby"),
+    ("rust", "rust", "rustc"),
+    ("php", "php", "php"),
+    ("deno", "deno", "deno"),
+    ("bun", "bun", "bun"),
+    ("maven", "maven", "mvn"),
+    ("gradle", "gradle", "gradle"),
+];
+
+/// SDKMAN candidates that have a detection rule, with their main executable
+const SDKMAN_TOOLS: &[(&str, &str, &str)] = &[
+    ("java", "java", "java"),
+    ("maven", "maven", "mvn"),
+    ("gradle", "gradle", "gradle"),
+];
+
+/// Detection rule id for an asdf/mise plugin or `.tool-versions` entry
+pub fn plugin_tool_id(plugin: &str) -> Option<&'static str> {
+    PLUGIN_TOOLS
+        .iter()
+        .find(|(name, _, _)| *name == plugin)
+        .map(|(_, tool_id, _)| *tool_id)
+}
+
+/// Every version installed by a supported version manager, measured in parallel
+pub fn discover_managed_versions() -> Vec<ManagedVersion> {
+    let Some(home) = user_home_dir() else {
+        return Vec::new();
+    };
+
+    let mut versions = Vec::new();
+    versions.extend(nvm_versions(&home));
+    versions.extend(fnm_versions(&home));
+    versions.extend(volta_versions(&home));
+    versions.extend(pyenv_versions(&home));
+    versions.extend(goenv_versions(&home));
+    let tool_versions = fs::read_to_string(home.join(".tool-versions"))
+        .map(|content| parse_tool_versions(&content))
+        .unwrap_or_default();
+    versions.extend(plugin_versions(
+        "asdf",
+        &env_dir("ASDF_DATA_DIR").unwrap_or_else(|| home.join(".asdf")),
+        Some(&tool_versions),
+    ));
+    versions.extend(plugin_versions(
+        "mise",
+        &env_dir("MISE_DATA_DIR").unwrap_or_else(|| home.join(".local/share/mise")),
+        mise_globals(&home, &tool_versions).as_deref(),
+    ));
+    versions.extend(rustup_toolchains(&home));
+    versions.extend(sdkman_candidates(&home));
+
+    versions.par_iter_mut().for_each(|version| {
+        version.size = get_dir_size(&version.install_dir);
+    });
+    versions
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+fn env_dir(name: &str) -> Option<PathBuf> {
+    env::var(name)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Real (non-symlink) subdirectories; aliases like mise's "20" -> "20.11.0" are skipped
+fn version_dirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(String, PathBuf)> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().map(|kind| kind.is_dir()).unwrap_or(false))
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+        .filter(|(name, _)| !name.starts_with('.'))
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.lines().next().map(|line| line.trim().to_string()))
+        .filter(|line| !line.is_empty())
+}
+
+/// `bin/<name>` under an install directory, if present
+fn executable_in(install_dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = install_dir.join("bin").join(name);
+    path.exists().then_some(path)
+}
+
+/// Whether an alias such as "20", "v20.11.0" or "lts/iron" names this version
+fn alias_matches(alias: &str, version: &str) -> bool {
+    let alias = alias.trim().trim_start_matches('v');
+    let version = version.trim_start_matches('v');
+    version == alias || version.starts_with(&format!("{}.", alias))
+}
+
+/// Installed versions a default spec selects: "20" or "v20.11.0" by prefix, and the
+/// newest for "latest", "node" or "stable"; `None` for specs that cannot be resolved
+/// here ("lts", named aliases, "ref:...")
+fn resolve_default(spec: &str, installed: &[String]) -> Option<Vec<String>> {
+    let spec = spec.trim();
+    match spec {
+        "system" => Some(Vec::new()),
+        "latest" | "node" | "stable" => Some(
+            installed
+                .iter()
+                .max_by(|left, right| compare_versions(left, right))
+                .cloned()
+                .into_iter()
+                .collect(),
+        ),
+        _ if spec
+            .trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit()) =>
+        {
+            Some(
+                installed
+                    .iter()
+                    .filter(|version| alias_matches(spec, version))
+                    .cloned()
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Mark `version` as the default, or as possibly the default when it is unresolved
+fn apply_default(mut version: ManagedVersion, default: Option<&[String]>) -> ManagedVersion {
+    match default {
+        Some(defaults) => version.is_default = defaults.contains(&version.version),
+        None => version.default_unknown = true,
+    }
+    version
+}
+
+fn managed(
+    tool_id: &'static str,
+    manager: &'static str,
+    version: String,
+    install_dir: PathBuf,
+    executable: Option<PathBuf>,
+    is_default: bool,
+) -> ManagedVersion {
+    ManagedVersion {
+        tool_id,
+        manager,
+        version,
+        install_dir,
+        executable,
+        is_default,
+        default_unknown: false,
+        size: 0,
+    }
+}
+
+/// nvm: `$NVM_DIR/versions/node/v20.11.0`, default in `alias/default`
+fn nvm_versions(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("NVM_DIR").unwrap_or_else(|| home.join(".nvm"));
+    let versions: Vec<(String, PathBuf)> = version_dirs(&root.join("versions").join("node"))
+        .into_iter()
+        .map(|(name, dir)| (name.trim_start_matches('v').to_string(), dir))
+        .collect();
+    let installed: Vec<String> = versions
+        .iter()
+        .map(|(version, _)| version.clone())
+        .collect();
+    let default = nvm_default(&root.join("alias"), &installed);
+
+    versions
+        .into_iter()
+        .map(|(version, dir)| {
+            let executable = executable_in(&dir, "node");
+            apply_default(
+                managed("node", "nvm", version, dir, executable, false),
+                default.as_deref(),
+            )
+        })
+        .collect()
+}
+
+/// Follow `alias/default` through nvm's named aliases ("lts/*" -> "lts/iron" -> "v20.11.0")
+fn nvm_default(alias_dir: &Path, installed: &[String]) -> Option<Vec<String>> {
+    let Some(mut spec) = read_trimmed(&alias_dir.join("default")) else {
+        return Some(Vec::new());
+    };
+    // Aliases may point at each other; give up on a cycle
+    for _ in 0..8 {
+        if let Some(default) = resolve_default(&spec, installed) {
+            return Some(default);
+        }
+        spec = read_trimmed(&alias_dir.join(&spec))?;
+    }
+    None
+}
+
+/// fnm: `<fnm dir>/node-versions/v20.11.0/installation`, default is the `aliases/default` link
+fn fnm_versions(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("FNM_DIR").unwrap_or_else(|| {
+        [
+            home.join(".local/share/fnm"),
+            home.join("Library/Application Support/fnm"),
+            home.join(".fnm"),
+        ]
+        .into_iter()
+        .find(|dir| dir.exists())
+        .unwrap_or_else(|| home.join(".local/share/fnm"))
+    });
+    let default = fs::canonicalize(root.join("aliases").join("default")).ok();
+
+    version_dirs(&root.join("node-versions"))
+        .into_iter()
+        .map(|(name, dir)| {
+            let install_dir = dir.join("installation");
+            let is_default = default
+                .as_ref()
+                .map(|target| fs::canonicalize(&install_dir).ok().as_ref() == Some(target))
+                .unwrap_or(false);
+            let executable = executable_in(&install_dir, "node");
+            managed(
+                "node",
+                "fnm",
+                name.trim_start_matches('v').to_string(),
+                dir,
+                executable,
+                is_default,
+            )
+        })
+        .collect()
+}
+
+/// volta: `$VOLTA_HOME/tools/image/node/20.11.0`, default in `tools/user/platform.json`
+fn volta_versions(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("VOLTA_HOME").unwrap_or_else(|| home.join(".volta"));
+    let default = fs::read_to_string(root.join("tools/user/platform.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|platform| {
+            platform
+                .pointer("/node/runtime")
+                .and_then(|runtime| runtime.as_str())
+                .map(str::to_string)
+        });
+
+    version_dirs(&root.join("tools/image/node"))
+        .into_iter()
+        .map(|(version, dir)| {
+            let is_default = default.as_deref() == Some(version.as_str());
+            let executable = executable_in(&dir, "node");
+            managed("node", "volta", version, dir, executable, is_default)
+        })
+        .collect()
+}
+
+/// pyenv: `$PYENV_ROOT/versions/3.12.1`, global version in `$PYENV_ROOT/version`
+fn pyenv_versions(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("PYENV_ROOT").unwrap_or_else(|| home.join(".pyenv"));
+    let global = read_trimmed(&root.join("version"));
+
+    version_dirs(&root.join("versions"))
+        .into_iter()
+        .map(|(version, dir)| {
+            let is_default = global.as_deref() == Some(version.as_str());
+            let executable =
+                executable_in(&dir, "python3").or_else(|| executable_in(&dir, "python"));
+            managed("python", "pyenv", version, dir, executable, is_default)
+        })
+        .collect()
+}
+
+/// goenv: `$GOENV_ROOT/versions/1.21.5`, global version in `$GOENV_ROOT/version`
+fn goenv_versions(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("GOENV_ROOT").unwrap_or_else(|| home.join(".goenv"));
+    let global = read_trimmed(&root.join("version"));
+
+    version_dirs(&root.join("versions"))
+        .into_iter()
+        .map(|(version, dir)| {
+            let is_default = global.as_deref() == Some(version.as_str());
+            let executable = executable_in(&dir, "go");
+            managed("go", "goenv", version, dir, executable, is_default)
+        })
+        .collect()
+}
+
+/// asdf and mise: `<data dir>/installs/<plugin>/<version>`; `globals` are the global tool
+/// versions, `None` when they could not be read
+fn plugin_versions(
+    manager: &'static str,
+    data_dir: &Path,
+    globals: Option<&[(String, Vec<String>)]>,
+) -> Vec<ManagedVersion> {
+    version_dirs(&data_dir.join("installs"))
+        .into_iter()
+        .filter_map(|(plugin, plugin_dir)| {
+            let (_, tool_id, binary) = PLUGIN_TOOLS.iter().find(|(name, _, _)| *name == plugin)?;
+            Some((*tool_id, *binary, plugin, plugin_dir))
+        })
+        .flat_map(|(tool_id, binary, plugin, plugin_dir)| {
+            let versions = version_dirs(&plugin_dir);
+            let installed: Vec<String> = versions
+                .iter()
+                .map(|(version, _)| version.clone())
+                .collect();
+            let default = globals.and_then(|globals| {
+                match globals.iter().find(|(name, _)| *name == plugin) {
+                    Some((_, specs)) => resolve_default(specs.first()?, &installed),
+                    None => Some(Vec::new()),
+                }
+            });
+            versions.into_iter().map(move |(version, dir)| {
+                // Python builds without a `python3` link only ship `python`
+                let executable =
+                    executable_in(&dir, binary).or_else(|| executable_in(&dir, tool_id));
+                apply_default(
+                    managed(tool_id, manager, version, dir, executable, false),
+                    default.as_deref(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// `.tool-versions` lines: "nodejs 20.11.0 18.19.0" (first version wins)
+pub fn parse_tool_versions(content: &str) -> Vec<(String, Vec<String>)> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let plugin = fields.next()?.to_string();
+            let versions: Vec<String> = fields.map(str::to_string).collect();
+            (!versions.is_empty()).then_some((plugin, versions))
+        })
+        .collect()
+}
+
+/// `[tools]` of a mise config: `node = "20"`, `python = ["3.12", "3.11"]` or
+/// `java = { version = "21" }`; `None` when the file is not valid TOML
+pub fn parse_mise_tools(content: &str) -> Option<Vec<(String, Vec<String>)>> {
+    let config: toml::Table = content.parse().ok()?;
+    let Some(tools) = config.get("tools").and_then(|tools| tools.as_table()) else {
+        return Some(Vec::new());
+    };
+    let version = |value: &toml::Value| match value {
+        toml::Value::String(version) => Some(version.clone()),
+        toml::Value::Table(options) => options.get("version")?.as_str().map(str::to_string),
+        _ => None,
+    };
+    Some(
+        tools
+            .iter()
+            .map(|(plugin, value)| {
+                let versions: Vec<String> = match value {
+                    toml::Value::Array(items) => items.iter().filter_map(version).collect(),
+                    other => version(other).into_iter().collect(),
+                };
+                (plugin.clone(), versions)
+            })
+            .filter(|(_, versions)| !versions.is_empty())
+            .collect(),
+    )
+}
+
+/// mise's global tools: its `config.toml` entries come before those of `~/.tool-versions`,
+/// which mise also reads; `None` when the config exists but cannot be read
+fn mise_globals(
+    home: &Path,
+    tool_versions: &[(String, Vec<String>)],
+) -> Option<Vec<(String, Vec<String>)>> {
+    let config = env_dir("MISE_GLOBAL_CONFIG_FILE").unwrap_or_else(|| {
+        env_dir("MISE_CONFIG_DIR")
+            .or_else(|| env_dir("XDG_CONFIG_HOME").map(|dir| dir.join("mise")))
+            .unwrap_or_else(|| home.join(".config/mise"))
+            .join("config.toml")
+    });
+    let mut globals = match fs::read_to_string(&config) {
+        Ok(content) => parse_mise_tools(&content)?,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(_) => return None,
+    };
+    globals.extend(tool_versions.iter().cloned());
+    Some(globals)
+}
+
+/// rustup: `$RUSTUP_HOME/toolchains/<toolchain>`, default in `settings.toml`
+fn rustup_toolchains(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("RUSTUP_HOME").unwrap_or_else(|| home.join(".rustup"));
+    let default = fs::read_to_string(root.join("settings.toml"))
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|settings| {
+            settings
+                .get("default_toolchain")
+                .and_then(|toolchain| toolchain.as_str())
+                .map(str::to_string)
+        });
+
+    version_dirs(&root.join("toolchains"))
+        .into_iter()
+        .map(|(toolchain, dir)| {
+            // settings.toml may store "stable" for "stable-x86_64-unknown-linux-gnu"
+            let is_default = default
+                .as_deref()
+                .map(|default| {
+                    toolchain == default || toolchain.starts_with(&format!("{}-", default))
+                })
+                .unwrap_or(false);
+            let executable = executable_in(&dir, "rustc");
+            managed("rust", "rustup", toolchain, dir, executable, is_default)
+        })
+        .collect()
+}
+
+/// SDKMAN: `$SDKMAN_DIR/candidates/<candidate>/<version>`, default is the `current` link
+fn sdkman_candidates(home: &Path) -> Vec<ManagedVersion> {
+    let root = env_dir("SDKMAN_DIR").unwrap_or_else(|| home.join(".sdkman"));
+
+    SDKMAN_TOOLS
+        .iter()
+        .flat_map(|(candidate, tool_id, binary)| {
+            let candidate_dir = root.join("candidates").join(candidate);
+            let current = fs::canonicalize(candidate_dir.join("current")).ok();
+            version_dirs(&candidate_dir)
+                .into_iter()
+                .filter(|(version, _)| version != "current")
+                .map(move |(version, dir)| {
+                    let is_default = current
+                        .as_ref()
+                        .map(|target| fs::canonicalize(&dir).ok().as_ref() == Some(target))
+                        .unwrap_or(false);
+                    let executable = executable_in(&dir, binary);
+                    managed(tool_id, "sdkman", version, dir, executable, is_default)
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tool_versions_and_aliases() {
+        let parsed = parse_tool_versions(
+            "nodejs 20.11.0 18.19.0\n# comment\npython 3.12.1 # pinned\nbroken\n",
+        );
+        assert_eq!(
+            parsed,
+            vec![
+                (
+                    "nodejs".to_string(),
+                    vec!["20.11.0".to_string(), "18.19.0".to_string()]
+                ),
+                ("python".to_string(), vec!["3.12.1".to_string()]),
+            ]
+        );
+        assert_eq!(plugin_tool_id("golang"), Some("go"));
+        assert!(alias_matches("v20", "20.11.0"));
+        assert!(alias_matches("20.11.0", "20.11.0"));
+        assert!(!alias_matches("2", "20.11.0"));
+
+        assert_eq!(
+            parse_mise_tools(
+                "[tools]\nnode = \"20\"\npython = [\"3.12\", \"3.11\"]\njava = { version = \"21\" }\n"
+            )
+            .unwrap(),
+            vec![
+                ("java".to_string(), vec!["21".to_string()]),
+                ("node".to_string(), vec!["20".to_string()]),
+                (
+                    "python".to_string(),
+                    vec!["3.12".to_string(), "3.11".to_string()]
+                ),
+            ]
+        );
+        assert!(parse_mise_tools("[tools\n").is_none());
+    }
+
+    #[test]
+    fn resolves_default_aliases() {
+        let installed = vec!["18.19.0".to_string(), "20.11.0".to_string()];
+        assert_eq!(
+            resolve_default("v20", &installed),
+            Some(vec!["20.11.0".to_string()])
+        );
+        assert_eq!(
+            resolve_default("node", &installed),
+            Some(vec!["20.11.0".to_string()])
+        );
+        assert_eq!(resolve_default("system", &installed), Some(Vec::new()));
+        assert_eq!(resolve_default("lts/iron", &installed), None);
+
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let alias_dir = env::temp_dir().join(format!("dev-janitor-nvm-alias-{nanos}"));
+        fs::create_dir_all(alias_dir.join("lts")).unwrap();
+        fs::write(alias_dir.join("default"), "lts/*\n").unwrap();
+        fs::write(alias_dir.join("lts/*"), "lts/hydrogen\n").unwrap();
+        fs::write(alias_dir.join("lts/hydrogen"), "v18.19.0\n").unwrap();
+        assert_eq!(
+            nvm_default(&alias_dir, &installed),
+            Some(vec!["18.19.0".to_string()])
+        );
+        // An alias nvm itself would resolve remotely is left unresolved
+        fs::write(alias_dir.join("default"), "lts/iron\n").unwrap();
+        assert_eq!(nvm_default(&alias_dir, &installed), None);
+        fs::remove_dir_all(&alias_dir).unwrap();
+    }
+}
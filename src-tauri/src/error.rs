@@ -1,7 +1,11 @@
 //! Error handling for Dev Janitor
 
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::utils::command::CommandOutcome;
+
 #[derive(Error, Debug)]
 pub enum DevJanitorError {
     #[error("IO error: {0}")]
@@ -18,13 +22,215 @@ pub enum DevJanitorError {
 
     #[error("Permission denied: {0}")]
     PermissionDenied(String),
+
+    #[error("{}", .0.summary())]
+    PackageOperation(Box<CommandOutcome>),
+
+    #[error("{0}")]
+    Other(String),
 }
 
+impl DevJanitorError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Io(_) => "io",
+            Self::CommandFailed(_) => "command_failed",
+            Self::ToolNotFound(_) => "tool_not_found",
+            Self::ParseError(_) => "parse_error",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::PackageOperation(_) => "package_operation",
+            Self::Other(_) => "other",
+        }
+    }
+}
+
+impl From<String> for DevJanitorError {
+    fn from(message: String) -> Self {
+        Self::Other(message)
+    }
+}
+
+impl From<DevJanitorError> for String {
+    fn from(error: DevJanitorError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Serialized as `{ kind, message, outcome }` so the UI can offer targeted fixes
 impl serde::Serialize for DevJanitorError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        let outcome = match self {
+            Self::PackageOperation(outcome) => Some(outcome.as_ref()),
+            _ => None,
+        };
+
+        let mut state = serializer.serialize_struct("DevJanitorError", 3)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("outcome", &outcome)?;
+        state.end()
+    }
+}
+
+/// Why an external command failed, classified from its output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    PermissionDenied,
+    Network,
+    NotFound,
+    ExternallyManaged, // PEP 668
+    LockHeld,
+    Timeout,
+    Other,
+}
+
+impl FailureKind {
+    /// Classify a failed command from its combined stdout/stderr
+    pub fn classify(output: &str) -> Self {
+        let output = output.to_lowercase();
+        let matches = |patterns: &[&str]| patterns.iter().any(|p| output.contains(p));
+
+        if matches(&["externally-managed-environment", "externally managed"]) {
+            Self::ExternallyManaged
+        } else if matches(&[
+            "could not get lock",
+            "waiting for cache lock",
+            "blocking waiting for file lock",
+            "another active homebrew",
+            "is locked by another",
+            "lock held",
+        ]) {
+            Self::LockHeld
+        } else if matches(&[
+            "permission denied",
+            "eacces",
+            "eperm",
+            "access is denied",
+            "operation not permitted",
+            "[errno 13]",
+            "a password is required",
+            "request dismissed",
+            "not authorized",
+        ]) {
+            Self::PermissionDenied
+        } else if matches(&[
+            "enotfound",
+            "etimedout",
+            "econnrefused",
+            "econnreset",
+            "eai_again",
+            "connection refused",
+            "could not resolve",
+            "temporary failure in name resolution",
+            "max retries exceeded",
+            "failed to connect",
+            "network is unreachable",
+            // TLS failures, not every mention of "ssl" (openssl headers missing in a build)
+            "certificate_verify_failed",
+            "sslerror",
+            "ssl handshake",
+            "unable to get local issuer certificate",
+            "self signed certificate in certificate chain",
+        ]) {
+            Self::Network
+        } else if matches(&[
+            // Registry lookups only; "command not found" or "header not found" in a build
+            // log is not a wrong package name
+            "e404",
+            "npm err! 404",
+            "404 not found",
+            "no matching distribution",
+            "could not find a version",
+            "no available formula",
+            "no formulae or casks found",
+            "packagesnotfounderror",
+            "package not found",
+            "packages not found",
+            "in registry `",
+            "no such keg",
+            "as it is not installed",
+        ]) {
+            Self::NotFound
+        } else {
+            Self::Other
+        }
+    }
+
+    /// Suggested next step shown alongside the failure
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            Self::PermissionDenied => {
+                Some("Retry with --user, or fix the ownership of the global install prefix")
+            }
+            Self::Network => Some("Check the network connection, proxy and registry settings"),
+            Self::NotFound => Some("Check the package name and the configured registry"),
+            Self::ExternallyManaged => Some(
+                "This Python is managed by the system; use pipx for applications or a virtual environment",
+            ),
+            Self::LockHeld => Some("Another package operation is running; retry once it finishes"),
+            Self::Timeout => Some("The command took too long; retry or run it from a terminal"),
+            Self::Other => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_package_failures() {
+        let pep668 =
+            "error: externally-managed-environment\n\n× This environment is externally managed";
+        assert_eq!(
+            FailureKind::classify(pep668),
+            FailureKind::ExternallyManaged
+        );
+        assert_eq!(
+            FailureKind::classify("npm ERR! code EACCES\nnpm ERR! syscall mkdir"),
+            FailureKind::PermissionDenied
+        );
+        assert_eq!(
+            FailureKind::classify("npm ERR! code ENOTFOUND\nnpm ERR! network request failed"),
+            FailureKind::Network
+        );
+        assert_eq!(
+            FailureKind::classify("ERROR: No matching distribution found for nope"),
+            FailureKind::NotFound
+        );
+        assert_eq!(
+            FailureKind::classify(
+                "Error: Another active Homebrew update process is already in progress."
+            ),
+            FailureKind::LockHeld
+        );
+        assert_eq!(FailureKind::classify("something odd"), FailureKind::Other);
+        assert_eq!(
+            FailureKind::classify(
+                "pip._vendor.urllib3.exceptions.SSLError: [SSL: CERTIFICATE_VERIFY_FAILED]"
+            ),
+            FailureKind::Network
+        );
+        assert_eq!(
+            FailureKind::classify("error: could not find `nope` in registry `crates-io`"),
+            FailureKind::NotFound
+        );
+        // Build failures that merely mention ssl or "not found"
+        assert_eq!(
+            FailureKind::classify("fatal error: openssl/opensslv.h: No such file or directory"),
+            FailureKind::Other
+        );
+        assert_eq!(
+            FailureKind::classify("sh: gcc: command not found"),
+            FailureKind::Other
+        );
+        assert_eq!(
+            FailureKind::classify("error: Python.h header not found"),
+            FailureKind::Other
+        );
     }
 }
@@ -0,0 +1,416 @@
+//! Editor extension inventory
+//! Lists the extensions VS Code, Cursor, Windsurf and VSCodium keep on disk, grouped
+//! by extension with every version found, and removes versions the editor no longer
+//! loads (superseded by an update but still on disk)
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{format_size, get_dir_size};
+use crate::utils::version::compare_versions;
+
+/// VS Code family editors: (id, name, extensions dir under home)
+pub const EDITORS: &[(&str, &str, &str)] = &[
+    ("vscode", "VS Code", ".vscode/extensions"),
+    (
+        "vscode-insiders",
+        "VS Code Insiders",
+        ".vscode-insiders/extensions",
+    ),
+    ("cursor", "Cursor", ".cursor/extensions"),
+    ("windsurf", "Windsurf", ".windsurf/extensions"),
+    ("vscodium", "VSCodium", ".vscode-oss/extensions"),
+];
+
+/// One installed version of an extension
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtensionVersion {
+    pub version: String,
+    pub path: String,
+    pub target_platform: Option<String>,
+    pub size: u64,
+    pub size_display: String,
+    pub superseded: bool, // another version is the one the editor loads
+}
+
+/// An extension with all its versions, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditorExtension {
+    pub id: String, // "publisher.name", lowercase
+    pub display_name: String,
+    pub publisher: String,
+    pub description: Option<String>,
+    pub versions: Vec<ExtensionVersion>,
+}
+
+/// Extensions of one editor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditorExtensions {
+    pub editor_id: String,
+    pub editor_name: String,
+    pub extensions_dir: String,
+    pub extensions: Vec<EditorExtension>,
+    pub total_size: u64,
+    pub total_size_display: String,
+    pub superseded_size: u64,
+    pub superseded_size_display: String,
+}
+
+/// An extension directory read from its `package.json`
+#[derive(Debug, Clone)]
+struct ExtensionDir {
+    dir: String,
+    id: String,
+    version: String,
+    display_name: String,
+    publisher: String,
+    description: Option<String>,
+    target_platform: Option<String>,
+}
+
+/// Inventory every editor with an extensions directory
+pub fn scan_editor_extensions() -> Vec<EditorExtensions> {
+    let Some(home) = user_home_dir() else {
+        return Vec::new();
+    };
+    EDITORS
+        .iter()
+        .map(|(id, name, dir)| (id, name, home.join(dir)))
+        .filter(|(_, _, dir)| dir.is_dir())
+        .map(|(id, name, dir)| scan_extensions_dir(id, name, &dir))
+        .collect()
+}
+
+/// Versions in `extensions_dir` the editor no longer loads: (extension id, version, dir)
+pub fn superseded_extension_dirs(extensions_dir: &Path) -> Vec<(String, String, PathBuf)> {
+    let dirs = read_extension_dirs(extensions_dir);
+    let superseded = superseded_dirs(extensions_dir, &dirs);
+    dirs.into_iter()
+        .filter(|dir| superseded.contains(&dir.dir))
+        .map(|dir| (dir.id, dir.version, extensions_dir.join(dir.dir)))
+        .collect()
+}
+
+/// Delete superseded extension versions of `editor_id`; any other path is refused
+pub fn remove_superseded_extensions(
+    editor_id: &str,
+    paths: &[String],
+) -> Vec<Result<String, String>> {
+    let extensions_dir = EDITORS
+        .iter()
+        .find(|(id, _, _)| *id == editor_id)
+        .and_then(|(_, _, dir)| Some(user_home_dir()?.join(dir)));
+    let Some(extensions_dir) = extensions_dir else {
+        return paths
+            .iter()
+            .map(|_| Err(format!("Unknown editor: {}", editor_id)))
+            .collect();
+    };
+
+    // Re-check against the current state; the editor may have switched versions
+    let superseded: Vec<PathBuf> = superseded_extension_dirs(&extensions_dir)
+        .into_iter()
+        .map(|(_, _, dir)| dir)
+        .collect();
+    let mut removed = Vec::new();
+
+    let results = paths
+        .iter()
+        .map(|path| {
+            let target = PathBuf::from(path);
+            let is_superseded = fs::symlink_metadata(&target)
+                .is_ok_and(|metadata| metadata.is_dir())
+                && superseded.contains(&target);
+            if !is_superseded {
+                return Err(format!(
+                    "Refusing to remove {}: not a superseded extension version",
+                    path
+                ));
+            }
+            let size = get_dir_size(&target);
+            fs::remove_dir_all(&target).map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+            if let Some(name) = target.file_name() {
+                removed.push(name.to_string_lossy().to_string());
+            }
+            Ok(format!("Removed {} (freed {})", path, format_size(size)))
+        })
+        .collect();
+
+    forget_obsolete(&extensions_dir, &removed);
+    results
+}
+
+fn scan_extensions_dir(
+    editor_id: &str,
+    editor_name: &str,
+    extensions_dir: &Path,
+) -> EditorExtensions {
+    let dirs = read_extension_dirs(extensions_dir);
+    let superseded = superseded_dirs(extensions_dir, &dirs);
+
+    let sized: Vec<(ExtensionDir, u64)> = dirs
+        .into_par_iter()
+        .map(|dir| {
+            let size = get_dir_size(&extensions_dir.join(&dir.dir));
+            (dir, size)
+        })
+        .collect();
+
+    let mut by_id: HashMap<String, EditorExtension> = HashMap::new();
+    for (dir, size) in sized {
+        let extension = by_id
+            .entry(dir.id.clone())
+            .or_insert_with(|| EditorExtension {
+                id: dir.id.clone(),
+                display_name: dir.display_name.clone(),
+                publisher: dir.publisher.clone(),
+                description: dir.description.clone(),
+                versions: Vec::new(),
+            });
+        extension.versions.push(ExtensionVersion {
+            version: dir.version,
+            path: extensions_dir.join(&dir.dir).to_string_lossy().to_string(),
+            target_platform: dir.target_platform,
+            size,
+            size_display: format_size(size),
+            superseded: superseded.contains(&dir.dir),
+        });
+    }
+
+    let mut extensions: Vec<EditorExtension> = by_id.into_values().collect();
+    for extension in &mut extensions {
+        extension
+            .versions
+            .sort_by(|a, b| compare_versions(&b.version, &a.version));
+    }
+    extensions.sort_by(|a, b| {
+        a.display_name
+            .to_lowercase()
+            .cmp(&b.display_name.to_lowercase())
+    });
+
+    let versions = || extensions.iter().flat_map(|extension| &extension.versions);
+    let total_size = versions().map(|version| version.size).sum();
+    let superseded_size = versions()
+        .filter(|version| version.superseded)
+        .map(|version| version.size)
+        .sum();
+
+    EditorExtensions {
+        editor_id: editor_id.to_string(),
+        editor_name: editor_name.to_string(),
+        extensions_dir: extensions_dir.to_string_lossy().to_string(),
+        extensions,
+        total_size,
+        total_size_display: format_size(total_size),
+        superseded_size,
+        superseded_size_display: format_size(superseded_size),
+    }
+}
+
+/// Every subdirectory with a readable `package.json`
+fn read_extension_dirs(extensions_dir: &Path) -> Vec<ExtensionDir> {
+    let Ok(entries) = fs::read_dir(extensions_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+        .filter_map(|entry| read_extension(&entry.path()))
+        .collect()
+}
+
+fn read_extension(path: &Path) -> Option<ExtensionDir> {
+    let content = fs::read_to_string(path.join("package.json")).ok()?;
+    let package: Value = serde_json::from_str(&content).ok()?;
+    let text = |key: &str| package.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let name = text("name")?;
+    let publisher = text("publisher")?;
+    let version = text("version")?;
+    let dir = path.file_name()?.to_string_lossy().to_string();
+    // "ms-python.python-2024.2.1-linux-x64": whatever follows the version is the platform
+    let prefix = format!("{}.{}-{}-", publisher, name, version).to_lowercase();
+    let target_platform = dir
+        .to_lowercase()
+        .starts_with(&prefix)
+        .then(|| dir[prefix.len()..].to_string());
+
+    Some(ExtensionDir {
+        id: format!("{}.{}", publisher, name).to_lowercase(),
+        display_name: text("displayName")
+            .map(|display_name| localized(path, &display_name))
+            .unwrap_or_else(|| name.clone()),
+        description: text("description").map(|description| localized(path, &description)),
+        publisher,
+        version,
+        target_platform,
+        dir,
+    })
+}
+
+/// Resolve a `%key%` placeholder through the extension's `package.nls.json`
+fn localized(path: &Path, value: &str) -> String {
+    let Some(key) = value
+        .strip_prefix('%')
+        .and_then(|value| value.strip_suffix('%'))
+    else {
+        return value.to_string();
+    };
+    fs::read_to_string(path.join("package.nls.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|nls| {
+            let entry = nls.get(key)?;
+            // Newer extensions store {"message": "...", "comment": [...]}
+            entry
+                .as_str()
+                .or_else(|| entry.get("message")?.as_str())
+                .map(str::to_string)
+        })
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Directory names of superseded versions
+///
+/// With `extensions.json`, a version is superseded when the file does not list it but
+/// lists another version of the same extension, or when `.obsolete` marks it.
+/// Without it, every version but the newest is.
+fn superseded_dirs(extensions_dir: &Path, dirs: &[ExtensionDir]) -> HashSet<String> {
+    let referenced = referenced_dirs(extensions_dir);
+    let obsolete = obsolete_dirs(extensions_dir);
+
+    dirs.iter()
+        .filter(|dir| {
+            let mut siblings = dirs
+                .iter()
+                .filter(|other| other.id == dir.id && other.dir != dir.dir);
+            match &referenced {
+                Some(referenced) => {
+                    !referenced.contains(&dir.dir)
+                        && (obsolete.contains(&dir.dir)
+                            || siblings.any(|other| referenced.contains(&other.dir)))
+                }
+                None => {
+                    siblings.any(|other| compare_versions(&other.version, &dir.version).is_gt())
+                }
+            }
+        })
+        .map(|dir| dir.dir.clone())
+        .collect()
+}
+
+/// Directory names listed in `extensions.json`, `None` when the file is missing
+fn referenced_dirs(extensions_dir: &Path) -> Option<HashSet<String>> {
+    let content = fs::read_to_string(extensions_dir.join("extensions.json")).ok()?;
+    let entries: Vec<Value> = serde_json::from_str(&content).ok()?;
+    Some(
+        entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .get("relativeLocation")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .or_else(|| {
+                        let path = entry.get("location")?.get("path")?.as_str()?;
+                        Some(Path::new(path).file_name()?.to_string_lossy().to_string())
+                    })
+            })
+            .collect(),
+    )
+}
+
+/// Directories the editor marked for deletion in `.obsolete` (`{"<dir>": true}`)
+fn obsolete_dirs(extensions_dir: &Path) -> HashSet<String> {
+    fs::read_to_string(extensions_dir.join(".obsolete"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<HashMap<String, Value>>(&content).ok())
+        .map(|obsolete| obsolete.into_keys().collect())
+        .unwrap_or_default()
+}
+
+/// Drop removed directories from `.obsolete` so the editor does not look for them
+fn forget_obsolete(extensions_dir: &Path, removed: &[String]) {
+    let path = extensions_dir.join(".obsolete");
+    let Some(mut obsolete) = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<HashMap<String, Value>>(&content).ok())
+    else {
+        return;
+    };
+    let before = obsolete.len();
+    obsolete.retain(|dir, _| !removed.contains(dir));
+    if obsolete.len() != before {
+        if let Ok(content) = serde_json::to_string(&obsolete) {
+            let _ = fs::write(&path, content);
+        }
+    }
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_extension(dir: &Path, folder: &str, version: &str) {
+        let path = dir.join(folder);
+        fs::create_dir_all(&path).unwrap();
+        fs::write(
+            path.join("package.json"),
+            format!(
+                r#"{{"name":"rust-analyzer","publisher":"rust-lang","version":"{}","displayName":"%displayName%"}}"#,
+                version
+            ),
+        )
+        .unwrap();
+        fs::write(
+            path.join("package.nls.json"),
+            r#"{"displayName":"rust-analyzer"}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn inventories_versions_and_finds_superseded_ones() {
+        let dir = env::temp_dir().join(format!("dev-janitor-extensions-{}", std::process::id()));
+        write_extension(&dir, "rust-lang.rust-analyzer-0.3.1-linux-x64", "0.3.1");
+        write_extension(&dir, "rust-lang.rust-analyzer-0.3.2-linux-x64", "0.3.2");
+
+        // Without extensions.json the newest version wins
+        let inventory = scan_extensions_dir("vscode", "VS Code", &dir);
+        let extension = &inventory.extensions[0];
+        assert_eq!(extension.id, "rust-lang.rust-analyzer");
+        assert_eq!(extension.display_name, "rust-analyzer");
+        assert_eq!(extension.versions[0].version, "0.3.2");
+        assert_eq!(
+            extension.versions[0].target_platform.as_deref(),
+            Some("linux-x64")
+        );
+        assert!(!extension.versions[0].superseded);
+        assert!(extension.versions[1].superseded);
+
+        // A version pinned in extensions.json is kept even when a newer one exists
+        fs::write(
+            dir.join("extensions.json"),
+            r#"[{"identifier":{"id":"rust-lang.rust-analyzer"},"version":"0.3.1","relativeLocation":"rust-lang.rust-analyzer-0.3.1-linux-x64"}]"#,
+        )
+        .unwrap();
+        let superseded = superseded_extension_dirs(&dir);
+        assert_eq!(superseded.len(), 1);
+        assert_eq!(superseded[0].1, "0.3.2");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -12,21 +12,32 @@ mod commands;
 mod config;
 mod detection;
 mod error;
+mod extensions;
+mod manifest;
 mod package_manager;
 mod security_scan;
 mod services;
+mod system_packages;
 mod utils;
 
 use commands::{
-    analyze_path_cmd, clean_cache_cmd, clean_multiple_caches, delete_ai_junk_cmd,
-    delete_chat_file_cmd, delete_multiple_ai_junk, delete_multiple_chat_files,
-    delete_project_chat_history_cmd, diagnose_env_cmd, get_ai_cli_tools_cmd, get_all_processes_cmd,
-    get_common_dev_ports_cmd, get_dev_processes_cmd, get_path_suggestions_cmd, get_ports_cmd,
-    get_security_tools_cmd, get_shell_configs_cmd, get_tool_info, get_total_cache_size,
-    install_ai_tool_cmd, kill_process_cmd, scan_ai_junk_cmd, scan_caches, scan_chat_history_cmd,
-    scan_global_chat_history_cmd, scan_packages, scan_project_caches_cmd, scan_security_cmd,
-    scan_tool_security_cmd, scan_tools, uninstall_ai_tool_cmd, uninstall_package, uninstall_tool,
-    update_ai_tool_cmd, update_package,
+    analyze_path_cmd, apply_manifest_cmd, audit_packages_cmd, batch_package_operation_cmd,
+    check_java_home_cmd, check_npm_prefix_cmd, check_project_requirements_cmd, clean_cache_cmd,
+    clean_multiple_caches, delete_ai_junk_cmd, delete_chat_file_cmd, delete_multiple_ai_junk,
+    delete_multiple_chat_files, delete_project_chat_history_cmd, delete_python_env_cmd,
+    diagnose_env_cmd, diff_manifest_cmd, export_manifest_cmd, fix_npm_prefix_cmd,
+    get_advisory_dir_cmd, get_ai_cli_tools_cmd, get_all_processes_cmd, get_common_dev_ports_cmd,
+    get_dependency_graph_cmd, get_dev_processes_cmd, get_homebrew_inventory_cmd,
+    get_package_dependencies_cmd, get_path_suggestions_cmd, get_ports_cmd, get_security_tools_cmd,
+    get_shell_configs_cmd, get_tool_info, get_tool_owner_cmd, get_tool_rules_cmd,
+    get_total_cache_size, hold_package_cmd, install_ai_tool_cmd, kill_process_cmd,
+    list_package_holds_cmd, plan_container_prune_cmd, prune_container_items_cmd,
+    release_package_hold_cmd, remove_superseded_extensions_cmd, scan_ai_junk_cmd, scan_caches,
+    scan_chat_history_cmd, scan_composer_projects_cmd, scan_container_storage_cmd,
+    scan_editor_extensions_cmd, scan_global_chat_history_cmd, scan_packages,
+    scan_project_caches_cmd, scan_python_envs_cmd, scan_security_cmd, scan_tool_security_cmd,
+    scan_tools, uninstall_ai_tool_cmd, uninstall_package, uninstall_runtime_version_cmd,
+    uninstall_system_package_cmd, uninstall_tool, update_ai_tool_cmd, update_package,
 };
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -45,16 +56,33 @@ pub fn run() {
             scan_tools,
             get_tool_info,
             uninstall_tool,
+            get_tool_owner_cmd,
+            uninstall_system_package_cmd,
+            uninstall_runtime_version_cmd,
+            get_tool_rules_cmd,
             // Package commands
             scan_packages,
             update_package,
             uninstall_package,
+            scan_python_envs_cmd,
+            delete_python_env_cmd,
+            get_dependency_graph_cmd,
+            get_package_dependencies_cmd,
+            batch_package_operation_cmd,
+            list_package_holds_cmd,
+            hold_package_cmd,
+            release_package_hold_cmd,
+            get_homebrew_inventory_cmd,
+            scan_composer_projects_cmd,
             // Cache commands
             scan_caches,
             scan_project_caches_cmd,
             clean_cache_cmd,
             clean_multiple_caches,
             get_total_cache_size,
+            scan_container_storage_cmd,
+            plan_container_prune_cmd,
+            prune_container_items_cmd,
             // AI Cleanup commands
             scan_ai_junk_cmd,
             delete_ai_junk_cmd,
@@ -76,6 +104,10 @@ pub fn run() {
             get_shell_configs_cmd,
             diagnose_env_cmd,
             get_path_suggestions_cmd,
+            check_npm_prefix_cmd,
+            fix_npm_prefix_cmd,
+            check_project_requirements_cmd,
+            check_java_home_cmd,
             // AI CLI tools commands
             get_ai_cli_tools_cmd,
             install_ai_tool_cmd,
@@ -85,6 +117,15 @@ pub fn run() {
             scan_security_cmd,
             scan_tool_security_cmd,
             get_security_tools_cmd,
+            audit_packages_cmd,
+            get_advisory_dir_cmd,
+            // Manifest commands
+            export_manifest_cmd,
+            diff_manifest_cmd,
+            apply_manifest_cmd,
+            // Editor extension commands
+            scan_editor_extensions_cmd,
+            remove_superseded_extensions_cmd,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
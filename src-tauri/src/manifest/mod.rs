@@ -0,0 +1,486 @@
+//! Toolchain manifest module for Dev Janitor v2
+//! Exports detected tools, global packages and AI CLIs to a versioned TOML/JSON file
+//! and applies a manifest on another machine by installing what is missing
+
+use chrono::Local;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::ai_cli::{get_ai_cli_tools, install_ai_tool};
+use crate::detection::scan_all_tools;
+use crate::package_manager::{create_manager, scan_all_packages};
+
+/// Manifest format version written by this build
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Versioned snapshot of a developer toolchain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolchainManifest {
+    pub manifest_version: u32,
+    pub generated_at: String,
+    pub platform: String,
+    #[serde(default)]
+    pub tools: Vec<ManifestTool>,
+    #[serde(default)]
+    pub packages: Vec<ManifestPackage>,
+    #[serde(default)]
+    pub ai_tools: Vec<ManifestAiTool>,
+}
+
+/// Active version of a detected runtime or tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestTool {
+    pub id: String,
+    pub name: String,
+    pub category: String,
+    pub version: String,
+}
+
+/// A global package and the manager that owns it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestPackage {
+    pub manager: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// An installed AI CLI tool
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ManifestAiTool {
+    pub id: String,
+    pub version: Option<String>,
+}
+
+/// One difference between a manifest and this machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDiffItem {
+    pub kind: String, // "tool", "package", "ai_tool"
+    pub manager: Option<String>,
+    pub name: String,
+    pub expected_version: Option<String>,
+    pub installed_version: Option<String>,
+    pub status: String, // "missing", "extra", "version_mismatch"
+}
+
+/// Result of comparing a manifest with the current machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDiff {
+    pub items: Vec<ManifestDiffItem>,
+    pub missing: usize,
+    pub extra: usize,
+    pub version_mismatch: usize,
+}
+
+/// Outcome of installing one missing manifest item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestApplyResult {
+    pub kind: String,
+    pub name: String,
+    pub status: String, // "installed", "failed", "manual"
+    pub message: String,
+}
+
+/// Build a manifest from the tools, packages and AI CLIs found on this machine
+pub fn build_manifest() -> ToolchainManifest {
+    let tools = scan_all_tools()
+        .into_iter()
+        // AI CLIs are tracked separately so they can be installed via ai_cli
+        .filter(|tool| tool.category != "ai_cli")
+        // Versions a manager installed but nothing puts on PATH are not part of the toolchain
+        .filter(|tool| tool.status != "not_in_path")
+        .filter_map(|tool| {
+            let active = tool
+                .versions
+                .iter()
+                .find(|version| version.is_active)
+                .or_else(|| tool.versions.first())?;
+            Some(ManifestTool {
+                id: tool.id.clone(),
+                name: tool.name.clone(),
+                category: tool.category.clone(),
+                version: active.version.clone(),
+            })
+        })
+        .collect();
+
+    let packages = scan_all_packages()
+        .into_iter()
+        .map(|pkg| ManifestPackage {
+            manager: pkg.manager,
+            name: pkg.name,
+            version: pkg.version,
+        })
+        .collect();
+
+    let ai_tools = get_ai_cli_tools()
+        .into_iter()
+        .filter(|tool| tool.installed)
+        .map(|tool| ManifestAiTool {
+            id: tool.id,
+            version: tool.version,
+        })
+        .collect();
+
+    ToolchainManifest {
+        manifest_version: MANIFEST_VERSION,
+        generated_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        platform: std::env::consts::OS.to_string(),
+        tools,
+        packages,
+        ai_tools,
+    }
+}
+
+fn is_toml_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.eq_ignore_ascii_case("toml"))
+        .unwrap_or(false)
+}
+
+/// Serialize a manifest as TOML or JSON depending on the file extension
+pub fn serialize_manifest(manifest: &ToolchainManifest, path: &Path) -> Result<String, String> {
+    if is_toml_path(path) {
+        toml::to_string_pretty(manifest)
+            .map_err(|error| format!("Failed to serialize manifest: {}", error))
+    } else {
+        serde_json::to_string_pretty(manifest)
+            .map_err(|error| format!("Failed to serialize manifest: {}", error))
+    }
+}
+
+/// Parse a TOML or JSON manifest and reject versions newer than this build understands
+pub fn parse_manifest(content: &str, path: &Path) -> Result<ToolchainManifest, String> {
+    let manifest: ToolchainManifest = if is_toml_path(path) {
+        toml::from_str(content).map_err(|error| format!("Invalid manifest: {}", error))?
+    } else {
+        serde_json::from_str(content).map_err(|error| format!("Invalid manifest: {}", error))?
+    };
+
+    if manifest.manifest_version > MANIFEST_VERSION {
+        return Err(format!(
+            "Manifest version {} is newer than the supported version {}",
+            manifest.manifest_version, MANIFEST_VERSION
+        ));
+    }
+
+    Ok(manifest)
+}
+
+/// Export the current toolchain to `path`
+pub fn export_manifest(path: &str) -> Result<String, String> {
+    let path = Path::new(path);
+    let manifest = build_manifest();
+    let content = serialize_manifest(&manifest, path)?;
+
+    fs::write(path, content)
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))?;
+
+    Ok(format!(
+        "Exported {} tools, {} packages and {} AI tools to {}",
+        manifest.tools.len(),
+        manifest.packages.len(),
+        manifest.ai_tools.len(),
+        path.display()
+    ))
+}
+
+/// Load a manifest file from disk
+pub fn load_manifest(path: &str) -> Result<ToolchainManifest, String> {
+    let path = Path::new(path);
+    let content = fs::read_to_string(path)
+        .map_err(|error| format!("Failed to read {}: {}", path.display(), error))?;
+    parse_manifest(&content, path)
+}
+
+fn diff_entries(
+    kind: &str,
+    expected: BTreeMap<(Option<String>, String), Option<String>>,
+    installed: BTreeMap<(Option<String>, String), Option<String>>,
+    items: &mut Vec<ManifestDiffItem>,
+) {
+    for ((manager, name), expected_version) in &expected {
+        let key = (manager.clone(), name.clone());
+        let status = match installed.get(&key) {
+            None => "missing",
+            Some(installed_version)
+                if expected_version.is_some() && installed_version != expected_version =>
+            {
+                "version_mismatch"
+            }
+            Some(_) => continue,
+        };
+
+        items.push(ManifestDiffItem {
+            kind: kind.to_string(),
+            manager: manager.clone(),
+            name: name.clone(),
+            expected_version: expected_version.clone(),
+            installed_version: installed.get(&key).cloned().flatten(),
+            status: status.to_string(),
+        });
+    }
+
+    for ((manager, name), installed_version) in installed {
+        if !expected.contains_key(&(manager.clone(), name.clone())) {
+            items.push(ManifestDiffItem {
+                kind: kind.to_string(),
+                manager,
+                name,
+                expected_version: None,
+                installed_version,
+                status: "extra".to_string(),
+            });
+        }
+    }
+}
+
+/// Compare a manifest with another (usually the current machine's)
+pub fn diff_manifests(expected: &ToolchainManifest, current: &ToolchainManifest) -> ManifestDiff {
+    let mut items = Vec::new();
+
+    let tools = |manifest: &ToolchainManifest| {
+        manifest
+            .tools
+            .iter()
+            .map(|tool| ((None, tool.id.clone()), Some(tool.version.clone())))
+            .collect()
+    };
+    diff_entries("tool", tools(expected), tools(current), &mut items);
+
+    let packages = |manifest: &ToolchainManifest| {
+        manifest
+            .packages
+            .iter()
+            .map(|pkg| {
+                (
+                    (Some(pkg.manager.clone()), pkg.name.clone()),
+                    Some(pkg.version.clone()),
+                )
+            })
+            .collect()
+    };
+    diff_entries("package", packages(expected), packages(current), &mut items);
+
+    let ai_tools = |manifest: &ToolchainManifest| {
+        manifest
+            .ai_tools
+            .iter()
+            .map(|tool| ((None, tool.id.clone()), tool.version.clone()))
+            .collect()
+    };
+    diff_entries("ai_tool", ai_tools(expected), ai_tools(current), &mut items);
+
+    let count = |status: &str| items.iter().filter(|item| item.status == status).count();
+
+    ManifestDiff {
+        missing: count("missing"),
+        extra: count("extra"),
+        version_mismatch: count("version_mismatch"),
+        items,
+    }
+}
+
+/// Diff a manifest file against this machine
+pub fn diff_manifest_file(path: &str) -> Result<ManifestDiff, String> {
+    let expected = load_manifest(path)?;
+    Ok(diff_manifests(&expected, &build_manifest()))
+}
+
+/// Registry package names each manager accepts; anything else (options, URLs, git or
+/// path specs, third-party taps and channels) could install code the diff never shows
+fn registry_name_pattern(manager: &str) -> Option<&'static str> {
+    match manager {
+        "npm" => Some(r"^(@[a-z0-9][a-z0-9._~-]*/)?[A-Za-z0-9][A-Za-z0-9._~-]*$"),
+        // PEP 508 names, without extras, URLs or markers
+        "pip" => Some(r"^[A-Za-z0-9]([A-Za-z0-9._-]*[A-Za-z0-9])?$"),
+        "cargo" => Some(r"^[A-Za-z][A-Za-z0-9_-]*$"),
+        "composer" => Some(r"^[a-z0-9]([_.-]?[a-z0-9]+)*/[a-z0-9](([_.]|-{1,2})?[a-z0-9]+)*$"),
+        "conda" => Some(r"^[a-z0-9_][a-z0-9._-]*$"),
+        "homebrew" => Some(r"^[a-z0-9][a-z0-9@+._-]*$"),
+        _ => None,
+    }
+}
+
+/// Refuse manifest entries that are not a plain registry name and version
+fn check_package_spec(manager: &str, name: &str, version: Option<&str>) -> Result<(), String> {
+    let pattern = registry_name_pattern(manager)
+        .ok_or_else(|| format!("Unknown package manager: {}", manager))?;
+    let valid_name = name.len() <= 214
+        && Regex::new(pattern)
+            .map(|regex| regex.is_match(name))
+            .unwrap_or(false);
+    if !valid_name {
+        return Err(format!(
+            "Refusing to install {:?}: not a plain {} package name",
+            name, manager
+        ));
+    }
+
+    // "1.2.3", "2.0b1", "1.0.0-rc.1", "v20.11.0"
+    let plain_version = |version: &str| {
+        version
+            .trim_start_matches('v')
+            .starts_with(|c: char| c.is_ascii_digit())
+            && version
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || ".+_-".contains(c))
+    };
+    match version {
+        Some(version) if !plain_version(version) => Err(format!(
+            "Refusing to install {} {:?}: not a plain version",
+            name, version
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Install every missing package and AI tool listed in the manifest
+pub fn apply_manifest(path: &str) -> Result<Vec<ManifestApplyResult>, String> {
+    let diff = diff_manifest_file(path)?;
+
+    let results = diff
+        .items
+        .into_iter()
+        .filter(|item| item.status == "missing")
+        .map(|item| {
+            let outcome = match item.kind.as_str() {
+                "package" => {
+                    let manager = item.manager.as_deref().unwrap_or_default();
+                    check_package_spec(manager, &item.name, item.expected_version.as_deref())
+                        .and_then(|_| create_manager(manager))
+                        .and_then(|package_manager| {
+                            package_manager
+                                .install_package(&item.name, item.expected_version.as_deref())
+                                .map(|outcome| outcome.summary())
+                                .map_err(String::from)
+                        })
+                }
+                "ai_tool" => install_ai_tool(&item.name),
+                _ => {
+                    return ManifestApplyResult {
+                        kind: item.kind,
+                        message: format!(
+                            "Install {} {} manually or through its version manager",
+                            item.name,
+                            item.expected_version.unwrap_or_default()
+                        ),
+                        name: item.name,
+                        status: "manual".to_string(),
+                    };
+                }
+            };
+
+            let (status, message) = match outcome {
+                Ok(message) => ("installed", message),
+                Err(message) => ("failed", message),
+            };
+
+            ManifestApplyResult {
+                kind: item.kind,
+                name: item.name,
+                status: status.to_string(),
+                message,
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(packages: &[(&str, &str, &str)], ai_tools: &[&str]) -> ToolchainManifest {
+        ToolchainManifest {
+            manifest_version: MANIFEST_VERSION,
+            generated_at: "2026-01-01 00:00:00".to_string(),
+            platform: "linux".to_string(),
+            tools: vec![ManifestTool {
+                id: "node".to_string(),
+                name: "Node.js".to_string(),
+                category: "runtime".to_string(),
+                version: "20.11.0".to_string(),
+            }],
+            packages: packages
+                .iter()
+                .map(|(manager, name, version)| ManifestPackage {
+                    manager: manager.to_string(),
+                    name: name.to_string(),
+                    version: version.to_string(),
+                })
+                .collect(),
+            ai_tools: ai_tools
+                .iter()
+                .map(|id| ManifestAiTool {
+                    id: id.to_string(),
+                    version: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn rejects_package_specs_outside_the_registry() {
+        assert!(check_package_spec("npm", "@types/node", Some("20.11.0")).is_ok());
+        assert!(check_package_spec("pip", "zope.interface", Some("6.1")).is_ok());
+        assert!(check_package_spec("composer", "laravel/installer", Some("v5.2.0")).is_ok());
+        assert!(check_package_spec("pip", "--index-url=http://evil", None).is_err());
+        assert!(check_package_spec("pip", "poetry", Some("-e.")).is_err());
+        assert!(check_package_spec("pip", "pkg @ https://host/x.whl", None).is_err());
+        assert!(check_package_spec("npm", "github:user/repo", None).is_err());
+        assert!(check_package_spec("npm", "git+https://host/repo.git", None).is_err());
+        assert!(check_package_spec("npm", "left-pad", Some("file:../x")).is_err());
+        assert!(check_package_spec("homebrew", "user/tap/formula", None).is_err());
+        assert!(check_package_spec("conda", "conda-forge::numpy", None).is_err());
+        assert!(check_package_spec("gem", "rails", None).is_err());
+    }
+
+    #[test]
+    fn round_trips_toml_and_json() {
+        let original = manifest(&[("npm", "typescript", "5.4.5")], &["codex"]);
+
+        for file in ["toolchain.toml", "toolchain.json"] {
+            let path = Path::new(file);
+            let content = serialize_manifest(&original, path).unwrap();
+            let parsed = parse_manifest(&content, path).unwrap();
+            assert_eq!(parsed.packages, original.packages);
+            assert_eq!(parsed.ai_tools, original.ai_tools);
+        }
+
+        let future = "manifest_version = 99\ngenerated_at = \"\"\nplatform = \"linux\"\n";
+        assert!(parse_manifest(future, Path::new("future.toml")).is_err());
+    }
+
+    #[test]
+    fn diffs_missing_extra_and_mismatched_items() {
+        let expected = manifest(
+            &[("npm", "typescript", "5.4.5"), ("pip", "poetry", "1.8.3")],
+            &["codex"],
+        );
+        let current = manifest(
+            &[
+                ("npm", "typescript", "5.3.0"),
+                ("cargo", "ripgrep", "14.1.0"),
+            ],
+            &[],
+        );
+
+        let diff = diff_manifests(&expected, &current);
+        assert_eq!(diff.missing, 2);
+        assert_eq!(diff.extra, 1);
+        assert_eq!(diff.version_mismatch, 1);
+
+        let poetry = diff
+            .items
+            .iter()
+            .find(|item| item.name == "poetry")
+            .unwrap();
+        assert_eq!(poetry.status, "missing");
+        assert_eq!(poetry.manager.as_deref(), Some("pip"));
+    }
+}
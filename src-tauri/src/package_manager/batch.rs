@@ -0,0 +1,339 @@
+//! Batch package operations
+//! Groups packages by manager, runs managers in parallel and packages sequentially
+
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::holds::{latest_version, HoldStore};
+use super::{create_manager, PackageManager};
+use crate::error::DevJanitorError;
+use crate::utils::command::CommandOutcome;
+
+/// Operation applied to every package in a batch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchOperation {
+    Update,
+    Uninstall,
+}
+
+/// A package selected for a batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPackageRequest {
+    pub manager: String,
+    pub name: String,
+}
+
+/// Result for one package, streamed as soon as it is known
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemResult {
+    pub manager: String,
+    pub name: String,
+    pub status: String, // "success", "failed", "skipped"
+    pub message: String,
+    #[serde(default)]
+    pub outcome: Option<CommandOutcome>, // set when a command actually ran
+}
+
+/// Final report of a batch operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub operation: BatchOperation,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub results: Vec<BatchItemResult>,
+}
+
+impl BatchItemResult {
+    fn new(manager: &str, name: &str, status: &str, message: String) -> Self {
+        Self {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            status: status.to_string(),
+            message,
+            outcome: None,
+        }
+    }
+
+    fn from_outcome(manager: &str, name: &str, status: &str, outcome: CommandOutcome) -> Self {
+        Self {
+            outcome: Some(outcome.clone()),
+            ..Self::new(manager, name, status, outcome.summary())
+        }
+    }
+}
+
+/// Run `operation` on every requested package, reporting each result through `on_result`
+pub fn run_batch<F>(
+    operation: BatchOperation,
+    requests: Vec<BatchPackageRequest>,
+    on_result: F,
+) -> BatchSummary
+where
+    F: Fn(&BatchItemResult) + Sync,
+{
+    run_batch_with(
+        operation,
+        requests,
+        &HoldStore::load(),
+        create_manager,
+        on_result,
+    )
+}
+
+fn run_batch_with<C, F>(
+    operation: BatchOperation,
+    requests: Vec<BatchPackageRequest>,
+    holds: &HoldStore,
+    create: C,
+    on_result: F,
+) -> BatchSummary
+where
+    C: Fn(&str) -> Result<Box<dyn PackageManager>, String> + Sync,
+    F: Fn(&BatchItemResult) + Sync,
+{
+    let mut groups: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut duplicates = Vec::new();
+    let mut seen = BTreeSet::new();
+
+    for request in requests {
+        if seen.insert((request.manager.clone(), request.name.clone())) {
+            groups
+                .entry(request.manager)
+                .or_default()
+                .push(request.name);
+        } else {
+            duplicates.push(BatchItemResult::new(
+                &request.manager,
+                &request.name,
+                "skipped",
+                "Duplicate request".to_string(),
+            ));
+        }
+    }
+
+    let mut results: Vec<BatchItemResult> = groups
+        .par_iter()
+        .flat_map_iter(|(manager, names)| {
+            run_manager_group(operation, manager, names, holds, &create, &on_result)
+        })
+        .collect();
+
+    for duplicate in duplicates {
+        on_result(&duplicate);
+        results.push(duplicate);
+    }
+
+    let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+
+    BatchSummary {
+        operation,
+        total: results.len(),
+        succeeded: count("success"),
+        failed: count("failed"),
+        skipped: count("skipped"),
+        results,
+    }
+}
+
+fn run_manager_group<C, F>(
+    operation: BatchOperation,
+    manager: &str,
+    names: &[String],
+    holds: &HoldStore,
+    create: &C,
+    on_result: &F,
+) -> Vec<BatchItemResult>
+where
+    C: Fn(&str) -> Result<Box<dyn PackageManager>, String>,
+    F: Fn(&BatchItemResult),
+{
+    let report = |result: BatchItemResult| {
+        on_result(&result);
+        result
+    };
+
+    // One manager instance serves the whole group instead of one per package
+    let package_manager = match create(manager) {
+        Ok(package_manager) => package_manager,
+        Err(error) => {
+            return names
+                .iter()
+                .map(|name| {
+                    report(BatchItemResult::new(
+                        manager,
+                        name,
+                        "skipped",
+                        error.clone(),
+                    ))
+                })
+                .collect();
+        }
+    };
+
+    // Packages still needed by something outside the batch must not be removed
+    let graph = match operation {
+        BatchOperation::Uninstall => package_manager.dependency_graph(),
+        BatchOperation::Update => None,
+    };
+    let removing: BTreeSet<&str> = names.iter().map(String::as_str).collect();
+
+    names
+        .iter()
+        .map(|name| {
+            if let Some(hold) = holds.find(manager, name).filter(|hold| {
+                operation == BatchOperation::Uninstall
+                    || !hold.allows_update(|| latest_version(package_manager.as_ref(), name))
+            }) {
+                let reason = match &hold.version_constraint {
+                    Some(constraint) => format!("Held at {}", constraint),
+                    None => "Held".to_string(),
+                };
+                return report(BatchItemResult::new(manager, name, "skipped", reason));
+            }
+
+            if let Some(node) = graph.as_ref().and_then(|graph| graph.find(name)) {
+                let blockers: Vec<&str> = node
+                    .required_by
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|parent| !removing.contains(parent))
+                    .collect();
+                if !blockers.is_empty() {
+                    return report(BatchItemResult::new(
+                        manager,
+                        name,
+                        "skipped",
+                        format!("Required by: {}", blockers.join(", ")),
+                    ));
+                }
+            }
+
+            let outcome = match operation {
+                BatchOperation::Update => package_manager.update_package(name),
+                BatchOperation::Uninstall => package_manager.uninstall_package(name),
+            };
+
+            report(match outcome {
+                Ok(outcome) => BatchItemResult::from_outcome(manager, name, "success", outcome),
+                Err(DevJanitorError::PackageOperation(outcome)) => {
+                    BatchItemResult::from_outcome(manager, name, "failed", *outcome)
+                }
+                Err(error) => BatchItemResult::new(manager, name, "failed", error.to_string()),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::FailureKind;
+    use crate::package_manager::{OperationResult, PackageInfo};
+    use std::fs;
+    use std::sync::Mutex;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    struct FakeManager;
+
+    impl PackageManager for FakeManager {
+        fn name(&self) -> &str {
+            "fake"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn get_version(&self) -> Option<String> {
+            None
+        }
+
+        fn list_packages(&self) -> Vec<PackageInfo> {
+            Vec::new()
+        }
+
+        fn install_package(&self, name: &str, _version: Option<&str>) -> OperationResult {
+            Ok(fake_outcome("install", name, None))
+        }
+
+        fn update_package(&self, name: &str) -> OperationResult {
+            if name == "broken" {
+                fake_outcome("update", name, Some(FailureKind::Network)).into_result()
+            } else {
+                Ok(fake_outcome("update", name, None))
+            }
+        }
+
+        fn uninstall_package(&self, name: &str) -> OperationResult {
+            Ok(fake_outcome("uninstall", name, None))
+        }
+    }
+
+    fn fake_outcome(action: &str, name: &str, failure: Option<FailureKind>) -> CommandOutcome {
+        CommandOutcome {
+            command: format!("fake {} {}", action, name),
+            exit_code: Some(if failure.is_some() { 1 } else { 0 }),
+            failure,
+            ..Default::default()
+        }
+    }
+
+    fn request(manager: &str, name: &str) -> BatchPackageRequest {
+        BatchPackageRequest {
+            manager: manager.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn summarizes_and_streams_batch_results() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let holds_path = std::env::temp_dir().join(format!("dev-janitor-batch-holds-{nanos}.json"));
+        fs::write(
+            &holds_path,
+            r#"[{"manager":"fake","name":"poetry","version_constraint":null,"created_at":"","native":false}]"#,
+        )
+        .unwrap();
+        let holds = HoldStore::load_from(Some(holds_path.clone()));
+
+        let streamed = Mutex::new(Vec::new());
+        let summary = run_batch_with(
+            BatchOperation::Update,
+            vec![
+                request("fake", "typescript"),
+                request("fake", "broken"),
+                request("fake", "typescript"),
+                request("fake", "poetry"),
+                request("missing", "poetry"),
+            ],
+            &holds,
+            |manager| {
+                if manager == "fake" {
+                    Ok(Box::new(FakeManager) as Box<dyn PackageManager>)
+                } else {
+                    Err(format!("{} is not available", manager))
+                }
+            },
+            |result| streamed.lock().unwrap().push(result.name.clone()),
+        );
+
+        assert_eq!(summary.total, 5);
+        assert_eq!(summary.succeeded, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.skipped, 3);
+        assert_eq!(streamed.lock().unwrap().len(), 5);
+
+        let broken = summary.results.iter().find(|r| r.name == "broken").unwrap();
+        let outcome = broken.outcome.as_ref().unwrap();
+        assert_eq!(outcome.failure, Some(FailureKind::Network));
+
+        fs::remove_file(holds_path).unwrap();
+    }
+}
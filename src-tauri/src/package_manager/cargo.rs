@@ -1,15 +1,34 @@
 //! Cargo package manager support
 
-use super::{PackageInfo, PackageManager};
+use super::footprint::{measure_packages, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
 use regex::Regex;
+use serde::Deserialize;
 
 use crate::utils::command::command_output_with_timeout;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::time::Duration;
 
 pub struct CargoManager {
     version: String,
 }
 
+/// `$CARGO_HOME/.crates2.json`, keyed by "name version (source)"
+#[derive(Deserialize)]
+struct Crates2 {
+    #[serde(default)]
+    installs: HashMap<String, Crates2Install>,
+}
+
+#[derive(Deserialize)]
+struct Crates2Install {
+    #[serde(default)]
+    bins: Vec<String>,
+}
+
 impl CargoManager {
     pub fn new() -> Option<Self> {
         let output = run_cargo_command(&["--version"])?;
@@ -64,27 +83,78 @@ impl PackageManager for CargoManager {
                         manager: "cargo".to_string(),
                         is_outdated: false,
                         description: None,
+                        ..Default::default()
                     });
                 }
             }
         }
 
+        // `cargo install` only leaves binaries behind, recorded per crate in .crates2.json
+        if let Some(cargo_home) = cargo_home() {
+            let bins = installed_bins(&cargo_home);
+            let bin_dir = cargo_home.join("bin");
+            measure_packages(&mut packages, |pkg| {
+                let paths: Vec<PathBuf> = bins
+                    .get(&pkg.name)?
+                    .iter()
+                    .map(|bin| bin_dir.join(bin))
+                    .collect();
+                let footprint = Footprint::of_paths(paths.iter().map(PathBuf::as_path));
+                Some((paths.into_iter().next()?, footprint))
+            });
+        }
+
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_cargo_command(&["install", name, "--force"]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult {
+        let mut args = vec!["install", name];
+        if let Some(version) = version {
+            args.extend(["--version", version]);
         }
+        run_operation("cargo", &args)
     }
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_cargo_command(&["uninstall", name]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
-        }
+    fn update_package(&self, name: &str) -> OperationResult {
+        run_operation("cargo", &["install", name, "--force"])
     }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        run_operation("cargo", &["uninstall", name])
+    }
+}
+
+fn cargo_home() -> Option<PathBuf> {
+    env::var("CARGO_HOME")
+        .ok()
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .or_else(|_| env::var("USERPROFILE"))
+                .ok()
+                .map(|home| PathBuf::from(home).join(".cargo"))
+        })
+}
+
+/// Binaries installed by each crate, keyed by crate name
+fn installed_bins(cargo_home: &std::path::Path) -> HashMap<String, Vec<String>> {
+    let crates2: Crates2 = match fs::read_to_string(cargo_home.join(".crates2.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+    {
+        Some(crates2) => crates2,
+        None => return HashMap::new(),
+    };
+
+    crates2
+        .installs
+        .into_iter()
+        .filter_map(|(key, install)| {
+            let name = key.split_whitespace().next()?.to_string();
+            Some((name, install.bins))
+        })
+        .collect()
 }
 
 fn run_cargo_command(args: &[&str]) -> Option<String> {
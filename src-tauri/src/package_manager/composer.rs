@@ -1,10 +1,20 @@
 //! Composer (PHP) package manager support
+//! Global packages plus per-project vendors checked against the installed PHP versions
 
-use super::{PackageInfo, PackageManager};
-use serde::Deserialize;
+use super::footprint::{measure_packages, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
 
+use crate::cache::{format_size, get_dir_size};
+use crate::detection::detect_tool_by_id;
 use crate::utils::command::command_output_with_timeout;
-use std::time::Duration;
+use crate::utils::version::satisfies_constraint;
 
 pub struct ComposerManager {
     version: String,
@@ -71,25 +81,43 @@ impl PackageManager for ComposerManager {
                     manager: "composer".to_string(),
                     is_outdated: false,
                     description: pkg.description,
+                    ..Default::default()
                 });
             }
         }
 
+        // Global packages live in `<global vendor-dir>/<vendor>/<package>`
+        if let Some(vendor_dir) =
+            run_composer_command(&["global", "config", "vendor-dir", "--absolute"])
+        {
+            let vendor_dir = PathBuf::from(vendor_dir.trim());
+            measure_packages(&mut packages, |pkg| {
+                let path = vendor_dir.join(&pkg.name);
+                if !path.exists() {
+                    return None;
+                }
+                let footprint = Footprint::of_paths([path.as_path()]);
+                Some((path, footprint))
+            });
+        }
+
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_composer_command(&["global", "update", name]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
-        }
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult {
+        let spec = match version {
+            Some(version) => format!("{}:{}", name, version),
+            None => name.to_string(),
+        };
+        run_operation("composer", &["global", "require", &spec])
     }
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_composer_command(&["global", "remove", name]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
-        }
+    fn update_package(&self, name: &str) -> OperationResult {
+        run_operation("composer", &["global", "update", name])
+    }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        run_operation("composer", &["global", "remove", name])
     }
 }
 
@@ -102,3 +130,258 @@ fn run_composer_command(args: &[&str]) -> Option<String> {
         None
     }
 }
+
+/// A PHP interpreter found by tool detection, with its loaded extensions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPhp {
+    pub version: String,
+    pub path: String,
+    pub extensions: Vec<String>, // lowercase `php -m` names
+}
+
+/// A `php` or `ext-*` requirement of the project or one of its locked packages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformRequirement {
+    pub package: String, // "root" for the project's own composer.json
+    pub requirement: String,
+    pub constraint: String,
+    pub satisfied_by: Vec<String>, // installed PHP versions meeting it
+}
+
+/// A Composer project (composer.json) under a scanned root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerProject {
+    pub path: String,
+    pub name: Option<String>,
+    pub lock_path: Option<String>,
+    pub vendor_path: Option<String>,
+    pub vendor_size: u64,
+    pub vendor_size_display: String,
+    pub package_count: usize, // locked packages, dev included
+    pub php_constraint: Option<String>,
+    pub requirements: Vec<PlatformRequirement>, // root ones plus any an installed PHP fails
+    pub compatible_php: Vec<String>,
+    pub incompatible: bool, // no installed PHP satisfies every platform requirement
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposerProjectReport {
+    pub php_versions: Vec<InstalledPhp>,
+    pub projects: Vec<ComposerProject>,
+}
+
+/// Find Composer projects under `root` and check their platform requirements
+pub fn scan_composer_projects(root: &str, max_depth: usize) -> ComposerProjectReport {
+    let php_versions = installed_php_versions();
+
+    let manifests: Vec<PathBuf> = WalkDir::new(root)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            !(entry.file_type().is_dir()
+                && (name == "vendor" || name == "node_modules" || name.starts_with('.'))
+                && entry.depth() > 0)
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file() && entry.file_name() == "composer.json")
+        .map(|entry| entry.into_path())
+        .collect();
+
+    let mut projects: Vec<ComposerProject> = manifests
+        .par_iter()
+        .filter_map(|manifest| inspect_project(manifest, &php_versions))
+        .collect();
+    projects.sort_by(|a, b| {
+        b.incompatible
+            .cmp(&a.incompatible)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+
+    ComposerProjectReport {
+        php_versions,
+        projects,
+    }
+}
+
+/// PHP versions from tool detection, each queried for its extensions
+fn installed_php_versions() -> Vec<InstalledPhp> {
+    detect_tool_by_id("php")
+        .map(|tool| tool.versions)
+        .unwrap_or_default()
+        .into_par_iter()
+        .filter(|php| php.version != "unknown")
+        .map(|php| {
+            let extensions =
+                command_output_with_timeout(&php.path, &["-m"], Duration::from_secs(10))
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .map(|output| parse_php_modules(&String::from_utf8_lossy(&output.stdout)))
+                    .unwrap_or_default();
+            InstalledPhp {
+                version: php.version,
+                path: php.path,
+                extensions,
+            }
+        })
+        .collect()
+}
+
+fn inspect_project(manifest: &Path, php_versions: &[InstalledPhp]) -> Option<ComposerProject> {
+    let project_dir = manifest.parent()?;
+    let composer_json: Value = serde_json::from_str(&fs::read_to_string(manifest).ok()?).ok()?;
+
+    let lock_path = project_dir.join("composer.lock");
+    let lock: Option<Value> = fs::read_to_string(&lock_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+
+    let vendor_dir = composer_json
+        .pointer("/config/vendor-dir")
+        .and_then(Value::as_str)
+        .unwrap_or("vendor");
+    let vendor_path = project_dir.join(vendor_dir);
+    let vendor_size = if vendor_path.is_dir() {
+        get_dir_size(&vendor_path)
+    } else {
+        0
+    };
+
+    // The lock's "platform" mirrors composer.json at lock time; fall back to the manifest
+    let root_requirements = lock
+        .as_ref()
+        .and_then(|lock| lock.get("platform"))
+        .or_else(|| composer_json.get("require"));
+    let mut requirements: Vec<(String, String, String)> =
+        platform_requirements("root", root_requirements);
+    let mut package_count = 0;
+    if let Some(lock) = &lock {
+        for section in ["packages", "packages-dev"] {
+            for package in lock
+                .get(section)
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+            {
+                package_count += 1;
+                let name = package.get("name").and_then(Value::as_str).unwrap_or("?");
+                requirements.extend(platform_requirements(name, package.get("require")));
+            }
+        }
+    }
+
+    let php_constraint = requirements
+        .iter()
+        .find(|(package, requirement, _)| package == "root" && requirement == "php")
+        .map(|(_, _, constraint)| constraint.clone());
+
+    let requirements: Vec<PlatformRequirement> = requirements
+        .into_iter()
+        .map(|(package, requirement, constraint)| PlatformRequirement {
+            satisfied_by: php_versions
+                .iter()
+                .filter(|php| php_meets(php, &requirement, &constraint))
+                .map(|php| php.version.clone())
+                .collect(),
+            package,
+            requirement,
+            constraint,
+        })
+        .collect();
+
+    let compatible_php: Vec<String> = php_versions
+        .iter()
+        .filter(|php| {
+            requirements
+                .iter()
+                .all(|requirement| requirement.satisfied_by.contains(&php.version))
+        })
+        .map(|php| php.version.clone())
+        .collect();
+    let incompatible = !requirements.is_empty() && compatible_php.is_empty();
+
+    Some(ComposerProject {
+        path: project_dir.to_string_lossy().to_string(),
+        name: composer_json
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        lock_path: lock
+            .is_some()
+            .then(|| lock_path.to_string_lossy().to_string()),
+        vendor_path: vendor_path
+            .is_dir()
+            .then(|| vendor_path.to_string_lossy().to_string()),
+        vendor_size,
+        vendor_size_display: format_size(vendor_size),
+        package_count,
+        php_constraint,
+        requirements: requirements
+            .into_iter()
+            .filter(|requirement| {
+                requirement.package == "root" || requirement.satisfied_by.len() < php_versions.len()
+            })
+            .collect(),
+        compatible_php,
+        incompatible,
+    })
+}
+
+/// `php`/`php-64bit` and `ext-*` entries of a require map as (package, requirement, constraint)
+fn platform_requirements(package: &str, require: Option<&Value>) -> Vec<(String, String, String)> {
+    require
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(requirement, _)| {
+            *requirement == "php" || *requirement == "php-64bit" || requirement.starts_with("ext-")
+        })
+        .filter_map(|(requirement, constraint)| {
+            Some((
+                package.to_string(),
+                requirement.to_lowercase(),
+                constraint.as_str()?.to_string(),
+            ))
+        })
+        .collect()
+}
+
+fn php_meets(php: &InstalledPhp, requirement: &str, constraint: &str) -> bool {
+    match requirement.strip_prefix("ext-") {
+        Some(extension) => php.extensions.iter().any(|loaded| loaded == extension),
+        None => satisfies_constraint(&php.version, constraint),
+    }
+}
+
+/// Extension names from `php -m`, lowercased with spaces as dashes ("Zend OPcache" -> "zend-opcache")
+fn parse_php_modules(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('['))
+        .map(|line| line.to_lowercase().replace(' ', "-"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_composer_constraints() {
+        let php = InstalledPhp {
+            version: "8.1.27".to_string(),
+            path: "/usr/bin/php".to_string(),
+            extensions: parse_php_modules(
+                "[PHP Modules]\nmbstring\nZend OPcache\n\n[Zend Modules]\n",
+            ),
+        };
+        assert!(php_meets(&php, "ext-zend-opcache", "*"));
+        assert!(!php_meets(&php, "ext-intl", "*"));
+        assert!(!php_meets(&php, "php", ">=8.2"));
+
+        let require: Value =
+            serde_json::json!({"php": ">=8.2", "ext-mbstring": "*", "laravel/framework": "^11"});
+        assert_eq!(platform_requirements("root", Some(&require)).len(), 2);
+    }
+}
@@ -1,9 +1,14 @@
 //! Conda package manager support
 
-use super::{PackageInfo, PackageManager};
+use super::dependencies::{exact_name, DependencyGraph, DependencyNode};
+use super::footprint::{measure_packages, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
 use serde::Deserialize;
 
 use crate::utils::command::command_output_with_timeout;
+use crate::utils::version::range_upper_bound;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 pub struct CondaManager {
@@ -17,6 +22,30 @@ struct CondaPackage {
     channel: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct CondaInfo {
+    active_prefix: Option<String>,
+    root_prefix: String,
+}
+
+#[derive(Deserialize)]
+struct CondaMetaRecord {
+    name: String,
+    version: String,
+    #[serde(default)]
+    depends: Vec<String>,
+    #[serde(default)]
+    files: Vec<String>,
+    #[serde(default)]
+    extracted_package_dir: Option<String>, // the package's copy in the pkgs cache
+}
+
+#[derive(Deserialize)]
+struct CondaHistoryExport {
+    #[serde(default)]
+    dependencies: Vec<serde_json::Value>,
+}
+
 impl CondaManager {
     pub fn new() -> Option<Self> {
         let output = run_conda_command(&["--version"])?;
@@ -70,25 +99,246 @@ impl PackageManager for CondaManager {
                 manager: "conda".to_string(),
                 is_outdated: false,
                 description: pkg.channel,
+                ..Default::default()
+            });
+        }
+
+        // conda-meta records every file a package linked into the environment prefix
+        if let Some(prefix) = conda_prefix() {
+            let records = read_conda_meta(&prefix);
+            measure_packages(&mut packages, |pkg| {
+                let record = records.iter().find(|record| record.name == pkg.name)?;
+                let footprint =
+                    Footprint::of_files(record.files.iter().map(|file| (prefix.join(file), None)));
+                let install_path = record
+                    .extracted_package_dir
+                    .as_ref()
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| prefix.clone());
+                Some((install_path, footprint))
             });
         }
 
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_conda_command(&["update", "-y", name]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
-        }
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult {
+        let spec = match version {
+            Some(version) => format!("{}={}", name, version),
+            None => name.to_string(),
+        };
+        run_operation("conda", &["install", "-y", &spec])
+    }
+
+    fn update_package(&self, name: &str) -> OperationResult {
+        run_operation("conda", &["update", "-y", name])
+    }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        run_operation("conda", &["remove", "-y", name])
+    }
+
+    fn set_native_hold(
+        &self,
+        name: &str,
+        version_constraint: Option<&str>,
+        held: bool,
+    ) -> Option<Result<String, String>> {
+        // A constraint with no MatchSpec equivalent stays an app-level hold only, and
+        // an earlier native pin for the package is dropped
+        let constraint = match version_constraint.filter(|_| held) {
+            Some(constraint) => match match_spec_version(constraint) {
+                Some(spec) => Some(spec),
+                None => {
+                    if let Some(prefix) = conda_prefix() {
+                        let pinned = prefix.join("conda-meta").join("pinned");
+                        let _ = update_pinned_file(&pinned, name, None);
+                    }
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        let prefix = match conda_prefix() {
+            Some(prefix) => prefix,
+            None => return Some(Err("Failed to locate the conda prefix".to_string())),
+        };
+
+        // Without an explicit constraint, pin the version that is installed now
+        let spec = if held {
+            let version = constraint.or_else(|| {
+                self.list_packages()
+                    .into_iter()
+                    .find(|pkg| pkg.name == name)
+                    .map(|pkg| format!("=={}", pkg.version))
+            });
+            Some(match version {
+                Some(version) => format!("{} {}", name, version),
+                None => name.to_string(),
+            })
+        } else {
+            None
+        };
+
+        Some(update_pinned_file(
+            &prefix.join("conda-meta").join("pinned"),
+            name,
+            spec.as_deref(),
+        ))
+    }
+
+    fn dependency_graph(&self) -> Option<DependencyGraph> {
+        let prefix = conda_prefix()?.to_string_lossy().to_string();
+
+        // Specs the user explicitly asked for, as recorded in conda-meta/history; without
+        // that record every package counts as requested so none is offered as an orphan
+        let requested =
+            run_conda_command(&["env", "export", "--from-history", "--json", "-p", &prefix])
+                .and_then(|export| parse_history_specs(&export));
+
+        let nodes = read_conda_meta(Path::new(&prefix))
+            .into_iter()
+            .map(|record| DependencyNode {
+                is_user_requested: requested
+                    .as_ref()
+                    .is_none_or(|requested| requested.contains(&record.name)),
+                dependencies: record
+                    .depends
+                    .iter()
+                    .filter_map(|spec| spec.split_whitespace().next())
+                    .map(str::to_string)
+                    .collect(),
+                name: record.name,
+                version: record.version,
+                required_by: Vec::new(),
+            })
+            .collect();
+
+        Some(DependencyGraph::from_nodes("conda", nodes, exact_name))
     }
+}
+
+/// Prefix of the environment `conda list` reports on (active env, else base)
+fn conda_prefix() -> Option<PathBuf> {
+    let info: CondaInfo = serde_json::from_str(&run_conda_command(&["info", "--json"])?).ok()?;
+    Some(PathBuf::from(
+        info.active_prefix.unwrap_or(info.root_prefix),
+    ))
+}
+
+/// MatchSpec version for a hold constraint ("5.4.x" -> "5.4.*", "^1.2" -> ">=1.2,<2");
+/// `None` when conda cannot express it (alternatives, hyphen ranges, aliases)
+fn match_spec_version(constraint: &str) -> Option<String> {
+    if constraint.contains('|') || constraint.contains(" - ") {
+        return None;
+    }
+
+    // Operators may be separated from their version by a space (">= 1.2")
+    let mut specs = Vec::new();
+    let mut pending_op = String::new();
+    for token in constraint.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        if token.chars().all(|c| "<>=!^~".contains(c)) {
+            pending_op = token.to_string();
+            continue;
+        }
+        let comparator = format!("{}{}", pending_op, token);
+        pending_op.clear();
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_conda_command(&["remove", "-y", name]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
+        let (op, base) = [">=", "<=", "!=", "==", "~=", ">", "<", "=", "^", "~"]
+            .iter()
+            .find_map(|op| comparator.strip_prefix(op).map(|base| (*op, base)))
+            .unwrap_or(("", comparator.as_str()));
+        let base = base.trim_start_matches(['v', 'V']);
+        let base = base
+            .strip_suffix(".x")
+            .map_or(base.to_string(), |prefix| format!("{}.*", prefix));
+        let plain = base.starts_with(|c: char| c.is_ascii_digit())
+            && base
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '*');
+        if !plain && base != "*" {
+            return None;
         }
+
+        specs.push(match op {
+            "^" | "~" | "~=" => format!(">={},<{}", base, range_upper_bound(op, &base)?),
+            // A single "=" is a fuzzy match in conda but an exact one here
+            "=" => format!("=={}", base),
+            _ => format!("{}{}", op, base),
+        });
     }
+    (!specs.is_empty()).then(|| specs.join(","))
+}
+
+/// Package name of a `conda-meta/pinned` line ("numpy 1.26.*", "numpy==1.26", "conda-forge::numpy")
+fn pinned_name(line: &str) -> &str {
+    let spec = line.trim().rsplit("::").next().unwrap_or("");
+    spec.split(|c: char| c.is_whitespace() || "=<>!~[".contains(c))
+        .next()
+        .unwrap_or("")
+}
+
+/// Replace the `conda-meta/pinned` entry for `name`; `spec` of `None` removes it
+fn update_pinned_file(pinned: &Path, name: &str, spec: Option<&str>) -> Result<String, String> {
+    let existing = fs::read_to_string(pinned).unwrap_or_default();
+    let mut lines: Vec<String> = existing
+        .lines()
+        .filter(|line| pinned_name(line) != name)
+        .map(str::to_string)
+        .collect();
+
+    if let Some(spec) = spec {
+        lines.push(spec.to_string());
+    }
+
+    let mut content = lines.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+
+    fs::write(pinned, content)
+        .map(|_| format!("Updated {}", pinned.display()))
+        .map_err(|error| format!("Failed to write {}: {}", pinned.display(), error))
+}
+
+fn read_conda_meta(prefix: &Path) -> Vec<CondaMetaRecord> {
+    let entries = match fs::read_dir(prefix.join("conda-meta")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect()
+}
+
+/// Package names from `conda env export --from-history --json` (e.g. `python=3.11`);
+/// `None` when the export is not valid JSON
+fn parse_history_specs(output: &str) -> Option<Vec<String>> {
+    let export: CondaHistoryExport = serde_json::from_str(output).ok()?;
+
+    let specs = export
+        .dependencies
+        .iter()
+        .filter_map(|dep| dep.as_str())
+        .map(|spec| {
+            let spec = spec.rsplit("::").next().unwrap_or(spec);
+            spec.split(['=', '<', '>', '!', ' ', '['])
+                .next()
+                .unwrap_or(spec)
+                .to_string()
+        })
+        .filter(|name| !name.is_empty())
+        .collect();
+    Some(specs)
 }
 
 fn run_conda_command(args: &[&str]) -> Option<String> {
@@ -100,3 +350,46 @@ fn run_conda_command(args: &[&str]) -> Option<String> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn translates_hold_constraints_to_match_specs() {
+        assert_eq!(match_spec_version("5.4.x").as_deref(), Some("5.4.*"));
+        assert_eq!(match_spec_version("^1.2").as_deref(), Some(">=1.2,<2"));
+        assert_eq!(
+            match_spec_version("~1.2.3").as_deref(),
+            Some(">=1.2.3,<1.3")
+        );
+        assert_eq!(match_spec_version(">= 1.2 <2").as_deref(), Some(">=1.2,<2"));
+        assert_eq!(match_spec_version("=1.26.4").as_deref(), Some("==1.26.4"));
+        assert_eq!(match_spec_version("^7.4 || ^8.0"), None);
+        assert_eq!(match_spec_version("lts"), None);
+    }
+
+    #[test]
+    fn replaces_pinned_entries_written_without_spaces() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let pinned = std::env::temp_dir().join(format!("dev-janitor-conda-pinned-{nanos}"));
+        fs::write(
+            &pinned,
+            "numpy==1.26\nconda-forge::scipy=1.11\nnumpyro 0.13.*\n",
+        )
+        .unwrap();
+
+        update_pinned_file(&pinned, "numpy", Some("numpy 1.26.*")).unwrap();
+        update_pinned_file(&pinned, "scipy", None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&pinned).unwrap(),
+            "numpyro 0.13.*\nnumpy 1.26.*\n"
+        );
+
+        fs::remove_file(pinned).unwrap();
+    }
+}
@@ -0,0 +1,260 @@
+//! Dependency graph support for global packages
+//! Answers "why is this installed" and finds orphaned dependencies
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A package in a manager's dependency graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyNode {
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<String>,
+    pub required_by: Vec<String>,
+    pub is_user_requested: bool,
+}
+
+impl DependencyNode {
+    pub fn new(name: &str, version: &str, dependencies: &[&str], is_user_requested: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+            required_by: Vec::new(),
+            is_user_requested,
+        }
+    }
+}
+
+/// Dependency graph for all packages of a single manager
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub manager: String,
+    pub nodes: Vec<DependencyNode>,
+}
+
+/// Dependency summary for one package, used before uninstalling it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageDependencyReport {
+    pub name: String,
+    pub manager: String,
+    pub is_user_requested: bool,
+    pub dependencies: Vec<String>,
+    pub required_by: Vec<String>,
+    pub orphans_after_uninstall: Vec<String>,
+}
+
+impl DependencyGraph {
+    /// Build a graph from nodes whose `dependencies` may name packages that are not
+    /// installed. Those are dropped and `required_by` is derived from the rest.
+    pub fn from_nodes(
+        manager: &str,
+        mut nodes: Vec<DependencyNode>,
+        normalize: fn(&str) -> String,
+    ) -> Self {
+        let installed: HashMap<String, String> = nodes
+            .iter()
+            .map(|node| (normalize(&node.name), node.name.clone()))
+            .collect();
+
+        for node in &mut nodes {
+            let mut resolved: Vec<String> = node
+                .dependencies
+                .iter()
+                .filter_map(|dep| installed.get(&normalize(dep)).cloned())
+                .filter(|dep| *dep != node.name)
+                .collect();
+            resolved.sort();
+            resolved.dedup();
+            node.dependencies = resolved;
+            node.required_by.clear();
+        }
+
+        let mut reverse: HashMap<String, Vec<String>> = HashMap::new();
+        for node in &nodes {
+            for dep in &node.dependencies {
+                reverse
+                    .entry(dep.clone())
+                    .or_default()
+                    .push(node.name.clone());
+            }
+        }
+
+        for node in &mut nodes {
+            if let Some(mut parents) = reverse.remove(&node.name) {
+                parents.sort();
+                node.required_by = parents;
+            }
+        }
+
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self {
+            manager: manager.to_string(),
+            nodes,
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&DependencyNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Packages that nothing needs anymore once `name` is removed
+    pub fn orphans_after_removal(&self, name: &str) -> Vec<String> {
+        let Some(target) = self.find(name) else {
+            return Vec::new();
+        };
+
+        let mut removed: HashSet<&str> = HashSet::new();
+        removed.insert(target.name.as_str());
+
+        loop {
+            let newly_orphaned: Vec<&str> = self
+                .nodes
+                .iter()
+                .filter(|node| !removed.contains(node.name.as_str()))
+                .filter(|node| !node.is_user_requested)
+                // Only packages pulled in by something we removed are candidates
+                .filter(|node| {
+                    node.required_by
+                        .iter()
+                        .any(|parent| removed.contains(parent.as_str()))
+                })
+                .filter(|node| {
+                    node.required_by
+                        .iter()
+                        .all(|parent| removed.contains(parent.as_str()))
+                })
+                .map(|node| node.name.as_str())
+                .collect();
+
+            if newly_orphaned.is_empty() {
+                break;
+            }
+            removed.extend(newly_orphaned);
+        }
+
+        let mut orphans: Vec<String> = removed
+            .into_iter()
+            .filter(|orphan| *orphan != target.name)
+            .map(str::to_string)
+            .collect();
+        orphans.sort();
+        orphans
+    }
+
+    /// Summarize why `name` is installed and what removing it leaves behind
+    pub fn report(&self, name: &str) -> Option<PackageDependencyReport> {
+        let node = self.find(name)?;
+
+        Some(PackageDependencyReport {
+            name: node.name.clone(),
+            manager: self.manager.clone(),
+            is_user_requested: node.is_user_requested,
+            dependencies: node.dependencies.clone(),
+            required_by: node.required_by.clone(),
+            orphans_after_uninstall: self.orphans_after_removal(&node.name),
+        })
+    }
+}
+
+/// Case-sensitive identity for managers with exact package names
+pub fn exact_name(name: &str) -> String {
+    name.to_string()
+}
+
+/// PEP 503 name normalization (case-insensitive, `-`, `_` and `.` are equivalent)
+pub fn normalize_python_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    let mut last_was_separator = false;
+
+    for ch in name.trim().chars() {
+        if matches!(ch, '-' | '_' | '.') {
+            if !last_was_separator {
+                normalized.push('-');
+            }
+            last_was_separator = true;
+        } else {
+            normalized.push(ch.to_ascii_lowercase());
+            last_was_separator = false;
+        }
+    }
+
+    normalized
+}
+
+/// Extract the package name from a requirement such as `idna (<4,>=2.5)` or
+/// `urllib3>=1.21 ; python_version >= "3.8"`. Extras-only requirements are skipped.
+pub fn requirement_name(requirement: &str) -> Option<String> {
+    let (spec, marker) = match requirement.split_once(';') {
+        Some((spec, marker)) => (spec, Some(marker)),
+        None => (requirement, None),
+    };
+
+    if marker.map(|m| m.contains("extra")).unwrap_or(false) {
+        return None;
+    }
+
+    let name: String = spec
+        .trim()
+        .chars()
+        .take_while(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.'))
+        .collect();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> DependencyGraph {
+        DependencyGraph::from_nodes(
+            "pip",
+            vec![
+                DependencyNode::new("requests", "2.32.3", &["urllib3", "idna"], true),
+                DependencyNode::new("httpx", "0.27.0", &["idna", "missing-pkg"], true),
+                DependencyNode::new("urllib3", "2.2.2", &[], false),
+                DependencyNode::new("idna", "3.7", &[], false),
+            ],
+            normalize_python_name,
+        )
+    }
+
+    #[test]
+    fn derives_reverse_dependencies_and_orphans() {
+        let graph = sample_graph();
+
+        let idna = graph.find("idna").unwrap();
+        assert_eq!(idna.required_by, vec!["httpx", "requests"]);
+
+        let httpx = graph.find("httpx").unwrap();
+        assert_eq!(httpx.dependencies, vec!["idna"]);
+
+        assert_eq!(graph.orphans_after_removal("requests"), vec!["urllib3"]);
+        assert!(graph.orphans_after_removal("idna").is_empty());
+    }
+
+    #[test]
+    fn parses_python_requirements() {
+        assert_eq!(
+            requirement_name("idna (<4,>=2.5)"),
+            Some("idna".to_string())
+        );
+        assert_eq!(
+            requirement_name("urllib3>=1.21 ; python_version >= \"3.8\""),
+            Some("urllib3".to_string())
+        );
+        assert_eq!(requirement_name("PySocks!=1.5.7 ; extra == 'socks'"), None);
+        assert_eq!(
+            normalize_python_name("Zope.Interface_x"),
+            "zope-interface-x"
+        );
+    }
+}
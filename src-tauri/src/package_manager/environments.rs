@@ -0,0 +1,572 @@
+//! Python environment discovery for Dev Janitor v2
+//! Enumerates conda environments, pyenv versions and virtualenvs (pyvenv.cfg)
+
+use super::PackageInfo;
+use chrono::{DateTime, Local};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::cache::{format_size, get_dir_size};
+use crate::utils::command::{command_output_with_timeout, command_output_with_timeout_vec};
+
+/// Represents a single Python environment and its installed packages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonEnvironment {
+    pub id: String,
+    pub name: String,
+    pub kind: String, // "conda", "pyenv", "venv"
+    pub path: String,
+    pub interpreter: Option<String>,
+    pub python_version: Option<String>,
+    pub packages: Vec<PackageInfo>,
+    pub size: u64,
+    pub size_display: String,
+    pub last_used: Option<String>,
+    pub is_base: bool,
+}
+
+#[derive(Deserialize)]
+struct CondaEnvList {
+    envs: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CondaMetaRecord {
+    name: String,
+    version: String,
+    channel: Option<String>,
+}
+
+/// Directories that never contain virtualenvs worth reporting
+const SKIPPED_SCAN_DIRS: &[&str] = &["node_modules", ".git", "target", "__pycache__"];
+
+/// Scan conda environments, pyenv versions and virtualenvs found under `venv_root`
+pub fn scan_python_environments(
+    venv_root: Option<&str>,
+    max_depth: usize,
+) -> Vec<PythonEnvironment> {
+    let mut candidates: Vec<(&'static str, PathBuf)> = Vec::new();
+
+    candidates.extend(
+        conda_environment_paths()
+            .into_iter()
+            .map(|path| ("conda", path)),
+    );
+    candidates.extend(
+        pyenv_version_paths()
+            .into_iter()
+            .map(|path| ("pyenv", path)),
+    );
+    if let Some(root) = venv_root {
+        candidates.extend(
+            find_virtualenvs(Path::new(root), max_depth)
+                .into_iter()
+                .map(|path| ("venv", path)),
+        );
+    }
+
+    // pyenv can host conda installs and venvs can live inside scan roots twice
+    let mut seen = HashSet::new();
+    candidates.retain(|(_, path)| {
+        let key = path.canonicalize().unwrap_or_else(|_| path.clone());
+        seen.insert(key)
+    });
+
+    let mut environments: Vec<PythonEnvironment> = candidates
+        .par_iter()
+        .map(|(kind, path)| build_environment(kind, path))
+        .collect();
+
+    environments.sort_by(|a, b| b.size.cmp(&a.size));
+    environments
+}
+
+/// List conda environment prefixes via `conda env list --json`
+fn conda_environment_paths() -> Vec<PathBuf> {
+    let output = match command_output_with_timeout(
+        "conda",
+        &["env", "list", "--json"],
+        Duration::from_secs(30),
+    ) {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    parse_conda_env_list(&stdout)
+        .into_iter()
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn parse_conda_env_list(output: &str) -> Vec<PathBuf> {
+    serde_json::from_str::<CondaEnvList>(output)
+        .map(|list| list.envs.into_iter().map(PathBuf::from).collect())
+        .unwrap_or_default()
+}
+
+/// List interpreters installed under `$PYENV_ROOT/versions`
+fn pyenv_version_paths() -> Vec<PathBuf> {
+    let root = env::var("PYENV_ROOT")
+        .map(PathBuf::from)
+        .ok()
+        .or_else(|| user_home_dir().map(|home| home.join(".pyenv")));
+
+    let versions_dir = match root {
+        Some(root) => root.join("versions"),
+        None => return Vec::new(),
+    };
+
+    let entries = match fs::read_dir(&versions_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .flatten()
+        // pyenv-virtualenv exposes aliases as symlinks; the real dirs are enough
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.path())
+        .collect()
+}
+
+/// Find virtualenvs (directories containing `pyvenv.cfg`) below a root
+fn find_virtualenvs(root: &Path, max_depth: usize) -> Vec<PathBuf> {
+    if !root.is_dir() {
+        return Vec::new();
+    }
+
+    let mut venvs = Vec::new();
+    let mut walker = WalkDir::new(root).max_depth(max_depth).into_iter();
+
+    while let Some(entry) = walker.next() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy();
+        if SKIPPED_SCAN_DIRS.iter().any(|skip| name == *skip) {
+            walker.skip_current_dir();
+            continue;
+        }
+
+        if entry.path().join("pyvenv.cfg").is_file() {
+            venvs.push(entry.path().to_path_buf());
+            walker.skip_current_dir();
+        }
+    }
+
+    venvs
+}
+
+fn build_environment(kind: &str, path: &Path) -> PythonEnvironment {
+    let is_conda = is_conda_prefix(path);
+    let interpreter = find_interpreter(path);
+    let python_version = read_python_version(path, interpreter.as_deref());
+    let packages = if is_conda {
+        list_conda_meta_packages(path)
+    } else {
+        list_site_packages(path)
+    };
+    let size = get_dir_size(&path.to_path_buf());
+
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+
+    PythonEnvironment {
+        id: format!("{:x}", md5::compute(path.to_string_lossy().as_bytes())),
+        name,
+        kind: kind.to_string(),
+        path: path.to_string_lossy().to_string(),
+        interpreter: interpreter.map(|p| p.to_string_lossy().to_string()),
+        python_version,
+        packages,
+        size,
+        size_display: format_size(size),
+        last_used: last_used_time(path),
+        is_base: is_conda_base(path),
+    }
+}
+
+fn is_conda_prefix(path: &Path) -> bool {
+    path.join("conda-meta").is_dir()
+}
+
+/// Only the root conda install ships `condabin`; named envs never do
+fn is_conda_base(path: &Path) -> bool {
+    is_conda_prefix(path) && path.join("condabin").is_dir()
+}
+
+fn find_interpreter(path: &Path) -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let candidates = [
+        path.join("python.exe"),
+        path.join("Scripts").join("python.exe"),
+    ];
+
+    #[cfg(not(target_os = "windows"))]
+    let candidates = [
+        path.join("bin").join("python3"),
+        path.join("bin").join("python"),
+    ];
+
+    candidates.into_iter().find(|candidate| candidate.exists())
+}
+
+fn read_python_version(path: &Path, interpreter: Option<&Path>) -> Option<String> {
+    // venvs record the base interpreter version in pyvenv.cfg
+    if let Ok(content) = fs::read_to_string(path.join("pyvenv.cfg")) {
+        if let Some(version) = parse_pyvenv_version(&content) {
+            return Some(version);
+        }
+    }
+
+    // conda keeps a metadata record for the python package itself
+    if let Some(pkg) = list_conda_meta_packages(path)
+        .into_iter()
+        .find(|pkg| pkg.name == "python")
+    {
+        return Some(pkg.version);
+    }
+
+    let interpreter = interpreter?.to_string_lossy().to_string();
+    let output = command_output_with_timeout_vec(
+        &interpreter,
+        &["--version".to_string()],
+        Duration::from_secs(6),
+    )
+    .ok()?;
+    let combined = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    combined
+        .trim()
+        .strip_prefix("Python ")
+        .map(|version| version.trim().to_string())
+}
+
+fn parse_pyvenv_version(content: &str) -> Option<String> {
+    let mut version_info = None;
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "version" => return Some(value.trim().to_string()),
+            // uv and virtualenv write "version_info = 3.12.1.final.0"
+            "version_info" => {
+                let parts: Vec<&str> = value.trim().split('.').take(3).collect();
+                version_info = Some(parts.join("."));
+            }
+            _ => {}
+        }
+    }
+
+    version_info
+}
+
+/// Read packages from `conda-meta/*.json` without spawning conda per environment
+fn list_conda_meta_packages(path: &Path) -> Vec<PackageInfo> {
+    let entries = match fs::read_dir(path.join("conda-meta")) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut packages: Vec<PackageInfo> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|p| p.extension().and_then(|ext| ext.to_str()) == Some("json"))
+        .filter_map(|p| fs::read_to_string(p).ok())
+        .filter_map(|content| serde_json::from_str::<CondaMetaRecord>(&content).ok())
+        .map(|record| PackageInfo {
+            name: record.name,
+            version: record.version,
+            latest: None,
+            manager: "conda".to_string(),
+            is_outdated: false,
+            description: record.channel,
+            ..Default::default()
+        })
+        .collect();
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    packages
+}
+
+/// Read packages from `*.dist-info` metadata in every site-packages directory
+fn list_site_packages(path: &Path) -> Vec<PackageInfo> {
+    let mut packages: Vec<PackageInfo> = site_packages_dirs(path)
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .map(|entry| entry.path())
+        .filter(|p| {
+            p.extension()
+                .and_then(|ext| ext.to_str())
+                .map(|ext| ext == "dist-info")
+                .unwrap_or(false)
+        })
+        .filter_map(|dist_info| fs::read_to_string(dist_info.join("METADATA")).ok())
+        .filter_map(|metadata| parse_dist_metadata(&metadata))
+        .map(|(name, version)| PackageInfo {
+            name,
+            version,
+            latest: None,
+            manager: "pip".to_string(),
+            is_outdated: false,
+            description: None,
+            ..Default::default()
+        })
+        .collect();
+
+    packages.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    packages
+}
+
+pub(crate) fn site_packages_dirs(path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    // Windows layout
+    let windows_site = path.join("Lib").join("site-packages");
+    if windows_site.is_dir() {
+        dirs.push(windows_site);
+    }
+
+    // Unix layout: lib/pythonX.Y/site-packages
+    if let Ok(entries) = fs::read_dir(path.join("lib")) {
+        for entry in entries.flatten() {
+            let site = entry.path().join("site-packages");
+            if entry.file_name().to_string_lossy().starts_with("python") && site.is_dir() {
+                dirs.push(site);
+            }
+        }
+    }
+
+    dirs
+}
+
+/// Extract `Name` and `Version` from a core metadata (RFC 822 style) header
+pub(crate) fn parse_dist_metadata(content: &str) -> Option<(String, String)> {
+    let mut name = None;
+    let mut version = None;
+
+    for line in content.lines() {
+        // Headers end at the first blank line; the body is the long description
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Name:") {
+            name = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Version:") {
+            version = Some(value.trim().to_string());
+        }
+    }
+
+    Some((name?, version?))
+}
+
+/// Most recent activity marker for an environment
+fn last_used_time(path: &Path) -> Option<String> {
+    let markers = [
+        path.join("pyvenv.cfg"),
+        path.join("conda-meta").join("history"),
+        path.join("bin"),
+        path.join("Scripts"),
+    ];
+
+    markers
+        .iter()
+        .filter_map(|marker| fs::metadata(marker).ok())
+        .filter_map(|meta| {
+            let accessed = meta.accessed().ok();
+            let modified = meta.modified().ok();
+            accessed.max(modified)
+        })
+        .max()
+        .map(format_system_time)
+}
+
+fn format_system_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn user_home_dir() -> Option<PathBuf> {
+    env::var("HOME")
+        .or_else(|_| env::var("USERPROFILE"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+fn validate_environment_delete_target(path: &Path) -> Result<PathBuf, String> {
+    let metadata = fs::symlink_metadata(path)
+        .map_err(|error| format!("Failed to inspect {}: {}", path.display(), error))?;
+    if metadata.file_type().is_symlink() {
+        return Err(format!(
+            "Refusing to delete symlink path: {}",
+            path.display()
+        ));
+    }
+
+    let canonical = path
+        .canonicalize()
+        .map_err(|error| format!("Failed to resolve {}: {}", path.display(), error))?;
+
+    let is_home = user_home_dir()
+        .and_then(|home| home.canonicalize().ok())
+        .map(|home| home == canonical)
+        .unwrap_or(false);
+    if canonical.parent().is_none() || is_home {
+        return Err(format!(
+            "Refusing to delete unsafe path: {}",
+            canonical.display()
+        ));
+    }
+
+    if is_conda_base(&canonical) {
+        return Err(format!(
+            "Refusing to delete the conda base environment: {}",
+            canonical.display()
+        ));
+    }
+
+    let is_pyenv_version = canonical
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .map(|name| name == "versions")
+        .unwrap_or(false)
+        && find_interpreter(&canonical).is_some();
+
+    if canonical.join("pyvenv.cfg").is_file() || is_conda_prefix(&canonical) || is_pyenv_version {
+        Ok(canonical)
+    } else {
+        Err(format!(
+            "Path is not a recognized Python environment: {}",
+            canonical.display()
+        ))
+    }
+}
+
+/// Delete a whole Python environment
+pub fn delete_python_environment(path: &str) -> Result<String, String> {
+    let env_path = PathBuf::from(path);
+
+    if !env_path.exists() {
+        return Err(format!("Path does not exist: {}", path));
+    }
+
+    let env_path = validate_environment_delete_target(&env_path)?;
+    let size_before = get_dir_size(&env_path);
+
+    // Let conda drop the prefix so its environments registry stays in sync
+    if is_conda_prefix(&env_path) {
+        let prefix = env_path.to_string_lossy().to_string();
+        let removed_by_conda = command_output_with_timeout(
+            "conda",
+            &["env", "remove", "-y", "-p", &prefix],
+            Duration::from_secs(120),
+        )
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+
+        if removed_by_conda && !env_path.exists() {
+            return Ok(format!(
+                "Removed conda environment {} (freed {})",
+                path,
+                format_size(size_before)
+            ));
+        }
+    }
+
+    fs::remove_dir_all(&env_path)
+        .map(|_| {
+            format!(
+                "Removed environment {} (freed {})",
+                path,
+                format_size(size_before)
+            )
+        })
+        .map_err(|error| format!("Failed to remove {}: {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("dev-janitor-python-envs-{name}-{nanos}"));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_pyvenv_versions() {
+        assert_eq!(
+            parse_pyvenv_version("home = /usr/bin\nversion = 3.11.4\n"),
+            Some("3.11.4".to_string())
+        );
+        assert_eq!(
+            parse_pyvenv_version("home = /usr/bin\nversion_info = 3.12.1.final.0\n"),
+            Some("3.12.1".to_string())
+        );
+        assert_eq!(
+            parse_conda_env_list(r#"{"envs": ["/opt/conda", "/opt/conda/envs/ml"]}"#),
+            vec![
+                PathBuf::from("/opt/conda"),
+                PathBuf::from("/opt/conda/envs/ml")
+            ]
+        );
+    }
+
+    #[test]
+    fn discovers_virtualenv_packages_and_deletes_it() {
+        let root = temp_root("venv");
+        let venv = root.join("project").join(".venv");
+        let site = venv.join("lib").join("python3.12").join("site-packages");
+        fs::create_dir_all(site.join("requests-2.32.3.dist-info")).unwrap();
+        fs::write(venv.join("pyvenv.cfg"), "version = 3.12.4\n").unwrap();
+        fs::write(
+            site.join("requests-2.32.3.dist-info").join("METADATA"),
+            "Metadata-Version: 2.1\nName: requests\nVersion: 2.32.3\n\nName: not-a-header\n",
+        )
+        .unwrap();
+
+        let found = find_virtualenvs(&root, 4);
+        assert_eq!(found, vec![venv.clone()]);
+
+        let environment = build_environment("venv", &venv);
+        assert_eq!(environment.python_version.as_deref(), Some("3.12.4"));
+        assert_eq!(environment.packages.len(), 1);
+        assert_eq!(environment.packages[0].name, "requests");
+        assert_eq!(environment.packages[0].version, "2.32.3");
+
+        assert!(delete_python_environment(root.join("project").to_str().unwrap()).is_err());
+        assert!(delete_python_environment(venv.to_str().unwrap()).is_ok());
+        assert!(!venv.exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
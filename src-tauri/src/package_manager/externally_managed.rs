@@ -0,0 +1,155 @@
+//! PEP 668 support
+//! Detects externally managed interpreters and distro-owned Python packages
+//! so pip never modifies what the OS package manager owns
+
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::utils::command::command_output_with_timeout;
+
+/// Values of a dist-info `INSTALLER` file written by OS package managers
+const DISTRO_INSTALLERS: &[&str] = &["debian", "dpkg", "rpm", "dnf", "pacman", "apk", "portage"];
+
+const INTERPRETER_QUERY: &str = r#"import json, os, sys, sysconfig
+marker = os.path.join(sysconfig.get_path("stdlib"), "EXTERNALLY-MANAGED")
+print(json.dumps({
+    "in_venv": sys.prefix != getattr(sys, "base_prefix", sys.prefix),
+    "marker": marker if os.path.isfile(marker) else None,
+}))"#;
+
+#[derive(Deserialize)]
+struct InterpreterQuery {
+    in_venv: bool,
+    marker: Option<String>,
+}
+
+/// PEP 668 state of a Python interpreter
+#[derive(Debug, Clone, Default)]
+pub struct ExternallyManaged {
+    pub marker_path: Option<String>,
+    pub message: Option<String>,
+}
+
+impl ExternallyManaged {
+    /// Query `program` (a Python interpreter); `None` when it is not externally managed
+    pub fn detect(program: &str) -> Option<Self> {
+        let output = command_output_with_timeout(
+            program,
+            &["-c", INTERPRETER_QUERY],
+            Duration::from_secs(10),
+        )
+        .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+
+        let query: InterpreterQuery =
+            serde_json::from_str(String::from_utf8_lossy(&output.stdout).trim()).ok()?;
+
+        // A virtual environment is never externally managed, even on a marked base
+        if query.in_venv {
+            return None;
+        }
+
+        let marker = query.marker?;
+        let message = fs::read_to_string(&marker)
+            .ok()
+            .and_then(|content| parse_marker_message(&content));
+
+        Some(Self {
+            marker_path: Some(marker),
+            message,
+        })
+    }
+
+    pub fn reason(&self) -> String {
+        match &self.message {
+            Some(message) => format!("This Python environment is externally managed: {}", message),
+            None => "This Python environment is externally managed by the OS (PEP 668)".to_string(),
+        }
+    }
+}
+
+/// The `Error=` value of an EXTERNALLY-MANAGED file, including continuation lines
+pub fn parse_marker_message(content: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .skip_while(|line| !line.trim_start().to_ascii_lowercase().starts_with("error"));
+
+    let first = lines.next()?.split_once('=')?.1.trim().to_string();
+    let continuation = lines
+        .take_while(|line| line.starts_with(' ') || line.starts_with('\t'))
+        .map(str::trim);
+
+    let message = std::iter::once(first.as_str())
+        .chain(continuation)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    (!message.is_empty()).then_some(message)
+}
+
+/// Whether a package reported by `pip list -v` belongs to the OS package manager
+pub fn is_distro_owned(installer: Option<&str>, location: Option<&str>) -> bool {
+    match installer.map(|installer| installer.trim().to_ascii_lowercase()) {
+        Some(installer) if !installer.is_empty() => DISTRO_INSTALLERS.contains(&installer.as_str()),
+        // No INSTALLER file: trust the location (distro paths live under /usr, not /usr/local)
+        _ => location
+            .map(Path::new)
+            .map(|path| path.starts_with("/usr") && !path.starts_with("/usr/local"))
+            .unwrap_or(false),
+    }
+}
+
+/// Where to install a Python application instead of the system interpreter
+pub fn isolated_install_suggestion(name: &str) -> String {
+    if which::which("pipx").is_ok() {
+        format!("pipx install {}", name)
+    } else if which::which("uv").is_ok() {
+        format!("uv tool install {}", name)
+    } else {
+        format!(
+            "Install pipx or uv and run `pipx install {}`, or use a virtual environment",
+            name
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_marker_and_distro_ownership() {
+        let marker = "[externally-managed]\nError=To install Python packages system-wide, try apt install\n python3-xyz, where xyz is the package.\n\n";
+        assert_eq!(
+            parse_marker_message(marker).as_deref(),
+            Some("To install Python packages system-wide, try apt install python3-xyz, where xyz is the package.")
+        );
+        assert_eq!(parse_marker_message("[externally-managed]\n"), None);
+
+        assert!(is_distro_owned(
+            Some("debian\n"),
+            Some("/usr/lib/python3/dist-packages")
+        ));
+        assert!(!is_distro_owned(
+            Some("pip"),
+            Some("/usr/lib/python3/dist-packages")
+        ));
+        assert!(is_distro_owned(
+            None,
+            Some("/usr/lib/python3.12/site-packages")
+        ));
+        assert!(!is_distro_owned(
+            None,
+            Some("/usr/local/lib/python3.12/dist-packages")
+        ));
+        assert!(!is_distro_owned(
+            None,
+            Some("/home/dev/.local/lib/python3.12/site-packages")
+        ));
+    }
+}
@@ -0,0 +1,137 @@
+//! Disk footprint of installed packages
+//! Sums file sizes under each package's install location and tracks the last access
+
+use chrono::{DateTime, Local};
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use super::dependencies::normalize_python_name;
+use super::PackageInfo;
+use crate::cache::format_size;
+
+/// Size on disk and most recent access of a set of files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Footprint {
+    pub size: u64,
+    pub last_accessed: Option<SystemTime>,
+}
+
+impl Footprint {
+    /// Walk directories (and plain files) and total everything inside them
+    pub fn of_paths<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Self {
+        paths
+            .into_iter()
+            .flat_map(|path| WalkDir::new(path).into_iter().filter_map(|e| e.ok()))
+            .filter_map(|entry| entry.metadata().ok())
+            .filter(|metadata| metadata.is_file())
+            .fold(Self::default(), |footprint, metadata| {
+                footprint.add(metadata.len(), last_touched(&metadata))
+            })
+    }
+
+    /// Total a list of files, trusting a recorded size when one is given (pip RECORD)
+    pub fn of_files(files: impl IntoIterator<Item = (PathBuf, Option<u64>)>) -> Self {
+        files
+            .into_iter()
+            .fold(Self::default(), |footprint, (path, recorded)| {
+                let metadata = fs::metadata(&path).ok();
+                let size = recorded
+                    .or_else(|| metadata.as_ref().map(|metadata| metadata.len()))
+                    .unwrap_or(0);
+                footprint.add(size, metadata.as_ref().and_then(last_touched))
+            })
+    }
+
+    fn add(self, size: u64, touched: Option<SystemTime>) -> Self {
+        Self {
+            size: self.size + size,
+            last_accessed: self.last_accessed.max(touched),
+        }
+    }
+
+    /// Record the footprint on a scanned package
+    pub fn apply(self, package: &mut PackageInfo, install_path: &Path) {
+        package.size = Some(self.size);
+        package.size_display = Some(format_size(self.size));
+        package.last_accessed = self.last_accessed.map(|time| {
+            DateTime::<Local>::from(time)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        });
+        package.install_path = Some(install_path.to_string_lossy().to_string());
+    }
+}
+
+/// Access time where the filesystem tracks it, otherwise modification time
+fn last_touched(metadata: &fs::Metadata) -> Option<SystemTime> {
+    metadata.accessed().ok().max(metadata.modified().ok())
+}
+
+/// The `<name>-<version>.dist-info` directory of a Python distribution in `site_packages`
+pub fn find_dist_info(site_packages: &Path, name: &str, version: &str) -> Option<PathBuf> {
+    let wanted = normalize_python_name(name);
+    fs::read_dir(site_packages)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|file_name| file_name.strip_suffix(".dist-info"))
+                .and_then(|stem| stem.rsplit_once('-'))
+                .map(|(dist, dist_version)| {
+                    dist_version == version && normalize_python_name(dist) == wanted
+                })
+                .unwrap_or(false)
+        })
+}
+
+/// Files listed in a dist-info `RECORD` ("path,hash,size"), resolved against `site_packages`
+pub fn parse_record(content: &str, site_packages: &Path) -> Vec<(PathBuf, Option<u64>)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // Paths may themselves contain commas, so split from the right
+            let mut fields = line.rsplitn(3, ',');
+            let size = fields.next()?.trim().parse().ok();
+            let _hash = fields.next()?;
+            let path = fields.next()?.trim().trim_matches('"');
+            (!path.is_empty()).then(|| (site_packages.join(path), size))
+        })
+        .collect()
+}
+
+/// Measure every package in parallel; `locate` returns the install path and its files
+pub fn measure_packages<F>(packages: &mut [PackageInfo], locate: F)
+where
+    F: Fn(&PackageInfo) -> Option<(PathBuf, Footprint)> + Sync,
+{
+    packages.par_iter_mut().for_each(|package| {
+        if let Some((install_path, footprint)) = locate(package) {
+            footprint.apply(package, &install_path);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_record_and_totals_recorded_sizes() {
+        let site_packages = Path::new("/nonexistent/site-packages");
+        let record = "black/__init__.py,sha256=abc,1200\n\
+                      \"odd,name.py\",sha256=def,300\n\
+                      ../../../bin/black,sha256=ghi,250\n\
+                      black-24.1.0.dist-info/RECORD,,\n";
+        let files = parse_record(record, site_packages);
+
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[1].0, site_packages.join("odd,name.py"));
+        assert_eq!(files[3].1, None);
+        assert_eq!(Footprint::of_files(files).size, 1750);
+    }
+}
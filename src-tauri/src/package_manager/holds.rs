@@ -0,0 +1,260 @@
+//! Package holds (pins)
+//! Held packages are excluded from uninstalls and bulk cleanups, and from updates
+//! unless the update stays within the hold's version constraint
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::{create_manager, PackageInfo, PackageManager};
+use crate::utils::storage::{app_data_file, load_json, save_json};
+use crate::utils::version::satisfies_constraint;
+
+const HOLDS_FILE: &str = "package_holds.json";
+
+/// A package protected from updates and removal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageHold {
+    pub manager: String,
+    pub name: String,
+    pub version_constraint: Option<String>,
+    pub created_at: String,
+    pub native: bool, // also pinned through the manager itself (brew pin, conda pinned)
+}
+
+impl PackageHold {
+    /// Whether updating to the `latest` available version stays within the
+    /// constraint; `latest` is only looked up when there is a constraint
+    pub fn allows_update(&self, latest: impl FnOnce() -> Option<String>) -> bool {
+        self.version_constraint
+            .as_deref()
+            .is_some_and(|constraint| {
+                latest().is_some_and(|latest| satisfies_constraint(&latest, constraint))
+            })
+    }
+}
+
+/// Persisted list of package holds
+pub struct HoldStore {
+    path: Option<PathBuf>,
+    holds: Vec<PackageHold>,
+}
+
+impl HoldStore {
+    /// Load holds from the app data directory
+    pub fn load() -> Self {
+        Self::load_from(app_data_file(HOLDS_FILE))
+    }
+
+    pub(super) fn load_from(path: Option<PathBuf>) -> Self {
+        let holds = path
+            .as_deref()
+            .and_then(load_json::<Vec<PackageHold>>)
+            .unwrap_or_default();
+        Self { path, holds }
+    }
+
+    pub fn holds(&self) -> &[PackageHold] {
+        &self.holds
+    }
+
+    pub fn find(&self, manager: &str, name: &str) -> Option<&PackageHold> {
+        self.holds
+            .iter()
+            .find(|hold| hold.manager == manager && hold.name.eq_ignore_ascii_case(name))
+    }
+
+    fn upsert(&mut self, hold: PackageHold) {
+        self.remove(&hold.manager, &hold.name);
+        self.holds.push(hold);
+        self.holds
+            .sort_by(|a, b| a.manager.cmp(&b.manager).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    fn remove(&mut self, manager: &str, name: &str) -> Option<PackageHold> {
+        let index = self
+            .holds
+            .iter()
+            .position(|hold| hold.manager == manager && hold.name.eq_ignore_ascii_case(name))?;
+        Some(self.holds.remove(index))
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = self
+            .path
+            .as_deref()
+            .ok_or_else(|| "Could not determine the app data directory".to_string())?;
+        save_json(path, &self.holds)
+    }
+
+    /// Error out when `action` would touch a held package
+    pub fn ensure_not_held(&self, manager: &str, name: &str, action: &str) -> Result<(), String> {
+        match self.find(manager, name) {
+            Some(hold) => Err(format!(
+                "{} is held{}; release the hold before you {} it",
+                hold.name,
+                hold.version_constraint
+                    .as_deref()
+                    .map(|constraint| format!(" at {}", constraint))
+                    .unwrap_or_default(),
+                action
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Error out when updating a held package would leave its version constraint
+    pub fn ensure_update_allowed(
+        &self,
+        manager: &str,
+        name: &str,
+        latest: impl FnOnce() -> Option<String>,
+    ) -> Result<(), String> {
+        match self.find(manager, name) {
+            Some(hold) if !hold.allows_update(latest) => {
+                self.ensure_not_held(manager, name, "update")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Mark held packages in scan results
+    pub fn apply(&self, packages: &mut [PackageInfo]) {
+        for package in packages {
+            if let Some(hold) = self.find(&package.manager, &package.name) {
+                package.is_held = true;
+                package.hold_constraint = hold.version_constraint.clone();
+            }
+        }
+    }
+}
+
+/// Latest available version of `name`, as reported by `package_manager`
+pub fn latest_version(package_manager: &dyn PackageManager, name: &str) -> Option<String> {
+    package_manager
+        .list_packages()
+        .into_iter()
+        .find(|package| package.name.eq_ignore_ascii_case(name))?
+        .latest
+}
+
+/// List all persisted holds
+pub fn list_holds() -> Vec<PackageHold> {
+    HoldStore::load().holds().to_vec()
+}
+
+/// Hold a package, mapping to the manager's native pin when it has one
+pub fn hold_package(
+    manager: &str,
+    name: &str,
+    version_constraint: Option<String>,
+) -> Result<String, String> {
+    let package_manager = create_manager(manager)?;
+    let constraint = version_constraint.filter(|constraint| !constraint.trim().is_empty());
+
+    let native = match package_manager.set_native_hold(name, constraint.as_deref(), true) {
+        Some(result) => {
+            result?;
+            true
+        }
+        None => false,
+    };
+
+    let mut store = HoldStore::load();
+    store.upsert(PackageHold {
+        manager: manager.to_string(),
+        name: name.to_string(),
+        version_constraint: constraint,
+        created_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        native,
+    });
+    store.save()?;
+
+    Ok(if native {
+        format!("Held {} (pinned via {})", name, manager)
+    } else {
+        format!("Held {}", name)
+    })
+}
+
+/// Release a hold and the matching native pin
+pub fn release_package(manager: &str, name: &str) -> Result<String, String> {
+    let mut store = HoldStore::load();
+    let hold = store
+        .remove(manager, name)
+        .ok_or_else(|| format!("{} is not held", name))?;
+
+    if hold.native {
+        let package_manager = create_manager(manager)?;
+        if let Some(result) = package_manager.set_native_hold(&hold.name, None, false) {
+            result?;
+        }
+    }
+
+    store.save()?;
+    Ok(format!("Released hold on {}", hold.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn hold(manager: &str, name: &str, constraint: Option<&str>) -> PackageHold {
+        PackageHold {
+            manager: manager.to_string(),
+            name: name.to_string(),
+            version_constraint: constraint.map(str::to_string),
+            created_at: String::new(),
+            native: false,
+        }
+    }
+
+    #[test]
+    fn persists_and_enforces_holds() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let path = std::env::temp_dir()
+            .join(format!("dev-janitor-holds-{nanos}"))
+            .join(HOLDS_FILE);
+
+        let mut store = HoldStore::load_from(Some(path.clone()));
+        store.upsert(hold("npm", "typescript", Some("5.4.x")));
+        store.upsert(hold("pip", "poetry", None));
+        store.save().unwrap();
+
+        let store = HoldStore::load_from(Some(path.clone()));
+        assert_eq!(store.holds().len(), 2);
+        assert!(store
+            .ensure_not_held("npm", "TypeScript", "uninstall")
+            .is_err());
+        assert!(store.ensure_not_held("pip", "typescript", "update").is_ok());
+        // Updates within the constraint go through, anything else is refused
+        assert!(store
+            .ensure_update_allowed("npm", "typescript", || Some("5.4.9".to_string()))
+            .is_ok());
+        assert!(store
+            .ensure_update_allowed("npm", "typescript", || Some("5.5.0".to_string()))
+            .is_err());
+        assert!(store
+            .ensure_update_allowed("npm", "typescript", || None)
+            .is_err());
+        assert!(store
+            .ensure_update_allowed("pip", "poetry", || Some("2.0.0".to_string()))
+            .is_err());
+
+        let mut packages = vec![PackageInfo {
+            name: "typescript".to_string(),
+            version: "5.4.5".to_string(),
+            manager: "npm".to_string(),
+            ..Default::default()
+        }];
+        store.apply(&mut packages);
+        assert!(packages[0].is_held);
+        assert_eq!(packages[0].hold_constraint.as_deref(), Some("5.4.x"));
+
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,24 +1,219 @@
 //! Homebrew package manager support (macOS and Linux)
+//! Finds Linuxbrew prefixes that are not on the GUI's PATH and separates
+//! formulae, casks, taps and leaves with per-keg disk usage
 
-use super::{PackageInfo, PackageManager};
+use super::dependencies::{exact_name, DependencyGraph, DependencyNode};
+use super::footprint::{measure_packages, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::path::{Path, PathBuf};
 
+use crate::cache::{format_size, get_dir_size};
 use crate::utils::command::command_output_with_timeout;
 use std::time::Duration;
 
 pub struct HomebrewManager {
     version: String,
+    program: String,
+}
+
+#[derive(Deserialize)]
+struct BrewInfoOutput {
+    #[serde(default)]
+    formulae: Vec<BrewFormula>,
+    #[serde(default)]
+    casks: Vec<BrewCask>,
+}
+
+#[derive(Deserialize)]
+struct BrewFormula {
+    name: String,
+    #[serde(default)]
+    tap: Option<String>,
+    #[serde(default)]
+    desc: Option<String>,
+    #[serde(default)]
+    versions: BrewVersions,
+    #[serde(default)]
+    dependencies: Vec<String>,
+    #[serde(default)]
+    installed: Vec<BrewInstalledKeg>,
+    #[serde(default)]
+    linked_keg: Option<String>,
+    #[serde(default)]
+    outdated: bool,
+    #[serde(default)]
+    pinned: bool,
+}
+
+#[derive(Deserialize, Default)]
+struct BrewVersions {
+    stable: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct BrewInstalledKeg {
+    version: String,
+    #[serde(default)]
+    installed_on_request: bool,
+}
+
+#[derive(Deserialize)]
+struct BrewCask {
+    token: String,
+    #[serde(default)]
+    tap: Option<String>,
+    #[serde(default)]
+    desc: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    installed: Option<String>,
+    #[serde(default)]
+    outdated: bool,
+}
+
+/// One installed version of a formula in the Cellar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewKeg {
+    pub name: String,
+    pub version: String,
+    pub path: String,
+    pub size: u64,
+    pub size_display: String,
+    pub is_linked: bool,
+    pub installed_on_request: bool,
+}
+
+/// An installed formula with all of its kegs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewFormulaInfo {
+    pub name: String,
+    pub tap: Option<String>,
+    pub description: Option<String>,
+    pub version: String,
+    pub latest: Option<String>,
+    pub is_outdated: bool,
+    pub is_pinned: bool,
+    pub is_leaf: bool,
+    pub installed_on_request: bool,
+    pub kegs: Vec<BrewKeg>,
+    pub size: u64,
+    pub size_display: String,
+}
+
+/// An installed cask
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewCaskInfo {
+    pub token: String,
+    pub tap: Option<String>,
+    pub description: Option<String>,
+    pub version: String,
+    pub latest: Option<String>,
+    pub is_outdated: bool,
+    pub size: u64,
+    pub size_display: String,
+}
+
+/// Something `brew cleanup` would delete
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrewCleanupCandidate {
+    pub path: String,
+    pub kind: String, // "keg" or "cache"
+    pub size: u64,
+    pub size_display: String,
+}
+
+/// Full picture of a Homebrew installation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HomebrewInventory {
+    pub program: String,
+    pub prefix: String,
+    pub cache_dir: Option<String>,
+    pub formulae: Vec<BrewFormulaInfo>,
+    pub casks: Vec<BrewCaskInfo>,
+    pub taps: Vec<String>,
+    pub leaves: Vec<String>,
+    pub cleanup: Vec<BrewCleanupCandidate>,
+    pub total_size: u64,
+    pub total_size_display: String,
+    pub reclaimable_size: u64,
+    pub reclaimable_size_display: String,
 }
 
 impl HomebrewManager {
     pub fn new() -> Option<Self> {
-        let output = run_brew_command(&["--version"])?;
+        let program = find_brew()?;
+        let output = run_brew_command(&program, &["--version"])?;
         let version = output
             .lines()
             .next()
             .and_then(|line| line.split_whitespace().nth(1))
             .unwrap_or("unknown")
             .to_string();
-        Some(Self { version })
+        Some(Self { version, program })
+    }
+
+    fn brew(&self, args: &[&str]) -> Option<String> {
+        run_brew_command(&self.program, args)
+    }
+
+    fn info(&self) -> Option<BrewInfoOutput> {
+        let output = self.brew(&["info", "--json=v2", "--installed"])?;
+        serde_json::from_str(&output).ok()
+    }
+
+    /// Formulae, casks, taps, leaves and cleanup candidates of this installation
+    pub fn inventory(&self) -> Option<HomebrewInventory> {
+        let prefix = PathBuf::from(self.brew(&["--prefix"])?.trim());
+        let cache_dir = self
+            .brew(&["--cache"])
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty());
+        let info = self.info()?;
+
+        let leaves: Vec<String> = self
+            .brew(&["leaves"])
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let taps: Vec<String> = self
+            .brew(&["tap"])
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+
+        let formulae = formula_infos(&info.formulae, &prefix, &leaves);
+        let casks = cask_infos(&info.casks, &prefix);
+        let cleanup = parse_cleanup_dry_run(
+            &self.brew(&["cleanup", "--dry-run"]).unwrap_or_default(),
+            &prefix,
+        );
+
+        let total_size = formulae.iter().map(|formula| formula.size).sum::<u64>()
+            + casks.iter().map(|cask| cask.size).sum::<u64>();
+        let reclaimable_size = cleanup.iter().map(|candidate| candidate.size).sum();
+
+        Some(HomebrewInventory {
+            program: self.program.clone(),
+            prefix: prefix.to_string_lossy().to_string(),
+            cache_dir,
+            formulae,
+            casks,
+            taps,
+            leaves,
+            cleanup,
+            total_size,
+            total_size_display: format_size(total_size),
+            reclaimable_size,
+            reclaimable_size_display: format_size(reclaimable_size),
+        })
     }
 }
 
@@ -36,57 +231,257 @@ impl PackageManager for HomebrewManager {
     }
 
     fn list_packages(&self) -> Vec<PackageInfo> {
-        let mut packages = Vec::new();
-
-        let output = match run_brew_command(&["list", "--formula", "--versions"]) {
-            Some(o) => o,
-            None => return packages,
+        let info = match self.info() {
+            Some(info) => info,
+            None => return Vec::new(),
         };
 
-        let outdated_output = run_brew_command(&["outdated", "--formula"]).unwrap_or_default();
-        let outdated_names: std::collections::HashSet<String> = outdated_output
-            .lines()
-            .map(|l| l.split_whitespace().next().unwrap_or("").to_string())
-            .collect();
+        let formulae = info.formulae.into_iter().filter_map(|formula| {
+            let version = formula.installed.last()?.version.clone();
+            Some(PackageInfo {
+                latest: formula.versions.stable.filter(|_| formula.outdated),
+                name: formula.name,
+                version,
+                manager: "homebrew".to_string(),
+                is_outdated: formula.outdated,
+                description: formula.desc,
+                ..Default::default()
+            })
+        });
 
-        for line in output.lines() {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            if parts.len() >= 2 {
-                let name = parts[0].to_string();
-                let version = parts[1].to_string();
-                let is_outdated = outdated_names.contains(&name);
-
-                packages.push(PackageInfo {
-                    name,
-                    version,
-                    latest: None,
-                    manager: "homebrew".to_string(),
-                    is_outdated,
-                    description: None,
-                });
-            }
+        let cask_tokens: HashSet<String> =
+            info.casks.iter().map(|cask| cask.token.clone()).collect();
+        let casks = info.casks.into_iter().filter_map(|cask| {
+            Some(PackageInfo {
+                version: cask.installed?,
+                latest: cask.version.filter(|_| cask.outdated),
+                name: cask.token,
+                manager: "homebrew".to_string(),
+                is_outdated: cask.outdated,
+                description: Some(match cask.desc {
+                    Some(desc) => format!("{} (cask)", desc),
+                    None => "cask".to_string(),
+                }),
+                ..Default::default()
+            })
+        });
+
+        let mut packages: Vec<PackageInfo> = formulae.chain(casks).collect();
+
+        // Every keg of a formula lives under Cellar/<name>; casks stage under Caskroom/<token>
+        if let Some(prefix) = self.brew(&["--prefix"]) {
+            let prefix = PathBuf::from(prefix.trim());
+            measure_packages(&mut packages, |pkg| {
+                let root = if cask_tokens.contains(&pkg.name) {
+                    "Caskroom"
+                } else {
+                    "Cellar"
+                };
+                let path = prefix.join(root).join(&pkg.name);
+                if !path.exists() {
+                    return None;
+                }
+                let footprint = Footprint::of_paths([path.as_path()]);
+                Some((path, footprint))
+            });
         }
 
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_brew_command(&["upgrade", name]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
-        }
+    fn install_package(&self, name: &str, _version: Option<&str>) -> OperationResult {
+        // Homebrew only installs the current formula version
+        run_operation(&self.program, &["install", name])
+    }
+
+    fn update_package(&self, name: &str) -> OperationResult {
+        run_operation(&self.program, &["upgrade", name])
+    }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        run_operation(&self.program, &["uninstall", name])
     }
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_brew_command(&["uninstall", name]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
+    fn set_native_hold(
+        &self,
+        name: &str,
+        version_constraint: Option<&str>,
+        held: bool,
+    ) -> Option<Result<String, String>> {
+        // brew pin always freezes the currently installed version, so a hold with a
+        // constraint stays app-level and lets updates within the constraint through
+        if held && version_constraint.is_some() {
+            return None;
         }
+        let action = if held { "pin" } else { "unpin" };
+        Some(match self.brew(&[action, name]) {
+            Some(output) => Ok(output),
+            None => Err(format!("Failed to {} {}", action, name)),
+        })
+    }
+
+    fn dependency_graph(&self) -> Option<DependencyGraph> {
+        let info = self.info()?;
+
+        let nodes = info
+            .formulae
+            .into_iter()
+            .filter_map(|formula| {
+                let keg = formula.installed.last()?;
+                Some(DependencyNode {
+                    version: keg.version.clone(),
+                    is_user_requested: keg.installed_on_request,
+                    name: formula.name,
+                    dependencies: formula.dependencies,
+                    required_by: Vec::new(),
+                })
+            })
+            .collect();
+
+        Some(DependencyGraph::from_nodes("homebrew", nodes, exact_name))
+    }
+}
+
+/// Get the Homebrew inventory, if Homebrew is installed
+pub fn homebrew_inventory() -> Option<HomebrewInventory> {
+    HomebrewManager::new()?.inventory()
+}
+
+/// Locate `brew`: PATH first, then the standard macOS and Linuxbrew prefixes
+fn find_brew() -> Option<String> {
+    if let Ok(path) = which::which("brew") {
+        return Some(path.to_string_lossy().to_string());
+    }
+
+    let mut candidates = vec![
+        PathBuf::from("/opt/homebrew/bin/brew"),
+        PathBuf::from("/usr/local/bin/brew"),
+        PathBuf::from("/home/linuxbrew/.linuxbrew/bin/brew"),
+    ];
+    if let Ok(prefix) = env::var("HOMEBREW_PREFIX") {
+        candidates.insert(0, PathBuf::from(prefix).join("bin/brew"));
     }
+    if let Ok(home) = env::var("HOME") {
+        candidates.push(PathBuf::from(home).join(".linuxbrew/bin/brew"));
+    }
+
+    candidates
+        .into_iter()
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
 }
 
-fn run_brew_command(args: &[&str]) -> Option<String> {
-    let output = command_output_with_timeout("brew", args, Duration::from_secs(30)).ok()?;
+fn formula_infos(
+    formulae: &[BrewFormula],
+    prefix: &Path,
+    leaves: &[String],
+) -> Vec<BrewFormulaInfo> {
+    let leaves: HashSet<&str> = leaves.iter().map(String::as_str).collect();
+
+    formulae
+        .iter()
+        .filter(|formula| !formula.installed.is_empty())
+        .map(|formula| {
+            let current = formula
+                .linked_keg
+                .clone()
+                .or_else(|| formula.installed.last().map(|keg| keg.version.clone()))
+                .unwrap_or_default();
+
+            let kegs: Vec<BrewKeg> = formula
+                .installed
+                .iter()
+                .map(|keg| {
+                    let path = prefix.join("Cellar").join(&formula.name).join(&keg.version);
+                    let size = get_dir_size(&path);
+                    BrewKeg {
+                        name: formula.name.clone(),
+                        version: keg.version.clone(),
+                        path: path.to_string_lossy().to_string(),
+                        size,
+                        size_display: format_size(size),
+                        is_linked: formula.linked_keg.as_deref() == Some(keg.version.as_str()),
+                        installed_on_request: keg.installed_on_request,
+                    }
+                })
+                .collect();
+            let size = kegs.iter().map(|keg| keg.size).sum();
+
+            BrewFormulaInfo {
+                name: formula.name.clone(),
+                tap: formula.tap.clone(),
+                description: formula.desc.clone(),
+                version: current,
+                latest: formula.versions.stable.clone(),
+                is_outdated: formula.outdated,
+                is_pinned: formula.pinned,
+                is_leaf: leaves.contains(formula.name.as_str()),
+                installed_on_request: formula.installed.iter().any(|keg| keg.installed_on_request),
+                kegs,
+                size,
+                size_display: format_size(size),
+            }
+        })
+        .collect()
+}
+
+fn cask_infos(casks: &[BrewCask], prefix: &Path) -> Vec<BrewCaskInfo> {
+    casks
+        .iter()
+        .filter_map(|cask| {
+            let version = cask.installed.clone()?;
+            // Caskroom holds the staged artifacts; the .app itself lives in /Applications
+            let size = get_dir_size(&prefix.join("Caskroom").join(&cask.token));
+            Some(BrewCaskInfo {
+                token: cask.token.clone(),
+                tap: cask.tap.clone(),
+                description: cask.desc.clone(),
+                version,
+                latest: cask.version.clone(),
+                is_outdated: cask.outdated,
+                size,
+                size_display: format_size(size),
+            })
+        })
+        .collect()
+}
+
+/// Parse `brew cleanup --dry-run` ("Would remove: /path (12.3MB)")
+fn parse_cleanup_dry_run(output: &str, prefix: &Path) -> Vec<BrewCleanupCandidate> {
+    let cellar = prefix.join("Cellar");
+
+    output
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Would remove: "))
+        .map(|rest| {
+            // Drop the trailing "(N files, 12.3MB)" / "(12.3MB)" annotation
+            let path = match rest.rfind(" (") {
+                Some(index) if rest.ends_with(')') => &rest[..index],
+                _ => rest,
+            };
+            let path = PathBuf::from(path.trim());
+            let size = if path.is_dir() {
+                get_dir_size(&path)
+            } else {
+                path.metadata().map(|metadata| metadata.len()).unwrap_or(0)
+            };
+            BrewCleanupCandidate {
+                kind: if path.starts_with(&cellar) {
+                    "keg"
+                } else {
+                    "cache"
+                }
+                .to_string(),
+                path: path.to_string_lossy().to_string(),
+                size,
+                size_display: format_size(size),
+            }
+        })
+        .collect()
+}
+
+fn run_brew_command(program: &str, args: &[&str]) -> Option<String> {
+    let output = command_output_with_timeout(program, args, Duration::from_secs(30)).ok()?;
 
     if output.status.success() {
         Some(String::from_utf8_lossy(&output.stdout).to_string())
@@ -94,3 +489,54 @@ fn run_brew_command(args: &[&str]) -> Option<String> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn separates_formulae_casks_and_cleanup_kegs() {
+        let info: BrewInfoOutput = serde_json::from_str(
+            r#"{
+                "formulae": [
+                    {"name": "jq", "tap": "homebrew/core", "versions": {"stable": "1.7.1"},
+                     "installed": [{"version": "1.6", "installed_on_request": true},
+                                   {"version": "1.7.1", "installed_on_request": true}],
+                     "linked_keg": "1.7.1", "outdated": false, "pinned": false},
+                    {"name": "oniguruma", "versions": {"stable": "6.9.9"},
+                     "installed": [{"version": "6.9.9", "installed_on_request": false}],
+                     "linked_keg": "6.9.9"}
+                ],
+                "casks": [
+                    {"token": "iterm2", "version": "3.5.0", "installed": "3.4.23", "outdated": true},
+                    {"token": "not-installed", "version": "1.0", "installed": null}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let prefix = Path::new("/home/linuxbrew/.linuxbrew");
+        let formulae = formula_infos(&info.formulae, prefix, &["jq".to_string()]);
+        assert_eq!(formulae.len(), 2);
+        assert_eq!(formulae[0].version, "1.7.1");
+        assert_eq!(formulae[0].kegs.len(), 2);
+        assert!(formulae[0].kegs[1].is_linked);
+        assert!(formulae[0].is_leaf);
+        assert!(!formulae[1].installed_on_request);
+
+        let casks = cask_infos(&info.casks, prefix);
+        assert_eq!(casks.len(), 1);
+        assert!(casks[0].is_outdated);
+
+        let cleanup = parse_cleanup_dry_run(
+            "Would remove: /home/linuxbrew/.linuxbrew/Cellar/jq/1.6 (18 files, 1.1MB)\n\
+             Would remove: /home/dev/.cache/Homebrew/jq--1.7.1.bottle.tar.gz (400KB)\n\
+             Would remove 2 files, freeing 1.5MB",
+            prefix,
+        );
+        assert_eq!(cleanup.len(), 2);
+        assert_eq!(cleanup[0].path, "/home/linuxbrew/.linuxbrew/Cellar/jq/1.6");
+        assert_eq!(cleanup[0].kind, "keg");
+        assert_eq!(cleanup[1].kind, "cache");
+    }
+}
@@ -1,18 +1,37 @@
 //! Package manager module for Dev Janitor v2
 //! Supports npm, pip, Cargo, Composer, Homebrew, Conda, etc.
+//! plus per-environment scanning of conda envs, pyenv versions and virtualenvs
+//! and PEP 668 protection for OS-managed Python packages
 
+pub mod batch;
 pub mod cargo;
 pub mod composer;
 pub mod conda;
+pub mod dependencies;
+pub mod environments;
+pub mod externally_managed;
+pub mod footprint;
+pub mod holds;
 pub mod homebrew;
 pub mod npm;
 pub mod pip;
 
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::error::DevJanitorError;
+use crate::utils::command::{run_command_outcome, CommandOutcome};
+use dependencies::DependencyGraph;
+
+/// Installs and upgrades can download and build, so they get far more time than queries
+const OPERATION_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Result of a package operation: the captured command or a structured error
+pub type OperationResult = Result<CommandOutcome, DevJanitorError>;
 
 /// Represents a global package from any package manager
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PackageInfo {
     pub name: String,
     pub version: String,
@@ -20,6 +39,32 @@ pub struct PackageInfo {
     pub manager: String,
     pub is_outdated: bool,
     pub description: Option<String>,
+    #[serde(default)]
+    pub is_held: bool,
+    #[serde(default)]
+    pub hold_constraint: Option<String>,
+    #[serde(default)]
+    pub read_only: bool, // owned by the OS or an externally managed interpreter
+    #[serde(default)]
+    pub read_only_reason: Option<String>,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+    #[serde(default)]
+    pub size_display: Option<String>,
+    #[serde(default)]
+    pub last_accessed: Option<String>,
+    #[serde(default)]
+    pub install_path: Option<String>,
+}
+
+/// Result of uninstalling a package and, optionally, its orphaned dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UninstallReport {
+    pub outcome: CommandOutcome,
+    pub orphans: Vec<CommandOutcome>, // one per orphan removal attempt, failed ones included
+    pub kept_held: Vec<String>,
 }
 
 /// Common trait for all package managers
@@ -36,11 +81,55 @@ pub trait PackageManager {
     /// List all global packages
     fn list_packages(&self) -> Vec<PackageInfo>;
 
+    /// Install a package, optionally at a specific version
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult;
+
     /// Update a package to the latest version
-    fn update_package(&self, name: &str) -> Result<String, String>;
+    fn update_package(&self, name: &str) -> OperationResult;
 
     /// Uninstall a package
-    fn uninstall_package(&self, name: &str) -> Result<String, String>;
+    fn uninstall_package(&self, name: &str) -> OperationResult;
+
+    /// Build the dependency graph of installed packages, if the manager exposes one
+    fn dependency_graph(&self) -> Option<DependencyGraph> {
+        None
+    }
+
+    /// Pin or unpin a package with the manager's own hold mechanism.
+    /// Returns `None` when the manager has no native holds.
+    fn set_native_hold(
+        &self,
+        name: &str,
+        version_constraint: Option<&str>,
+        held: bool,
+    ) -> Option<Result<String, String>> {
+        None
+    }
+}
+
+/// Create the package manager registered under `manager`
+pub fn create_manager(manager: &str) -> Result<Box<dyn PackageManager>, String> {
+    let created: Option<Box<dyn PackageManager>> = match manager {
+        "npm" => npm::NpmManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>),
+        "pip" => pip::PipManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>),
+        "cargo" => cargo::CargoManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>),
+        "composer" => {
+            composer::ComposerManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>)
+        }
+        "conda" => conda::CondaManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>),
+        "homebrew" => {
+            homebrew::HomebrewManager::new().map(|m| Box::new(m) as Box<dyn PackageManager>)
+        }
+        _ => return Err(format!("Unknown package manager: {}", manager)),
+    };
+
+    created.ok_or_else(|| format!("{} is not available", manager))
+}
+
+/// Run a mutating package command, keeping its output and classifying failures
+pub(crate) fn run_operation(program: &str, args: &[&str]) -> OperationResult {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    run_command_outcome(program, &args, OPERATION_TIMEOUT).into_result()
 }
 
 type PackageScanFn = fn() -> Vec<PackageInfo>;
@@ -85,6 +174,8 @@ pub fn scan_all_packages() -> Vec<PackageInfo> {
         .flat_map(|scan| scan())
         .collect();
 
+    holds::HoldStore::load().apply(&mut all_packages);
+
     all_packages.sort_by(|left, right| {
         left.manager
             .cmp(&right.manager)
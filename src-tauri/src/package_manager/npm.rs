@@ -1,9 +1,12 @@
 //! npm package manager support
 
-use super::{PackageInfo, PackageManager};
+use super::dependencies::{exact_name, DependencyGraph, DependencyNode};
+use super::footprint::{measure_packages, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
 use serde::Deserialize;
 
 use crate::utils::command::command_output_with_timeout;
+use std::path::PathBuf;
 use std::time::Duration;
 
 pub struct NpmManager {
@@ -20,6 +23,17 @@ struct NpmPackage {
     version: String,
 }
 
+#[derive(Deserialize)]
+struct NpmTreeOutput {
+    dependencies: Option<std::collections::HashMap<String, NpmTreePackage>>,
+}
+
+#[derive(Deserialize)]
+struct NpmTreePackage {
+    #[serde(default)]
+    version: String,
+}
+
 #[derive(Deserialize)]
 struct NpmOutdatedPackage {
     current: String,
@@ -87,26 +101,81 @@ impl PackageManager for NpmManager {
                     manager: "npm".to_string(),
                     is_outdated,
                     description: None,
+                    ..Default::default()
                 });
             }
         }
 
+        // Each global package lives in `<npm root -g>/<name>` (scoped names included)
+        if let Some(root) = npm_global_root() {
+            measure_packages(&mut packages, |pkg| {
+                let path = root.join(&pkg.name);
+                if !path.exists() {
+                    return None;
+                }
+                let footprint = Footprint::of_paths([path.as_path()]);
+                Some((path, footprint))
+            });
+        }
+
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_npm_command(&["update", "-g", name]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
-        }
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult {
+        let spec = match version {
+            Some(version) => format!("{}@{}", name, version),
+            None => name.to_string(),
+        };
+        run_operation("npm", &["install", "-g", &spec])
     }
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_npm_command(&["uninstall", "-g", name, "--force"]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
-        }
+    fn update_package(&self, name: &str) -> OperationResult {
+        run_operation("npm", &["update", "-g", name])
+    }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        run_operation("npm", &["uninstall", "-g", name, "--force"])
     }
+
+    fn dependency_graph(&self) -> Option<DependencyGraph> {
+        let output = run_npm_command(&["ls", "-g", "--depth=0", "--json"])?;
+        parse_npm_tree(&output)
+    }
+}
+
+/// Every global package is user-installed and ships its own private copy of its
+/// dependencies under its own `node_modules`, so globals never depend on each other
+/// and the graph has no edges
+fn parse_npm_tree(output: &str) -> Option<DependencyGraph> {
+    let tree: NpmTreeOutput = serde_json::from_str(output).ok()?;
+
+    let nodes = tree
+        .dependencies
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(name, _)| name != "npm")
+        .map(|(name, pkg)| DependencyNode {
+            name,
+            version: pkg.version,
+            dependencies: Vec::new(),
+            required_by: Vec::new(),
+            is_user_requested: true,
+        })
+        .collect();
+
+    Some(DependencyGraph::from_nodes("npm", nodes, exact_name))
+}
+
+/// Configured global prefix (`npm config get prefix`)
+pub fn npm_prefix() -> Option<PathBuf> {
+    run_npm_command(&["config", "get", "prefix"])
+        .map(|prefix| PathBuf::from(prefix.trim()))
+        .filter(|prefix| !prefix.as_os_str().is_empty())
+}
+
+/// Directory holding global packages (`npm root -g`)
+pub fn npm_global_root() -> Option<PathBuf> {
+    run_npm_command(&["root", "-g"]).map(|root| PathBuf::from(root.trim()))
 }
 
 fn run_npm_command(args: &[&str]) -> Option<String> {
@@ -115,8 +184,9 @@ fn run_npm_command(args: &[&str]) -> Option<String> {
     if output.status.success() {
         Some(String::from_utf8_lossy(&output.stdout).to_string())
     } else {
-        // For npm outdated, non-zero exit is normal when packages are outdated
-        if args.contains(&"outdated") {
+        // For npm outdated, non-zero exit is normal when packages are outdated,
+        // and npm ls still prints the tree when it reports peer/extraneous problems
+        if args.contains(&"outdated") || args.contains(&"ls") {
             Some(String::from_utf8_lossy(&output.stdout).to_string())
         } else {
             None
@@ -1,14 +1,23 @@
 //! pip package manager support
 
-use super::{PackageInfo, PackageManager};
+use super::dependencies::{
+    normalize_python_name, requirement_name, DependencyGraph, DependencyNode,
+};
+use super::externally_managed::{is_distro_owned, isolated_install_suggestion, ExternallyManaged};
+use super::footprint::{find_dist_info, measure_packages, parse_record, Footprint};
+use super::{run_operation, OperationResult, PackageInfo, PackageManager};
 use serde::Deserialize;
 
-use crate::utils::command::command_output_with_timeout_vec;
+use crate::error::{DevJanitorError, FailureKind};
+use crate::utils::command::{command_output_with_timeout_vec, CommandOutcome};
+use std::fs;
+use std::path::Path;
 use std::time::Duration;
 
 pub struct PipManager {
     version: String,
     command: PipCommand,
+    externally_managed: Option<ExternallyManaged>,
 }
 
 #[derive(Clone)]
@@ -30,6 +39,17 @@ impl PipCommand {
 struct PipPackage {
     name: String,
     version: String,
+    // Only present with `pip list -v`
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(default)]
+    installer: Option<String>,
+}
+
+impl PipPackage {
+    fn is_distro_owned(&self) -> bool {
+        is_distro_owned(self.installer.as_deref(), self.location.as_deref())
+    }
 }
 
 #[derive(Deserialize)]
@@ -39,6 +59,26 @@ struct PipOutdatedPackage {
     latest_version: String,
 }
 
+#[derive(Deserialize)]
+struct PipInspectOutput {
+    installed: Vec<PipInspectPackage>,
+}
+
+#[derive(Deserialize)]
+struct PipInspectPackage {
+    metadata: PipInspectMetadata,
+    #[serde(default)]
+    requested: bool,
+}
+
+#[derive(Deserialize)]
+struct PipInspectMetadata {
+    name: String,
+    version: String,
+    #[serde(default)]
+    requires_dist: Vec<String>,
+}
+
 impl PipManager {
     pub fn new() -> Option<Self> {
         // Prefer invoking pip via the Python launcher/interpreter when available.
@@ -68,9 +108,16 @@ impl PipManager {
                     .nth(1)
                     .unwrap_or("unknown")
                     .to_string();
+                // The marker lives next to the interpreter's stdlib, so only
+                // `python -m pip` style commands can be queried for it
+                let externally_managed = match cmd.prefix_args.first().map(String::as_str) {
+                    Some("-m") => ExternallyManaged::detect(&cmd.program),
+                    _ => None,
+                };
                 return Some(Self {
                     version,
                     command: cmd.clone(),
+                    externally_managed,
                 });
             }
         }
@@ -95,15 +142,10 @@ impl PackageManager for PipManager {
         let mut packages = Vec::new();
 
         // Get installed packages
-        let output = match run_pip_command(&self.command, &["list", "--format=json"]) {
-            Some(o) => o,
-            None => return packages,
-        };
-
-        let list: Vec<PipPackage> = match serde_json::from_str(&output) {
-            Ok(l) => l,
-            Err(_) => return packages,
-        };
+        let list = self.installed();
+        if list.is_empty() {
+            return packages;
+        }
 
         // Skip outdated check for now - it requires network and is very slow
         // TODO: Move to async background task
@@ -115,6 +157,8 @@ impl PackageManager for PipManager {
 
         let outdated_map: std::collections::HashMap<String, String> =
             std::collections::HashMap::new();
+        let mut locations: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
 
         for pkg in list {
             // Skip common system packages
@@ -129,34 +173,157 @@ impl PackageManager for PipManager {
                 (false, None)
             };
 
+            let read_only_reason = self.read_only_reason(&pkg);
+            if let Some(location) = &pkg.location {
+                locations.insert(pkg.name.clone(), location.clone());
+            }
             packages.push(PackageInfo {
+                read_only: read_only_reason.is_some(),
+                suggestion: read_only_reason
+                    .as_ref()
+                    .map(|_| isolated_install_suggestion(&pkg.name)),
+                read_only_reason,
                 name: pkg.name,
                 version: pkg.version,
                 latest,
                 manager: "pip".to_string(),
                 is_outdated,
                 description: None,
+                ..Default::default()
             });
         }
 
+        // RECORD lists every installed file, including console scripts outside site-packages
+        measure_packages(&mut packages, |pkg| {
+            let site_packages = Path::new(locations.get(&pkg.name)?);
+            let dist_info = find_dist_info(site_packages, &pkg.name, &pkg.version)?;
+            let record = fs::read_to_string(dist_info.join("RECORD")).ok()?;
+            let footprint = Footprint::of_files(parse_record(&record, site_packages));
+            Some((dist_info, footprint))
+        });
+
         packages
     }
 
-    fn update_package(&self, name: &str) -> Result<String, String> {
-        match run_pip_command(&self.command, &["install", "--upgrade", name]) {
-            Some(output) => Ok(format!("Updated {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to update {}", name)),
+    fn install_package(&self, name: &str, version: Option<&str>) -> OperationResult {
+        let spec = match version {
+            Some(version) => format!("{}=={}", name, version),
+            None => name.to_string(),
+        };
+        self.ensure_writable(name, false, &["install", &spec])?;
+        run_pip_operation(&self.command, &["install", &spec])
+    }
+
+    fn update_package(&self, name: &str) -> OperationResult {
+        self.ensure_writable(name, true, &["install", "--upgrade", name])?;
+        run_pip_operation(&self.command, &["install", "--upgrade", name])
+    }
+
+    fn uninstall_package(&self, name: &str) -> OperationResult {
+        self.ensure_writable(name, true, &["uninstall", "-y", name])?;
+        run_pip_operation(&self.command, &["uninstall", "-y", name])
+    }
+
+    fn dependency_graph(&self) -> Option<DependencyGraph> {
+        // `pip inspect` (pip >= 22.2) reports requirements and the REQUESTED marker
+        let output = run_pip_command(&self.command, &["inspect"])?;
+        parse_pip_inspect(&output)
+    }
+}
+
+impl PipManager {
+    fn installed(&self) -> Vec<PipPackage> {
+        run_pip_command(&self.command, &["list", "-v", "--format=json"])
+            .and_then(|output| serde_json::from_str(&output).ok())
+            .unwrap_or_default()
+    }
+
+    fn read_only_reason(&self, pkg: &PipPackage) -> Option<String> {
+        if let Some(managed) = &self.externally_managed {
+            return Some(managed.reason());
         }
+        pkg.is_distro_owned().then(|| {
+            format!(
+                "{} is owned by the OS package manager ({})",
+                pkg.name,
+                pkg.installer.as_deref().unwrap_or("system")
+            )
+        })
     }
 
-    fn uninstall_package(&self, name: &str) -> Result<String, String> {
-        match run_pip_command(&self.command, &["uninstall", "-y", name]) {
-            Some(output) => Ok(format!("Uninstalled {} successfully:\n{}", name, output)),
-            None => Err(format!("Failed to uninstall {}", name)),
+    /// Refuse to run pip against an externally managed interpreter or a distro-owned package
+    pub fn ensure_writable(
+        &self,
+        name: &str,
+        existing: bool,
+        args: &[&str],
+    ) -> Result<(), DevJanitorError> {
+        let reason = match &self.externally_managed {
+            Some(managed) => Some(managed.reason()),
+            None if existing => self
+                .installed()
+                .iter()
+                .find(|pkg| normalize_python_name(&pkg.name) == normalize_python_name(name))
+                .and_then(|pkg| self.read_only_reason(pkg)),
+            None => None,
+        };
+
+        match reason {
+            Some(reason) => Err(DevJanitorError::PackageOperation(Box::new(
+                CommandOutcome {
+                    command: std::iter::once(self.command.program.as_str())
+                        .chain(self.command.prefix_args.iter().map(String::as_str))
+                        .chain(args.iter().copied())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    stderr: reason,
+                    failure: Some(FailureKind::ExternallyManaged),
+                    hint: Some(isolated_install_suggestion(name)),
+                    ..Default::default()
+                },
+            ))),
+            None => Ok(()),
         }
     }
 }
 
+fn parse_pip_inspect(output: &str) -> Option<DependencyGraph> {
+    let inspect: PipInspectOutput = serde_json::from_str(output).ok()?;
+
+    let nodes = inspect
+        .installed
+        .into_iter()
+        .map(|pkg| DependencyNode {
+            name: pkg.metadata.name,
+            version: pkg.metadata.version,
+            dependencies: pkg
+                .metadata
+                .requires_dist
+                .iter()
+                .filter_map(|req| requirement_name(req))
+                .collect(),
+            required_by: Vec::new(),
+            is_user_requested: pkg.requested,
+        })
+        .collect();
+
+    Some(DependencyGraph::from_nodes(
+        "pip",
+        nodes,
+        normalize_python_name,
+    ))
+}
+
+fn run_pip_operation(command: &PipCommand, args: &[&str]) -> OperationResult {
+    let full_args: Vec<&str> = command
+        .prefix_args
+        .iter()
+        .map(String::as_str)
+        .chain(args.iter().copied())
+        .collect();
+    run_operation(&command.program, &full_args)
+}
+
 fn run_pip_command(command: &PipCommand, args: &[&str]) -> Option<String> {
     let mut full_args: Vec<String> = Vec::new();
     full_args.extend(command.prefix_args.iter().cloned());
@@ -0,0 +1,968 @@
+//! Offline vulnerability audit
+//!
+//! Matches installed global packages and project lockfiles against advisories the user
+//! syncs into a local directory: OSV JSON exports (npm, PyPI, GitHub advisory-database)
+//! and RustSec advisory-db files. Nothing is fetched from the network.
+
+use chrono::Local;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use super::definitions::{RiskLevel, SecurityFinding, SecuritySummary};
+use super::scanner::build_summary;
+use crate::package_manager::dependencies::normalize_python_name;
+use crate::package_manager::scan_all_packages;
+use crate::utils::storage::app_data_file;
+use crate::utils::version::compare_versions;
+
+const NPM: &str = "npm";
+const PYPI: &str = "PyPI";
+const CRATES_IO: &str = "crates.io";
+const PACKAGIST: &str = "Packagist";
+
+/// Ecosystems we have package sources for; advisories for anything else are not loaded
+const ECOSYSTEMS: &[&str] = &[NPM, PYPI, CRATES_IO, PACKAGIST];
+
+/// Extracts (name, version) pairs from a lockfile's content
+type LockfileParser = fn(&str) -> Vec<(String, String)>;
+
+/// A package version to check, from a package manager or a project lockfile
+#[derive(Debug, Clone)]
+pub struct AuditTarget {
+    pub ecosystem: &'static str,
+    pub name: String,
+    pub version: String,
+    pub source: String, // "npm (global)" or the lockfile path
+}
+
+/// Result of auditing packages against the offline advisory database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageAuditResult {
+    pub scan_time: String,
+    pub advisory_dir: String,
+    pub advisories_loaded: usize,
+    pub skipped_files: usize, // advisory files that could not be parsed
+    pub packages_checked: usize,
+    pub sources: Vec<String>,
+    pub findings: Vec<SecurityFinding>,
+    pub summary: SecuritySummary,
+}
+
+#[derive(Debug, Clone)]
+enum RangeEvent {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+}
+
+/// How an advisory describes the affected versions of one package
+#[derive(Debug, Clone)]
+enum AffectedVersions {
+    /// OSV: ordered introduced/fixed/last_affected events plus explicitly listed versions
+    Osv {
+        ranges: Vec<Vec<RangeEvent>>,
+        versions: Vec<String>,
+    },
+    /// RustSec: semver requirements of the patched and unaffected versions
+    Requirements {
+        patched: Vec<String>,
+        unaffected: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct AffectedPackage {
+    ecosystem: String,
+    name: String,
+    versions: AffectedVersions,
+}
+
+/// One advisory from the local database
+#[derive(Debug, Clone)]
+pub struct Advisory {
+    pub id: String,
+    pub summary: String,
+    pub aliases: Vec<String>,
+    pub cvss_score: Option<f64>,
+    pub severity: Option<String>, // database label when no CVSS vector is given
+    pub informational: bool,      // RustSec "unmaintained"/"unsound" notices
+    affected: Vec<AffectedPackage>,
+}
+
+impl Advisory {
+    fn risk_level(&self) -> RiskLevel {
+        if let Some(score) = self.cvss_score {
+            return RiskLevel::from_cvss(score);
+        }
+        if let Some(level) = self.severity.as_deref().and_then(RiskLevel::from_label) {
+            return level;
+        }
+        if self.informational {
+            RiskLevel::Low
+        } else {
+            RiskLevel::Medium
+        }
+    }
+}
+
+/// Advisories loaded from disk, indexed by ecosystem and normalized package name
+#[derive(Debug, Default)]
+pub struct AdvisoryDatabase {
+    advisories: Vec<Advisory>,
+    index: HashMap<(String, String), Vec<(usize, usize)>>,
+    pub skipped_files: usize,
+}
+
+impl AdvisoryDatabase {
+    /// Where the user syncs advisory exports when no directory is given
+    pub fn default_dir() -> Option<PathBuf> {
+        app_data_file("advisories")
+    }
+
+    /// Load every OSV `.json` and RustSec `.md`/`.toml` advisory below `dir`
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        if !dir.is_dir() {
+            return Err(format!(
+                "No advisory database at {}. Sync RustSec advisory-db, OSV exports or the GitHub advisory-database into this directory first.",
+                dir.display()
+            ));
+        }
+
+        let files: Vec<PathBuf> = WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("json" | "toml" | "md")
+                )
+            })
+            .collect();
+
+        let parsed: Vec<Result<Vec<Advisory>, ()>> = files
+            .par_iter()
+            .map(|path| {
+                let content = fs::read_to_string(path).map_err(|_| ())?;
+                parse_advisory_file(path, &content)
+            })
+            .collect();
+
+        let mut database = Self::default();
+        for result in parsed {
+            match result {
+                Ok(advisories) => advisories.into_iter().for_each(|advisory| {
+                    database.insert(advisory);
+                }),
+                Err(()) => database.skipped_files += 1,
+            }
+        }
+        Ok(database)
+    }
+
+    fn insert(&mut self, advisory: Advisory) {
+        let advisory_index = self.advisories.len();
+        for (affected_index, affected) in advisory.affected.iter().enumerate() {
+            self.index
+                .entry((
+                    affected.ecosystem.clone(),
+                    package_key(&affected.ecosystem, &affected.name),
+                ))
+                .or_default()
+                .push((advisory_index, affected_index));
+        }
+        self.advisories.push(advisory);
+    }
+
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// Findings for every advisory that affects the target's version
+    pub fn check(&self, target: &AuditTarget) -> Vec<SecurityFinding> {
+        let key = (
+            target.ecosystem.to_string(),
+            package_key(target.ecosystem, &target.name),
+        );
+        let Some(entries) = self.index.get(&key) else {
+            return Vec::new();
+        };
+
+        entries
+            .iter()
+            .filter_map(|&(advisory_index, affected_index)| {
+                let advisory = &self.advisories[advisory_index];
+                let affected = &advisory.affected[affected_index];
+                is_affected(&target.version, &affected.versions)
+                    .then(|| build_finding(target, advisory, &affected.versions))
+            })
+            .collect()
+    }
+}
+
+/// Audit global packages (and a project's lockfiles) against the offline advisories
+pub fn audit_packages(
+    advisory_dir: Option<&Path>,
+    project: Option<&Path>,
+) -> Result<PackageAuditResult, String> {
+    let advisory_dir = match advisory_dir {
+        Some(dir) => dir.to_path_buf(),
+        None => AdvisoryDatabase::default_dir()
+            .ok_or_else(|| "Cannot determine the advisory directory".to_string())?,
+    };
+    let database = AdvisoryDatabase::load(&advisory_dir)?;
+
+    let mut targets = global_targets();
+    if let Some(project) = project {
+        targets.extend(lockfile_targets(project));
+    }
+
+    let mut sources: Vec<String> = Vec::new();
+    for target in &targets {
+        if !sources.contains(&target.source) {
+            sources.push(target.source.clone());
+        }
+    }
+
+    let mut findings: Vec<SecurityFinding> = targets
+        .par_iter()
+        .flat_map(|target| database.check(target))
+        .collect();
+    findings.sort_by(|a, b| {
+        a.risk_level
+            .cmp(&b.risk_level)
+            .then_with(|| a.tool_id.cmp(&b.tool_id))
+    });
+    let summary = build_summary(&findings);
+
+    Ok(PackageAuditResult {
+        scan_time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        advisory_dir: advisory_dir.to_string_lossy().to_string(),
+        advisories_loaded: database.len(),
+        skipped_files: database.skipped_files,
+        packages_checked: targets.len(),
+        sources,
+        findings,
+        summary,
+    })
+}
+
+/// Globally installed packages from the managers that have an advisory ecosystem
+fn global_targets() -> Vec<AuditTarget> {
+    scan_all_packages()
+        .into_iter()
+        .filter_map(|package| {
+            let ecosystem = match package.manager.as_str() {
+                "npm" => NPM,
+                "pip" => PYPI,
+                "cargo" => CRATES_IO,
+                "composer" => PACKAGIST,
+                _ => return None,
+            };
+            Some(AuditTarget {
+                ecosystem,
+                source: format!("{} (global)", package.manager),
+                name: package.name,
+                version: package.version,
+            })
+        })
+        .collect()
+}
+
+/// Locked dependencies of a project: Cargo.lock, package-lock.json, poetry.lock, requirements.txt
+pub fn lockfile_targets(project: &Path) -> Vec<AuditTarget> {
+    let lockfiles: [(&str, &'static str, LockfileParser); 4] = [
+        ("Cargo.lock", CRATES_IO, parse_cargo_lock),
+        ("package-lock.json", NPM, parse_package_lock),
+        ("poetry.lock", PYPI, parse_poetry_lock),
+        ("requirements.txt", PYPI, parse_requirements),
+    ];
+
+    let mut targets = Vec::new();
+    for (file_name, ecosystem, parse) in lockfiles {
+        let path = project.join(file_name);
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let source = path.to_string_lossy().to_string();
+        let mut seen = HashSet::new();
+        for (name, version) in parse(&content) {
+            if seen.insert((name.clone(), version.clone())) {
+                targets.push(AuditTarget {
+                    ecosystem,
+                    name,
+                    version,
+                    source: source.clone(),
+                });
+            }
+        }
+    }
+    targets
+}
+
+fn build_finding(
+    target: &AuditTarget,
+    advisory: &Advisory,
+    versions: &AffectedVersions,
+) -> SecurityFinding {
+    let remediation = match versions {
+        AffectedVersions::Osv { ranges, .. } => ranges
+            .iter()
+            .flatten()
+            .filter_map(|event| match event {
+                RangeEvent::Fixed(fixed)
+                    if compare_versions(fixed, &target.version) == Ordering::Greater =>
+                {
+                    Some(fixed.as_str())
+                }
+                _ => None,
+            })
+            .min_by(|a, b| compare_versions(a, b))
+            .map(|fixed| format!("Upgrade {} to {} or later", target.name, fixed)),
+        AffectedVersions::Requirements { patched, .. } if !patched.is_empty() => Some(format!(
+            "Upgrade {} to a version matching {}",
+            target.name,
+            patched.join(" or ")
+        )),
+        AffectedVersions::Requirements { .. } => None,
+    }
+    .unwrap_or_else(|| {
+        format!(
+            "No patched version is published; remove {} or replace it",
+            target.name
+        )
+    });
+
+    let mut details = vec![format!("Found in {}", target.source)];
+    if let Some(score) = advisory.cvss_score {
+        details.push(format!("CVSS {:.1}", score));
+    }
+    if !advisory.aliases.is_empty() {
+        details.push(format!("Aliases: {}", advisory.aliases.join(", ")));
+    }
+
+    SecurityFinding {
+        tool_id: format!("{}:{}", target.ecosystem, target.name),
+        tool_name: format!("{} {}", target.name, target.version),
+        issue: advisory.id.clone(),
+        description: advisory.summary.clone(),
+        risk_level: advisory.risk_level(),
+        remediation,
+        details: details.join(" · "),
+    }
+}
+
+/// Advisory lookup key: PyPI names are normalized (PEP 503), the rest are case-insensitive
+fn package_key(ecosystem: &str, name: &str) -> String {
+    if ecosystem == PYPI {
+        normalize_python_name(name)
+    } else {
+        name.to_ascii_lowercase()
+    }
+}
+
+fn is_affected(version: &str, versions: &AffectedVersions) -> bool {
+    match versions {
+        AffectedVersions::Osv { ranges, versions } => {
+            versions.iter().any(|listed| listed == version)
+                || ranges.iter().any(|events| in_osv_range(version, events))
+        }
+        AffectedVersions::Requirements {
+            patched,
+            unaffected,
+        } => !patched
+            .iter()
+            .chain(unaffected)
+            .any(|requirement| matches_requirement(version, requirement)),
+    }
+}
+
+/// Evaluate OSV range events in version order, as the OSV schema specifies
+fn in_osv_range(version: &str, events: &[RangeEvent]) -> bool {
+    let event_version = |event: &RangeEvent| match event {
+        RangeEvent::Introduced(v) | RangeEvent::Fixed(v) | RangeEvent::LastAffected(v) => v.clone(),
+    };
+    let mut sorted = events.to_vec();
+    sorted.sort_by(|a, b| compare_versions(&event_version(a), &event_version(b)));
+
+    let mut affected = false;
+    for event in &sorted {
+        match event {
+            RangeEvent::Introduced(introduced)
+                if compare_versions(version, introduced) != Ordering::Less =>
+            {
+                affected = true
+            }
+            RangeEvent::Fixed(fixed) if compare_versions(version, fixed) != Ordering::Less => {
+                affected = false
+            }
+            RangeEvent::LastAffected(last)
+                if compare_versions(version, last) == Ordering::Greater =>
+            {
+                affected = false
+            }
+            _ => {}
+        }
+    }
+    affected
+}
+
+/// Whether `version` satisfies a Cargo-style requirement such as ">= 1.2.3, < 1.3" or "^0.5"
+fn matches_requirement(version: &str, requirement: &str) -> bool {
+    requirement
+        .split(',')
+        .map(str::trim)
+        .filter(|comparator| !comparator.is_empty())
+        .all(|comparator| {
+            if comparator == "*" {
+                return true;
+            }
+            let (op, base) = [">=", "<=", ">", "<", "=", "^", "~"]
+                .iter()
+                .find_map(|op| comparator.strip_prefix(op).map(|base| (*op, base.trim())))
+                .unwrap_or(("^", comparator));
+            let ordering = compare_versions(version, base);
+            match op {
+                ">=" => ordering != Ordering::Less,
+                "<=" => ordering != Ordering::Greater,
+                ">" => ordering == Ordering::Greater,
+                "<" => ordering == Ordering::Less,
+                "=" => ordering == Ordering::Equal,
+                _ => {
+                    ordering != Ordering::Less
+                        && compare_versions(version, &requirement_upper_bound(op, base))
+                            == Ordering::Less
+                }
+            }
+        })
+}
+
+/// Exclusive upper bound of a caret or tilde requirement
+fn requirement_upper_bound(op: &str, base: &str) -> String {
+    let parts: Vec<u64> = base
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    let major = parts.first().copied().unwrap_or(0);
+    let minor = parts.get(1).copied().unwrap_or(0);
+    let patch = parts.get(2).copied().unwrap_or(0);
+
+    if op == "~" {
+        return if parts.len() > 1 {
+            format!("{}.{}.0", major, minor + 1)
+        } else {
+            format!("{}.0.0", major + 1)
+        };
+    }
+    if major > 0 || parts.len() == 1 {
+        format!("{}.0.0", major + 1)
+    } else if minor > 0 || parts.len() == 2 {
+        format!("0.{}.0", minor + 1)
+    } else {
+        format!("0.0.{}", patch + 1)
+    }
+}
+
+/// CVSS base score from a numeric score or a CVSS v3 vector; v2/v4 vectors are not scored
+fn cvss_base_score(score: &str) -> Option<f64> {
+    let score = score.trim();
+    if let Ok(number) = score.parse::<f64>() {
+        return Some(number);
+    }
+    if !score.starts_with("CVSS:3") {
+        return None;
+    }
+
+    let metrics: HashMap<&str, &str> = score
+        .split('/')
+        .skip(1)
+        .filter_map(|metric| metric.split_once(':'))
+        .collect();
+    let changed = *metrics.get("S")? == "C";
+
+    let attack_vector = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        _ => 0.2,
+    };
+    let attack_complexity = if *metrics.get("AC")? == "L" {
+        0.77
+    } else {
+        0.44
+    };
+    let privileges = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        (_, false) => 0.27,
+        (_, true) => 0.5,
+    };
+    let user_interaction = if *metrics.get("UI")? == "N" {
+        0.85
+    } else {
+        0.62
+    };
+    let impact_of = |metric: &str| match metrics.get(metric).copied() {
+        Some("H") => Some(0.56),
+        Some("L") => Some(0.22),
+        Some("N") => Some(0.0),
+        _ => None,
+    };
+    let (confidentiality, integrity, availability) =
+        (impact_of("C")?, impact_of("I")?, impact_of("A")?);
+
+    let base_impact = 1.0 - (1.0 - confidentiality) * (1.0 - integrity) * (1.0 - availability);
+    let impact = if changed {
+        7.52 * (base_impact - 0.029) - 3.25 * (base_impact - 0.02f64).powi(15)
+    } else {
+        6.42 * base_impact
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * attack_vector * attack_complexity * privileges * user_interaction;
+    let total = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(total.min(10.0)))
+}
+
+/// CVSS v3.1 "Roundup": smallest one-decimal number not below the input
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        ((scaled / 10_000) + 1) as f64 / 10.0
+    }
+}
+
+// ============ Advisory file formats ============
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OsvFile {
+    Many(Vec<OsvRecord>),
+    One(Box<OsvRecord>),
+}
+
+#[derive(Deserialize)]
+struct OsvRecord {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    withdrawn: Option<String>,
+    #[serde(default)]
+    severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize)]
+struct OsvSeverity {
+    score: String,
+}
+
+#[derive(Deserialize)]
+struct OsvAffected {
+    package: Option<OsvPackage>,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<HashMap<String, String>>,
+}
+
+#[derive(Deserialize)]
+struct RustSecFile {
+    advisory: RustSecAdvisory,
+    #[serde(default)]
+    versions: RustSecVersions,
+}
+
+#[derive(Deserialize)]
+struct RustSecAdvisory {
+    id: String,
+    package: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    cvss: Option<String>,
+    #[serde(default)]
+    informational: Option<String>,
+    #[serde(default)]
+    withdrawn: Option<toml::Value>,
+}
+
+#[derive(Deserialize, Default)]
+struct RustSecVersions {
+    #[serde(default)]
+    patched: Vec<String>,
+    #[serde(default)]
+    unaffected: Vec<String>,
+}
+
+/// Parse one advisory file; `Ok(vec![])` for files that are not advisories (READMEs)
+fn parse_advisory_file(path: &Path, content: &str) -> Result<Vec<Advisory>, ()> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => {
+            let records = match serde_json::from_str::<OsvFile>(content).map_err(|_| ())? {
+                OsvFile::Many(records) => records,
+                OsvFile::One(record) => vec![*record],
+            };
+            Ok(records.into_iter().filter_map(osv_advisory).collect())
+        }
+        // advisory-db stores the TOML front matter in a ```toml fence, followed by "# Title"
+        Some("md") => match content.split_once("```toml") {
+            Some((_, rest)) => {
+                let (front_matter, body) = rest.split_once("```").ok_or(())?;
+                let title = body
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("# "))
+                    .map(str::to_string);
+                rustsec_advisory(front_matter, title).map(|advisory| advisory.into_iter().collect())
+            }
+            None => Ok(Vec::new()),
+        },
+        Some("toml") => {
+            rustsec_advisory(content, None).map(|advisory| advisory.into_iter().collect())
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn osv_advisory(record: OsvRecord) -> Option<Advisory> {
+    if record.withdrawn.is_some() {
+        return None;
+    }
+
+    let affected: Vec<AffectedPackage> = record
+        .affected
+        .into_iter()
+        .filter_map(|affected| {
+            let package = affected.package?;
+            if !ECOSYSTEMS.contains(&package.ecosystem.as_str()) {
+                return None;
+            }
+            let ranges = affected
+                .ranges
+                .into_iter()
+                .filter(|range| range.kind == "SEMVER" || range.kind == "ECOSYSTEM")
+                .map(|range| {
+                    range
+                        .events
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|(kind, version)| match kind.as_str() {
+                            "introduced" => Some(RangeEvent::Introduced(version)),
+                            "fixed" => Some(RangeEvent::Fixed(version)),
+                            "last_affected" => Some(RangeEvent::LastAffected(version)),
+                            _ => None,
+                        })
+                        .collect()
+                })
+                .collect();
+            Some(AffectedPackage {
+                ecosystem: package.ecosystem,
+                name: package.name,
+                versions: AffectedVersions::Osv {
+                    ranges,
+                    versions: affected.versions,
+                },
+            })
+        })
+        .collect();
+    if affected.is_empty() {
+        return None;
+    }
+
+    let summary = record
+        .summary
+        .or_else(|| {
+            record
+                .details
+                .and_then(|details| details.lines().next().map(str::to_string))
+        })
+        .unwrap_or_default();
+    let severity = record
+        .database_specific
+        .as_ref()
+        .and_then(|specific| specific.get("severity"))
+        .and_then(|severity| severity.as_str())
+        .map(str::to_string);
+
+    Some(Advisory {
+        id: record.id,
+        summary,
+        aliases: record.aliases,
+        cvss_score: record
+            .severity
+            .iter()
+            .find_map(|severity| cvss_base_score(&severity.score)),
+        severity,
+        informational: false,
+        affected,
+    })
+}
+
+fn rustsec_advisory(front_matter: &str, title: Option<String>) -> Result<Option<Advisory>, ()> {
+    let file: RustSecFile = toml::from_str(front_matter).map_err(|_| ())?;
+    if file.advisory.withdrawn.is_some() {
+        return Ok(None);
+    }
+
+    Ok(Some(Advisory {
+        id: file.advisory.id,
+        summary: title.or(file.advisory.title).unwrap_or_default(),
+        aliases: file.advisory.aliases,
+        cvss_score: file.advisory.cvss.as_deref().and_then(cvss_base_score),
+        severity: None,
+        informational: file.advisory.informational.is_some(),
+        affected: vec![AffectedPackage {
+            ecosystem: CRATES_IO.to_string(),
+            name: file.advisory.package,
+            versions: AffectedVersions::Requirements {
+                patched: file.versions.patched,
+                unaffected: file.versions.unaffected,
+            },
+        }],
+    }))
+}
+
+// ============ Lockfile formats ============
+
+#[derive(Deserialize)]
+struct TomlLockfile {
+    #[serde(default)]
+    package: Vec<TomlLockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct TomlLockedPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    source: Option<toml::Value>,
+}
+
+/// Registry crates from Cargo.lock (workspace and path crates have no `source`)
+fn parse_cargo_lock(content: &str) -> Vec<(String, String)> {
+    toml::from_str::<TomlLockfile>(content)
+        .map(|lockfile| {
+            lockfile
+                .package
+                .into_iter()
+                .filter(|package| {
+                    package
+                        .source
+                        .as_ref()
+                        .and_then(|source| source.as_str())
+                        .map(|source| {
+                            source.starts_with("registry+") || source.starts_with("sparse+")
+                        })
+                        .unwrap_or(false)
+                })
+                .map(|package| (package.name, package.version))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_poetry_lock(content: &str) -> Vec<(String, String)> {
+    toml::from_str::<TomlLockfile>(content)
+        .map(|lockfile| {
+            lockfile
+                .package
+                .into_iter()
+                .map(|package| (package.name, package.version))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// package-lock.json v2/v3 (`packages`) with a fallback to v1 (`dependencies`)
+fn parse_package_lock(content: &str) -> Vec<(String, String)> {
+    let Ok(lockfile) = serde_json::from_str::<serde_json::Value>(content) else {
+        return Vec::new();
+    };
+
+    if let Some(packages) = lockfile.get("packages").and_then(|p| p.as_object()) {
+        return packages
+            .iter()
+            .filter(|(_, entry)| entry.get("link").and_then(|l| l.as_bool()) != Some(true))
+            .filter_map(|(path, entry)| {
+                let name = path.rsplit_once("node_modules/")?.1;
+                let version = entry.get("version")?.as_str()?;
+                Some((name.to_string(), version.to_string()))
+            })
+            .collect();
+    }
+
+    fn collect_v1(dependencies: &serde_json::Value, out: &mut Vec<(String, String)>) {
+        let Some(dependencies) = dependencies.as_object() else {
+            return;
+        };
+        for (name, entry) in dependencies {
+            if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
+                out.push((name.clone(), version.to_string()));
+            }
+            if let Some(nested) = entry.get("dependencies") {
+                collect_v1(nested, out);
+            }
+        }
+    }
+    let mut packages = Vec::new();
+    if let Some(dependencies) = lockfile.get("dependencies") {
+        collect_v1(dependencies, &mut packages);
+    }
+    packages
+}
+
+/// Exact pins ("name==1.2.3", extras and markers allowed) from requirements.txt
+fn parse_requirements(content: &str) -> Vec<(String, String)> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('-'))
+        .filter_map(|line| {
+            let requirement = line.split(';').next()?.trim();
+            let (name, version) = requirement.split_once("==")?;
+            let name = name.split('[').next()?.trim();
+            let version = version.split(',').next()?.trim();
+            (!name.is_empty() && !version.is_empty())
+                .then(|| (name.to_string(), version.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_osv_and_rustsec_advisories() {
+        let osv = r#"{
+            "id": "GHSA-xxxx-yyyy-zzzz",
+            "summary": "Prototype pollution in lodash",
+            "aliases": ["CVE-2020-8203"],
+            "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+            "affected": [{
+                "package": {"ecosystem": "npm", "name": "lodash"},
+                "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}, {"fixed": "4.17.19"}]}]
+            }]
+        }"#;
+        let rustsec = "```toml\n[advisory]\nid = \"RUSTSEC-2021-0003\"\npackage = \"smallvec\"\ncvss = \"CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H\"\n\n[versions]\npatched = [\"^0.6.14\", \">= 1.6.1\"]\nunaffected = [\"< 0.6.10\"]\n```\n\n# Buffer overflow in SmallVec::insert_many\n";
+
+        let mut database = AdvisoryDatabase::default();
+        for (file, content) in [("GHSA.json", osv), ("RUSTSEC-2021-0003.md", rustsec)] {
+            for advisory in parse_advisory_file(Path::new(file), content).unwrap() {
+                database.insert(advisory);
+            }
+        }
+        assert_eq!(database.len(), 2);
+
+        let target = |ecosystem, name: &str, version: &str| AuditTarget {
+            ecosystem,
+            name: name.to_string(),
+            version: version.to_string(),
+            source: "test".to_string(),
+        };
+
+        let findings = database.check(&target(NPM, "lodash", "4.17.15"));
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].risk_level, RiskLevel::Critical);
+        assert_eq!(
+            findings[0].remediation,
+            "Upgrade lodash to 4.17.19 or later"
+        );
+        assert!(database.check(&target(NPM, "lodash", "4.17.21")).is_empty());
+
+        let findings = database.check(&target(CRATES_IO, "smallvec", "1.6.0"));
+        assert_eq!(findings[0].risk_level, RiskLevel::High);
+        assert_eq!(
+            findings[0].description,
+            "Buffer overflow in SmallVec::insert_many"
+        );
+        assert!(database
+            .check(&target(CRATES_IO, "smallvec", "0.6.14"))
+            .is_empty());
+        assert!(database
+            .check(&target(CRATES_IO, "smallvec", "0.6.9"))
+            .is_empty());
+        assert!(database
+            .check(&target(CRATES_IO, "smallvec", "1.6.1"))
+            .is_empty());
+
+        assert_eq!(
+            cvss_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"),
+            Some(6.1)
+        );
+    }
+
+    #[test]
+    fn parses_lockfiles() {
+        let cargo_lock = "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"smallvec\"\nversion = \"1.6.0\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n";
+        assert_eq!(
+            parse_cargo_lock(cargo_lock),
+            vec![("smallvec".to_string(), "1.6.0".to_string())]
+        );
+
+        let package_lock = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "app"},
+            "node_modules/lodash": {"version": "4.17.15"},
+            "node_modules/a/node_modules/@scope/b": {"version": "1.0.0"},
+            "node_modules/local": {"resolved": "../local", "link": true}
+        }}"#;
+        let mut packages = parse_package_lock(package_lock);
+        packages.sort();
+        assert_eq!(
+            packages,
+            vec![
+                ("@scope/b".to_string(), "1.0.0".to_string()),
+                ("lodash".to_string(), "4.17.15".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_requirements(
+                "# pinned\nrequests[socks]==2.19.0 ; python_version >= '3'\nflask>=2\n-r dev.txt\n"
+            ),
+            vec![("requests".to_string(), "2.19.0".to_string())]
+        );
+    }
+}
@@ -6,7 +6,7 @@
 use serde::{Deserialize, Serialize};
 
 /// Risk level classification
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum RiskLevel {
     Critical, // Immediate action required - exposed API keys, RCE risk
     High,     // Serious issue - exposed admin interface, auth bypass
@@ -24,6 +24,30 @@ impl RiskLevel {
         }
     }
 
+    /// Qualitative rating of a CVSS base score (9.0+ critical, 7.0+ high, 4.0+ medium)
+    pub fn from_cvss(score: f64) -> Self {
+        if score >= 9.0 {
+            RiskLevel::Critical
+        } else if score >= 7.0 {
+            RiskLevel::High
+        } else if score >= 4.0 {
+            RiskLevel::Medium
+        } else {
+            RiskLevel::Low
+        }
+    }
+
+    /// Severity labels used by advisory databases ("HIGH", "MODERATE", ...)
+    pub fn from_label(label: &str) -> Option<Self> {
+        match label.trim().to_ascii_uppercase().as_str() {
+            "CRITICAL" => Some(RiskLevel::Critical),
+            "HIGH" => Some(RiskLevel::High),
+            "MODERATE" | "MEDIUM" => Some(RiskLevel::Medium),
+            "LOW" => Some(RiskLevel::Low),
+            _ => None,
+        }
+    }
+
     pub fn emoji(&self) -> &'static str {
         match self {
             RiskLevel::Critical => "🔴",
@@ -1,10 +1,14 @@
 //! AI Tool Security Scanner Module
 //! Generic security scanning for AI coding tools with exposed ports/configs
 //!
+//! Also audits installed packages against an offline advisory database (advisories.rs)
+//!
 //! To add a new tool, simply add a new entry to `get_ai_tool_rules()` in definitions.rs
 
+pub mod advisories;
 mod definitions;
 pub mod scanner;
 
+pub use advisories::{audit_packages, PackageAuditResult};
 pub use definitions::{get_rules, SecurityScanResult};
 pub use scanner::{scan_ai_tool_security, scan_specific_tool};
@@ -350,7 +350,7 @@ fn collect_findings_for_tool(
     findings
 }
 
-fn build_summary(findings: &[SecurityFinding]) -> SecuritySummary {
+pub(super) fn build_summary(findings: &[SecurityFinding]) -> SecuritySummary {
     let mut summary = SecuritySummary {
         total_findings: findings.len(),
         critical: 0,
@@ -0,0 +1,283 @@
+//! System package manager integration for Dev Janitor v2
+//! Resolves which apt/dnf/pacman package owns a tool binary and removes it
+//! through pkexec or sudo, refusing removals that would take other packages along
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::error::DevJanitorError;
+use crate::utils::command::{command_output_with_timeout, run_command_outcome, CommandOutcome};
+
+const QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+const REMOVE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Package database that owns files on this system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemPackageManager {
+    Apt,
+    Dnf,
+    Yum,
+    Pacman,
+}
+
+impl SystemPackageManager {
+    /// Detect the package database in use, preferring dpkg, then rpm, then pacman
+    pub fn detect() -> Option<Self> {
+        if which::which("dpkg").is_ok() {
+            Some(Self::Apt)
+        } else if which::which("rpm").is_ok() {
+            if which::which("dnf").is_ok() {
+                Some(Self::Dnf)
+            } else {
+                Some(Self::Yum)
+            }
+        } else if which::which("pacman").is_ok() {
+            Some(Self::Pacman)
+        } else {
+            None
+        }
+    }
+
+    /// Command (without privilege escalation) that removes `package`
+    fn remove_args(self, package: &str) -> Vec<String> {
+        let args: &[&str] = match self {
+            Self::Apt => &["apt-get", "remove", "-y"],
+            Self::Dnf => &["dnf", "remove", "-y"],
+            Self::Yum => &["yum", "remove", "-y"],
+            Self::Pacman => &["pacman", "-R", "--noconfirm"],
+        };
+        args.iter()
+            .map(|arg| arg.to_string())
+            .chain(std::iter::once(package.to_string()))
+            .collect()
+    }
+}
+
+/// The OS package that installed a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwningPackage {
+    pub manager: SystemPackageManager,
+    pub package: String,
+    pub version: Option<String>,
+    pub path: String,
+    pub remove_command: String, // exact command for the user to run
+}
+
+/// Find the OS package that owns `path` (read-only: dpkg -S, rpm -qf, pacman -Qo)
+pub fn resolve_owner(path: &str) -> Option<OwningPackage> {
+    let manager = SystemPackageManager::detect()?;
+
+    // dpkg only knows the path it installed, so try the symlink first, then its target
+    let mut candidates = vec![path.to_string()];
+    if let Ok(resolved) = Path::new(path).canonicalize() {
+        let resolved = resolved.to_string_lossy().to_string();
+        if resolved != path {
+            candidates.push(resolved);
+        }
+    }
+
+    candidates.into_iter().find_map(|candidate| {
+        let (package, version) = match manager {
+            SystemPackageManager::Apt => {
+                let package = parse_dpkg_search(&query("dpkg", &["-S", &candidate])?)?;
+                let version = query("dpkg-query", &["-W", "-f=${Version}", &package])
+                    .map(|version| version.trim().to_string())
+                    .filter(|version| !version.is_empty());
+                (package, version)
+            }
+            SystemPackageManager::Dnf | SystemPackageManager::Yum => parse_rpm_query(&query(
+                "rpm",
+                &[
+                    "-qf",
+                    "--queryformat",
+                    "%{NAME} %{VERSION}-%{RELEASE}\\n",
+                    &candidate,
+                ],
+            )?)?,
+            SystemPackageManager::Pacman => {
+                parse_pacman_owner(&query("pacman", &["-Qo", &candidate])?)?
+            }
+        };
+
+        Some(OwningPackage {
+            remove_command: format!("sudo {}", manager.remove_args(&package).join(" ")),
+            manager,
+            package,
+            version,
+            path: candidate,
+        })
+    })
+}
+
+/// Remove the package that owns `path` through a pkexec (graphical) or sudo prompt
+pub fn uninstall_owner(path: &str) -> Result<CommandOutcome, DevJanitorError> {
+    let owner =
+        resolve_owner(path).ok_or_else(|| format!("{} is not owned by a system package", path))?;
+
+    ensure_removal_is_contained(&owner)?;
+
+    let mut args = owner.manager.remove_args(&owner.package);
+    let program = match privilege_program() {
+        Some(program) => program,
+        None => {
+            return Err(DevJanitorError::PermissionDenied(format!(
+                "No pkexec or sudo available. Run: {}",
+                owner.remove_command
+            )))
+        }
+    };
+    if program == "sudo" {
+        // Without a terminal sudo cannot prompt; fail fast instead of hanging
+        args.insert(0, "-n".to_string());
+    }
+
+    run_command_outcome(program, &args, REMOVE_TIMEOUT).into_result()
+}
+
+/// pkexec shows a graphical password prompt, so prefer it inside a desktop session
+fn privilege_program() -> Option<&'static str> {
+    let has_display = env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some();
+    if has_display && which::which("pkexec").is_ok() {
+        Some("pkexec")
+    } else if which::which("sudo").is_ok() {
+        Some("sudo")
+    } else {
+        None
+    }
+}
+
+/// Refuse removals that would cascade into packages that depend on this one
+fn ensure_removal_is_contained(owner: &OwningPackage) -> Result<(), String> {
+    let package = owner.package.as_str();
+    match owner.manager {
+        SystemPackageManager::Apt => {
+            let simulation = query("apt-get", &["-s", "remove", package])
+                .ok_or_else(|| format!("Failed to simulate removing {}", package))?;
+            let others: Vec<String> = parse_apt_simulation(&simulation)
+                .into_iter()
+                .filter(|name| name != package)
+                .collect();
+            if others.is_empty() {
+                Ok(())
+            } else {
+                Err(format!(
+                    "Removing {} would also remove: {}. Run `{}` yourself if that is intended.",
+                    package,
+                    others.join(", "),
+                    owner.remove_command
+                ))
+            }
+        }
+        SystemPackageManager::Dnf | SystemPackageManager::Yum => {
+            // rpm -e --test fails when installed packages still require this one
+            let output =
+                command_output_with_timeout("rpm", &["-e", "--test", package], QUERY_TIMEOUT)
+                    .map_err(|error| error.to_string())?;
+            if output.status.success() {
+                Ok(())
+            } else {
+                Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+            }
+        }
+        // pacman -R (without -c) already refuses to break dependencies
+        SystemPackageManager::Pacman => Ok(()),
+    }
+}
+
+fn query(program: &str, args: &[&str]) -> Option<String> {
+    let output = command_output_with_timeout(program, args, QUERY_TIMEOUT).ok()?;
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        None
+    }
+}
+
+/// `dpkg -S` prints "nodejs: /usr/bin/node" or "libfoo:amd64, libbar: /path";
+/// a path claimed by several packages has no single owner and yields `None`
+fn parse_dpkg_search(output: &str) -> Option<String> {
+    let (packages, _) = output
+        .lines()
+        .filter(|line| !line.starts_with("diversion by"))
+        .find_map(|line| line.split_once(": /"))?;
+    let mut owners: Vec<&str> = packages
+        .split(", ")
+        .map(|package| package.split(':').next().unwrap_or(package).trim())
+        .filter(|package| !package.is_empty())
+        .collect();
+    // The same package installed for several architectures still counts as one owner
+    owners.dedup();
+    match owners[..] {
+        [owner] => Some(owner.to_string()),
+        _ => None,
+    }
+}
+
+/// `rpm -qf --queryformat "%{NAME} %{VERSION}-%{RELEASE}\n"`
+fn parse_rpm_query(output: &str) -> Option<(String, Option<String>)> {
+    let line = output.lines().next()?.trim();
+    let mut parts = line.split_whitespace();
+    let name = parts.next()?.to_string();
+    Some((name, parts.next().map(str::to_string)))
+}
+
+/// `pacman -Qo` prints "/usr/bin/node is owned by nodejs 21.1.0-1"
+fn parse_pacman_owner(output: &str) -> Option<(String, Option<String>)> {
+    let (_, owner) = output.lines().next()?.split_once(" is owned by ")?;
+    let mut parts = owner.split_whitespace();
+    let name = parts.next()?.to_string();
+    Some((name, parts.next().map(str::to_string)))
+}
+
+/// Package names from the "Remv" lines of `apt-get -s remove`
+fn parse_apt_simulation(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.strip_prefix("Remv "))
+        .filter_map(|rest| rest.split_whitespace().next())
+        .map(|name| name.split(':').next().unwrap_or(name).to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_owner_queries() {
+        assert_eq!(
+            parse_dpkg_search("diversion by dash from: /bin/sh\nnodejs: /usr/bin/node\n")
+                .as_deref(),
+            Some("nodejs")
+        );
+        assert_eq!(
+            parse_dpkg_search("libpython3.12:amd64, python3.12: /usr/bin/python3.12"),
+            None
+        );
+        assert_eq!(
+            parse_dpkg_search("libc6:amd64, libc6:i386: /usr/lib/locale").as_deref(),
+            Some("libc6")
+        );
+        assert_eq!(
+            parse_rpm_query("git-core 2.43.0-1.fc39\n"),
+            Some(("git-core".to_string(), Some("2.43.0-1.fc39".to_string())))
+        );
+        assert_eq!(
+            parse_pacman_owner("/usr/bin/cmake is owned by cmake 3.28.1-1\n"),
+            Some(("cmake".to_string(), Some("3.28.1-1".to_string())))
+        );
+        assert_eq!(
+            parse_apt_simulation(
+                "NOTE: This is only a simulation!\nRemv npm [9.2.0]\nRemv nodejs:amd64 [18.19.0]\n"
+            ),
+            vec!["npm".to_string(), "nodejs".to_string()]
+        );
+        assert_eq!(
+            SystemPackageManager::Apt.remove_args("cmake").join(" "),
+            "apt-get remove -y cmake"
+        );
+    }
+}
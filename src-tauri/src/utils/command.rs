@@ -1,8 +1,110 @@
+use serde::{Deserialize, Serialize};
 use std::io::{self, Read};
+use std::path::Path;
 use std::process::{Child, Command, Output, Stdio};
 use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
+use crate::error::{DevJanitorError, FailureKind};
+
+/// Everything known about one finished (or failed to start) command
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandOutcome {
+    pub command: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub failure: Option<FailureKind>,
+    pub hint: Option<String>,
+}
+
+impl CommandOutcome {
+    pub fn success(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// One-line description, used as the error message of failed operations
+    pub fn summary(&self) -> String {
+        if self.success() {
+            return format!("`{}` succeeded", self.command);
+        }
+
+        let detail = self
+            .stderr
+            .lines()
+            .chain(self.stdout.lines())
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or("no output");
+        match self.exit_code {
+            Some(code) => format!("`{}` exited with {}: {}", self.command, code, detail),
+            None => format!("`{}` failed: {}", self.command, detail),
+        }
+    }
+
+    /// `Ok(self)` on success, otherwise a structured `DevJanitorError`
+    pub fn into_result(self) -> Result<CommandOutcome, DevJanitorError> {
+        if self.success() {
+            Ok(self)
+        } else {
+            Err(DevJanitorError::PackageOperation(Box::new(self)))
+        }
+    }
+
+    fn failed(mut self, failure: FailureKind) -> Self {
+        self.hint = failure.hint().map(str::to_string);
+        self.failure = Some(failure);
+        self
+    }
+}
+
+/// Run a command and capture a `CommandOutcome` instead of discarding stderr
+pub fn run_command_outcome(program: &str, args: &[String], timeout: Duration) -> CommandOutcome {
+    let start = Instant::now();
+    let result = command_output_with_timeout_vec(program, args, timeout);
+
+    let outcome = CommandOutcome {
+        command: std::iter::once(program)
+            .chain(args.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" "),
+        duration_ms: start.elapsed().as_millis() as u64,
+        ..Default::default()
+    };
+
+    match result {
+        Ok(output) => {
+            let outcome = CommandOutcome {
+                exit_code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+                ..outcome
+            };
+            if output.status.success() {
+                outcome
+            } else {
+                let failure =
+                    FailureKind::classify(&format!("{}\n{}", outcome.stderr, outcome.stdout));
+                outcome.failed(failure)
+            }
+        }
+        Err(error) => {
+            let failure = match error.kind() {
+                io::ErrorKind::TimedOut => FailureKind::Timeout,
+                io::ErrorKind::NotFound => FailureKind::NotFound,
+                io::ErrorKind::PermissionDenied => FailureKind::PermissionDenied,
+                _ => FailureKind::Other,
+            };
+            CommandOutcome {
+                stderr: error.to_string(),
+                ..outcome
+            }
+            .failed(failure)
+        }
+    }
+}
+
 pub fn command_no_window(program: &str) -> Command {
     #[allow(unused_mut)]
     let mut command = Command::new(program);
@@ -27,9 +129,20 @@ pub fn command_output_with_timeout_vec(
     program: &str,
     args: &[String],
     timeout: Duration,
+) -> io::Result<Output> {
+    command_output_in_dir(program, args, None, timeout)
+}
+
+/// Like `command_output_with_timeout_vec`, run from `dir` so per-directory version
+/// selection (pyenv, asdf, mise, volta) applies
+pub fn command_output_in_dir(
+    program: &str,
+    args: &[String],
+    dir: Option<&Path>,
+    timeout: Duration,
 ) -> io::Result<Output> {
     let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
-    let mut child = spawn_command(program, &arg_refs)?;
+    let mut child = spawn_command(program, &arg_refs, dir)?;
     let stdout_handle = child.stdout.take().map(spawn_reader);
     let stderr_handle = child.stderr.take().map(spawn_reader);
 
@@ -85,13 +198,17 @@ fn join_reader(handle: Option<JoinHandle<Vec<u8>>>) -> Vec<u8> {
         .unwrap_or_default()
 }
 
-fn spawn_command(program: &str, args: &[&str]) -> io::Result<Child> {
+fn spawn_command(program: &str, args: &[&str], dir: Option<&Path>) -> io::Result<Child> {
     #[cfg(target_os = "windows")]
     {
         if should_use_cmd_wrapper(program) {
             use std::os::windows::process::CommandExt;
 
-            return command_no_window("cmd")
+            let mut command = command_no_window("cmd");
+            if let Some(dir) = dir {
+                command.current_dir(dir);
+            }
+            return command
                 .args(["/D", "/S", "/C"])
                 .raw_arg(format_cmd_command_line(program, args))
                 .stdin(Stdio::null())
@@ -101,7 +218,11 @@ fn spawn_command(program: &str, args: &[&str]) -> io::Result<Child> {
         }
     }
 
-    command_no_window(program)
+    let mut command = command_no_window(program);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    command
         .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
@@ -214,6 +335,25 @@ mod tests {
         );
     }
 
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn captures_failed_command_outcome() {
+        let outcome = run_command_outcome(
+            "sh",
+            &[
+                "-c".to_string(),
+                "echo 'npm ERR! code EACCES' >&2; exit 243".to_string(),
+            ],
+            Duration::from_secs(5),
+        );
+
+        assert_eq!(outcome.exit_code, Some(243));
+        assert_eq!(outcome.failure, Some(FailureKind::PermissionDenied));
+        assert!(outcome.hint.is_some());
+        assert!(outcome.summary().contains("EACCES"));
+        assert!(outcome.into_result().is_err());
+    }
+
     #[cfg(target_os = "windows")]
     #[test]
     fn quotes_windows_cmd_command_line() {
@@ -1 +1,3 @@
 pub mod command;
+pub mod storage;
+pub mod version;
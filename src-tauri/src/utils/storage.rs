@@ -0,0 +1,59 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Matches the bundle identifier in tauri.conf.json
+const APP_IDENTIFIER: &str = "dev.janitor.app";
+
+/// Per-user directory for Dev Janitor's own state files
+pub fn app_data_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = env::var("APPDATA").ok().map(PathBuf::from);
+
+    #[cfg(target_os = "macos")]
+    let base = env::var("HOME")
+        .ok()
+        .map(|home| PathBuf::from(home).join("Library/Application Support"));
+
+    #[cfg(all(not(target_os = "windows"), not(target_os = "macos")))]
+    let base = env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var("HOME")
+                .ok()
+                .map(|home| PathBuf::from(home).join(".local/share"))
+        });
+
+    base.map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Path of a state file inside the app data directory
+pub fn app_data_file(file_name: &str) -> Option<PathBuf> {
+    app_data_dir().map(|dir| dir.join(file_name))
+}
+
+/// Read a JSON state file, returning `None` when it is missing or unreadable
+pub fn load_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Write a JSON state file atomically (temp file + rename)
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|error| format!("Failed to create {}: {}", parent.display(), error))?;
+    }
+
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|error| format!("Failed to serialize {}: {}", path.display(), error))?;
+    let temp_path = path.with_extension("json.tmp");
+
+    fs::write(&temp_path, content)
+        .and_then(|_| fs::rename(&temp_path, path))
+        .map_err(|error| format!("Failed to write {}: {}", path.display(), error))
+}
@@ -0,0 +1,247 @@
+//! Lenient version comparison for package versions
+//! Handles semver, PEP 440-style and plain dotted versions without a full parser
+
+use std::cmp::Ordering;
+
+/// Compare two version strings segment by segment
+///
+/// Numeric segments compare numerically, a pre-release ("1.0.0-rc.1", "2.0b1") sorts
+/// before its release, and build metadata ("+build") is ignored.
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let (left_release, left_pre) = split_version(left);
+    let (right_release, right_pre) = split_version(right);
+
+    let left_parts = numeric_parts(left_release);
+    let right_parts = numeric_parts(right_release);
+    let len = left_parts.len().max(right_parts.len());
+    for index in 0..len {
+        let left_part = left_parts.get(index).copied().unwrap_or(0);
+        let right_part = right_parts.get(index).copied().unwrap_or(0);
+        match left_part.cmp(&right_part) {
+            Ordering::Equal => {}
+            other => return other,
+        }
+    }
+
+    match (left_pre, right_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(left_pre), Some(right_pre)) => compare_pre_release(left_pre, right_pre),
+    }
+}
+
+/// Split "v1.2.3-rc.1+build" into ("1.2.3", Some("rc.1")); "2.0b1" into ("2.0", Some("b1"))
+fn split_version(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim().trim_start_matches(['v', 'V', '=']);
+    let version = version.split('+').next().unwrap_or(version);
+
+    if let Some((release, pre)) = version.split_once('-') {
+        return (release, Some(pre));
+    }
+    match version.find(|c: char| !c.is_ascii_digit() && c != '.') {
+        Some(index) if index > 0 => (
+            version[..index].trim_end_matches('.'),
+            Some(&version[index..]),
+        ),
+        _ => (version, None),
+    }
+}
+
+fn numeric_parts(release: &str) -> Vec<u64> {
+    release
+        .split('.')
+        .map(|part| {
+            part.chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+fn compare_pre_release(left: &str, right: &str) -> Ordering {
+    let mut left_ids = left.split(['.', '-']);
+    let mut right_ids = right.split(['.', '-']);
+    loop {
+        match (left_ids.next(), right_ids.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left_id), Some(right_id)) => {
+                let ordering = match (left_id.parse::<u64>(), right_id.parse::<u64>()) {
+                    (Ok(left_number), Ok(right_number)) => left_number.cmp(&right_number),
+                    _ => left_id.cmp(right_id),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+/// Whether `version` satisfies a range constraint in Composer, npm or PEP 440 syntax
+/// ("^8.1", ">=7.4 <8.3", "7.4.* || ^8.0", ">=3.8,!=3.9.*")
+pub fn satisfies_constraint(version: &str, constraint: &str) -> bool {
+    constraint
+        .split('|')
+        .map(str::trim)
+        .filter(|alternative| !alternative.is_empty())
+        .any(|alternative| {
+            if let Some((low, high)) = alternative.split_once(" - ") {
+                return compare_versions(version, low.trim()) != Ordering::Less
+                    && compare_versions(version, high.trim()) != Ordering::Greater;
+            }
+
+            // Operators may be separated from their version by a space (">= 8.1")
+            let mut comparators: Vec<String> = Vec::new();
+            let mut pending_op = String::new();
+            for token in alternative.split(|c: char| c == ',' || c.is_whitespace()) {
+                if token.is_empty() {
+                    continue;
+                }
+                if token.chars().all(|c| "<>=!^~".contains(c)) {
+                    pending_op = token.to_string();
+                } else {
+                    comparators.push(format!("{}{}", pending_op, token));
+                    pending_op.clear();
+                }
+            }
+            comparators
+                .iter()
+                .all(|comparator| matches_comparator(version, comparator))
+        })
+}
+
+fn matches_comparator(version: &str, comparator: &str) -> bool {
+    // Stability flags ("@dev", "@stable") do not affect platform packages
+    let comparator = comparator.split('@').next().unwrap_or(comparator);
+    if comparator == "*" {
+        return true;
+    }
+
+    let (op, base) = [">=", "<=", "!=", "==", "~=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|base| (*op, base)))
+        .unwrap_or(("=", comparator));
+    let base = base.trim_start_matches(['v', 'V']);
+
+    let at_least = |lower: &str| compare_versions(version, lower) != Ordering::Less;
+    let below = |upper: &str| compare_versions(version, upper) == Ordering::Less;
+
+    // Wildcards: "8.*" means >=8.0 <9.0, and "!=3.9.*" anything outside 3.9
+    if let Some(prefix) = base.strip_suffix(".*").or_else(|| base.strip_suffix(".x")) {
+        let parts = version_parts(prefix);
+        let in_range = at_least(prefix) && below(&bump(&parts, parts.len() - 1));
+        return if op == "!=" { !in_range } else { in_range };
+    }
+
+    let parts = version_parts(base);
+    match op {
+        ">=" => at_least(base),
+        "<=" => compare_versions(version, base) != Ordering::Greater,
+        ">" => compare_versions(version, base) == Ordering::Greater,
+        "<" => below(base),
+        "!=" => compare_versions(version, base) != Ordering::Equal,
+        "^" | "~" | "~=" => {
+            at_least(base) && range_upper_bound(op, base).is_some_and(|upper| below(&upper))
+        }
+        _ => {
+            // A bare "8.1" is an exact match on the given segments
+            let given = version_parts(version);
+            parts
+                .iter()
+                .enumerate()
+                .all(|(index, part)| given.get(index).copied().unwrap_or(0) == *part)
+        }
+    }
+}
+
+/// Exclusive upper bound of a caret or tilde range ("^", "1.2" -> "2"; "~", "1.2.3" -> "1.3")
+pub fn range_upper_bound(op: &str, base: &str) -> Option<String> {
+    let parts = version_parts(base);
+    let index = match op {
+        // First non-zero segment is the one that may not change
+        "^" => parts
+            .iter()
+            .position(|part| *part != 0)
+            .unwrap_or(parts.len() - 1),
+        // ~8.1 allows 8.x, ~8.1.2 allows 8.1.x (PEP 440 "~=" reads the same)
+        "~" | "~=" => {
+            if parts.len() > 2 {
+                parts.len() - 2
+            } else {
+                0
+            }
+        }
+        _ => return None,
+    };
+    Some(bump(&parts, index))
+}
+
+fn version_parts(version: &str) -> Vec<u64> {
+    let parts: Vec<u64> = version
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+    if parts.is_empty() {
+        vec![0]
+    } else {
+        parts
+    }
+}
+
+/// Increment segment `index` and drop everything after it ("8.1.2", 0 -> "9")
+fn bump(parts: &[u64], index: usize) -> String {
+    parts[..index]
+        .iter()
+        .copied()
+        .chain(std::iter::once(parts[index] + 1))
+        .map(|part| part.to_string())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_release_and_pre_release_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("v2.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0-rc.1", "1.0.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.0.0-rc.2", "1.0.0-rc.10"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("2.0b1", "2.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.3+build.5", "1.2.3"), Ordering::Equal);
+    }
+
+    #[test]
+    fn matches_range_constraints() {
+        assert!(satisfies_constraint("8.2.10", "^8.1"));
+        assert!(!satisfies_constraint("9.0.0", "^8.1"));
+        assert!(satisfies_constraint("7.4.33", "^7.4 || ^8.0"));
+        assert!(satisfies_constraint("8.1.0", ">= 7.4, <8.3"));
+        assert!(!satisfies_constraint("8.3.1", ">=7.4 <8.3"));
+        assert!(satisfies_constraint("8.1.9", "~8.1.0"));
+        assert!(!satisfies_constraint("8.2.0", "~8.1.0"));
+        assert!(satisfies_constraint("8.3.0", "~8.1"));
+        assert!(satisfies_constraint("7.4.1", "7.4.*"));
+        assert!(satisfies_constraint("8.2.0", "8.2"));
+        assert!(satisfies_constraint("8.0.5", "*"));
+
+        // PEP 440 exclusions and compatible releases
+        assert!(satisfies_constraint("3.12.1", ">=3.8,!=3.9.*"));
+        assert!(!satisfies_constraint("3.9.2", ">=3.8,!=3.9.*"));
+        assert!(satisfies_constraint("3.12.1", ">=2.7,!=3.0.*,!=3.1.*"));
+        assert!(!satisfies_constraint("3.1.4", ">=2.7,!=3.0.*,!=3.1.*"));
+        assert!(satisfies_constraint("3.9.18", "==3.9.*"));
+        assert!(satisfies_constraint("3.12.1", "~=3.8"));
+        assert!(!satisfies_constraint("3.9.0", "~=3.8.1"));
+    }
+}
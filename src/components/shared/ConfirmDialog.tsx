@@ -1,3 +1,4 @@
+import type { ReactNode } from 'react';
 import * as Dialog from '@radix-ui/react-dialog';
 import { useTranslation } from 'react-i18next';
 
@@ -8,6 +9,7 @@ interface ConfirmDialogProps {
     confirmLabel?: string;
     cancelLabel?: string;
     danger?: boolean;
+    children?: ReactNode;
     onConfirm: () => void;
     onCancel: () => void;
 }
@@ -19,6 +21,7 @@ export function ConfirmDialog({
     confirmLabel,
     cancelLabel,
     danger,
+    children,
     onConfirm,
     onCancel,
 }: ConfirmDialogProps) {
@@ -33,6 +36,7 @@ export function ConfirmDialog({
                     <Dialog.Description className="confirm-description">
                         {description}
                     </Dialog.Description>
+                    {children}
                     <div className="confirm-actions">
                         <button className="btn btn-secondary" onClick={onCancel}>
                             {cancelLabel ?? t('common.cancel', { defaultValue: 'Cancel' })}
@@ -1,9 +1,39 @@
 import { useState, useCallback, useMemo } from 'react';
 import { useTranslation } from 'react-i18next';
-import { scanPackages, updatePackage, uninstallPackage } from '../../ipc/commands';
+import {
+    CommandError,
+    PackageDependencyReport,
+    getPackageDependencies,
+    scanPackages,
+    updatePackage,
+    uninstallPackage,
+} from '../../ipc/commands';
 import { useAppStore, PackageInfoStore } from '../../store';
 import { ConfirmDialog } from '../shared/ConfirmDialog';
 
+type PackageSortKey = 'name' | 'size' | 'last_accessed';
+
+// Largest / most recently used first; packages without data sort last
+function comparePackages(sortBy: PackageSortKey) {
+    return (a: PackageInfoStore, b: PackageInfoStore): number => {
+        if (sortBy === 'size') {
+            return (b.size ?? -1) - (a.size ?? -1) || a.name.localeCompare(b.name);
+        }
+        if (sortBy === 'last_accessed') {
+            return (b.last_accessed ?? '').localeCompare(a.last_accessed ?? '') || a.name.localeCompare(b.name);
+        }
+        return a.name.localeCompare(b.name);
+    };
+}
+
+// Append the backend's suggested fix (e.g. retry with --user, use pipx) to the error
+function describeOperationError(e: unknown): string {
+    if (e instanceof CommandError && e.outcome?.hint) {
+        return `${e.message}\n${e.outcome.hint}`;
+    }
+    return String(e);
+}
+
 export function PackagesView() {
     const { t } = useTranslation();
 
@@ -20,7 +50,13 @@ export function PackagesView() {
     const [error, setError] = useState<string | null>(null);
     const [success, setSuccess] = useState<string | null>(null);
     const [operatingPackage, setOperatingPackage] = useState<string | null>(null);
-    const [pendingUninstall, setPendingUninstall] = useState<{ manager: string; name: string } | null>(null);
+    const [pendingUninstall, setPendingUninstall] = useState<{
+        manager: string;
+        name: string;
+        dependencies: PackageDependencyReport | null;
+    } | null>(null);
+    const [removeOrphans, setRemoveOrphans] = useState(false);
+    const [sortBy, setSortBy] = useState<PackageSortKey>('name');
 
     const scanPackagesData = useCallback(async ({ preserveMessages = false }: { preserveMessages?: boolean } = {}) => {
         setIsScanning(true);
@@ -53,14 +89,17 @@ export function PackagesView() {
             setSuccess(t('packages.success_update', { name }));
             await scanPackagesData({ preserveMessages: true });
         } catch (e) {
-            setError(String(e));
+            setError(describeOperationError(e));
         } finally {
             setOperatingPackage(null);
         }
     };
 
-    const handleUninstall = (manager: string, name: string) => {
-        setPendingUninstall({ manager, name });
+    // Look up reverse dependencies and would-be orphans before asking for confirmation
+    const handleUninstall = async (manager: string, name: string) => {
+        const dependencies = await getPackageDependencies(manager, name).catch(() => null);
+        setRemoveOrphans(false);
+        setPendingUninstall({ manager, name, dependencies });
     };
 
     const confirmUninstall = async () => {
@@ -68,18 +107,23 @@ export function PackagesView() {
             return;
         }
 
-        const { manager, name } = pendingUninstall;
+        const { manager, name, dependencies } = pendingUninstall;
+        const force = (dependencies?.required_by.length ?? 0) > 0;
         setPendingUninstall(null);
         setOperatingPackage(`uninstall-${manager}-${name}`);
         setError(null);
         setSuccess(null);
 
         try {
-            await uninstallPackage(manager, name);
-            setSuccess(t('packages.success_uninstall', { name }));
+            const report = await uninstallPackage(manager, name, force, removeOrphans);
+            setSuccess(
+                report.orphans.length > 0
+                    ? t('packages.success_uninstall_orphans', { name, count: report.orphans.length })
+                    : t('packages.success_uninstall', { name })
+            );
             await scanPackagesData({ preserveMessages: true });
         } catch (e) {
-            setError(String(e));
+            setError(describeOperationError(e));
         } finally {
             setOperatingPackage(null);
         }
@@ -121,12 +165,16 @@ export function PackagesView() {
             grouped[pkg.manager].push(pkg);
         }
 
+        for (const managerPackages of Object.values(grouped)) {
+            managerPackages.sort(comparePackages(sortBy));
+        }
+
         return {
             managers: [...managerSet],
             groupedPackages: grouped,
             outdatedCount: nextOutdatedCount,
         };
-    }, [packages, filterManager, filterOutdated]);
+    }, [packages, filterManager, filterOutdated, sortBy]);
 
     return (
         <div className="view-container packages-view">
@@ -177,6 +225,15 @@ export function PackagesView() {
                         />
                         {t('packages.filter_outdated_only')}
                     </label>
+                    <select
+                        className="filter-select"
+                        value={sortBy}
+                        onChange={(e) => setSortBy(e.target.value as PackageSortKey)}
+                    >
+                        <option value="name">{t('packages.sort_by_name')}</option>
+                        <option value="size">{t('packages.sort_by_size')}</option>
+                        <option value="last_accessed">{t('packages.sort_by_last_accessed')}</option>
+                    </select>
                 </div>
             )}
 
@@ -218,9 +275,10 @@ export function PackagesView() {
                                 <table className="table">
                                     <thead>
                                         <tr>
-                                            <th className="col-w-35">{t('packages.name')}</th>
-                                            <th className="col-w-15">{t('packages.version')}</th>
-                                            <th className="col-w-15">{t('packages.latest')}</th>
+                                            <th className="col-w-30">{t('packages.name')}</th>
+                                            <th className="col-w-12">{t('packages.version')}</th>
+                                            <th className="col-w-12">{t('packages.latest')}</th>
+                                            <th className="col-w-10">{t('packages.size')}</th>
                                             <th className="col-w-15">{t('packages.status')}</th>
                                             <th className="col-w-20">{t('tools.actions')}</th>
                                         </tr>
@@ -236,40 +294,55 @@ export function PackagesView() {
                                                 </td>
                                                 <td>{pkg.version}</td>
                                                 <td>{pkg.latest || '-'}</td>
+                                                <td
+                                                    title={pkg.last_accessed ? t('packages.last_accessed', { time: pkg.last_accessed }) : undefined}
+                                                >
+                                                    {pkg.size_display || '-'}
+                                                </td>
                                                 <td>
                                                     {pkg.is_outdated ? (
                                                         <span className="badge badge-warning">{t('packages.outdated')}</span>
                                                     ) : (
                                                         <span className="badge badge-success">{t('packages.up_to_date')}</span>
                                                     )}
+                                                    {pkg.read_only && (
+                                                        <span
+                                                            className="badge badge-info"
+                                                            title={[pkg.read_only_reason, pkg.suggestion].filter(Boolean).join('\n')}
+                                                        >
+                                                            {t('packages.system_managed')}
+                                                        </span>
+                                                    )}
                                                 </td>
                                                 <td>
-                                                    <div className="action-buttons">
-                                                        {pkg.is_outdated && (
+                                                    {!pkg.read_only && (
+                                                        <div className="action-buttons">
+                                                            {pkg.is_outdated && (
+                                                                <button
+                                                                    className="btn btn-primary btn-small"
+                                                                    onClick={() => handleUpdate(pkg.manager, pkg.name)}
+                                                                    disabled={operatingPackage !== null}
+                                                                >
+                                                                    {operatingPackage === `update-${pkg.manager}-${pkg.name}` ? (
+                                                                        <span className="spinner spinner-xs" />
+                                                                    ) : (
+                                                                        t('packages.update')
+                                                                    )}
+                                                                </button>
+                                                            )}
                                                             <button
-                                                                className="btn btn-primary btn-small"
-                                                                onClick={() => handleUpdate(pkg.manager, pkg.name)}
+                                                                className="btn btn-secondary btn-small"
+                                                                onClick={() => handleUninstall(pkg.manager, pkg.name)}
                                                                 disabled={operatingPackage !== null}
                                                             >
-                                                                {operatingPackage === `update-${pkg.manager}-${pkg.name}` ? (
+                                                                {operatingPackage === `uninstall-${pkg.manager}-${pkg.name}` ? (
                                                                     <span className="spinner spinner-xs" />
                                                                 ) : (
-                                                                    t('packages.update')
+                                                                    t('packages.uninstall')
                                                                 )}
                                                             </button>
-                                                        )}
-                                                        <button
-                                                            className="btn btn-secondary btn-small"
-                                                            onClick={() => handleUninstall(pkg.manager, pkg.name)}
-                                                            disabled={operatingPackage !== null}
-                                                        >
-                                                            {operatingPackage === `uninstall-${pkg.manager}-${pkg.name}` ? (
-                                                                <span className="spinner spinner-xs" />
-                                                            ) : (
-                                                                t('packages.uninstall')
-                                                            )}
-                                                        </button>
-                                                    </div>
+                                                        </div>
+                                                    )}
                                                 </td>
                                             </tr>
                                         ))}
@@ -284,11 +357,34 @@ export function PackagesView() {
             <ConfirmDialog
                 open={pendingUninstall !== null}
                 title={t('packages.confirm_uninstall_title', { defaultValue: 'Uninstall Package' })}
-                description={pendingUninstall ? t('packages.confirm_uninstall', { name: pendingUninstall.name }) : ''}
+                description={pendingUninstall ? [
+                    t('packages.confirm_uninstall', { name: pendingUninstall.name }),
+                    ...(pendingUninstall.dependencies?.required_by.length
+                        ? [t('packages.required_by_warning', {
+                            packages: pendingUninstall.dependencies.required_by.join(', '),
+                        })]
+                        : []),
+                ].join('\n\n') : ''}
+                confirmLabel={pendingUninstall?.dependencies?.required_by.length
+                    ? t('packages.uninstall_anyway')
+                    : undefined}
                 danger
                 onConfirm={confirmUninstall}
                 onCancel={() => setPendingUninstall(null)}
-            />
+            >
+                {pendingUninstall?.dependencies?.orphans_after_uninstall.length ? (
+                    <label className="filter-checkbox">
+                        <input
+                            type="checkbox"
+                            checked={removeOrphans}
+                            onChange={(e) => setRemoveOrphans(e.target.checked)}
+                        />
+                        {t('packages.remove_orphans', {
+                            packages: pendingUninstall.dependencies.orphans_after_uninstall.join(', '),
+                        })}
+                    </label>
+                ) : null}
+            </ConfirmDialog>
         </div>
     );
 }
@@ -1,6 +1,6 @@
 import { useState, useCallback, Fragment } from 'react';
 import { useTranslation } from 'react-i18next';
-import { scanTools, uninstallTool } from '../../ipc/commands';
+import { scanTools, uninstallTool, uninstallRuntimeVersion, ToolVersion } from '../../ipc/commands';
 import { useAppStore } from '../../store';
 import { ConfirmDialog } from '../shared/ConfirmDialog';
 
@@ -10,6 +10,9 @@ export function ToolsView() {
     // Use global store for data that should persist
     const tools = useAppStore((state) => state.toolsData);
     const setTools = useAppStore((state) => state.setToolsData);
+    // Pins are looked up under the project folder chosen in the cache view, or the home directory
+    const projectPath = useAppStore((state) => state.cacheProjectPath);
+    const projectRoot = projectPath.trim() || undefined;
 
     // Local state for transient UI
     const [isScanning, setIsScanning] = useState(false);
@@ -26,28 +29,28 @@ export function ToolsView() {
         }
 
         try {
-            const detected = await scanTools();
+            const detected = await scanTools(projectRoot);
             setTools(detected);
         } catch (e) {
             setError(String(e));
         } finally {
             setIsScanning(false);
         }
-    }, [setTools]);
+    }, [projectRoot, setTools]);
 
     const handleScan = useCallback(() => {
         void scanToolsData();
     }, [scanToolsData]);
 
-    const [pendingUninstall, setPendingUninstall] = useState<{ id: string; name: string; path: string } | null>(null);
+    const [pendingUninstall, setPendingUninstall] = useState<{ id: string; name: string; path: string; managed?: ToolVersion } | null>(null);
 
-    const handleUninstall = (toolId: string, toolName: string, path: string) => {
-        setPendingUninstall({ id: toolId, name: toolName, path });
+    const handleUninstall = (toolId: string, toolName: string, path: string, managed?: ToolVersion) => {
+        setPendingUninstall({ id: toolId, name: toolName, path, managed });
     };
 
     const confirmUninstall = async () => {
         if (!pendingUninstall) return;
-        const { id, name, path } = pendingUninstall;
+        const { id, name, path, managed } = pendingUninstall;
         setPendingUninstall(null);
 
         setUninstallingTool(id);
@@ -55,8 +58,14 @@ export function ToolsView() {
         setSuccess(null);
 
         try {
-            await uninstallTool(id, path);
-            setSuccess(t('tools.success_uninstall', { name }));
+            if (managed?.manager) {
+                // Version managers remove their own installs and report what was reclaimed
+                const result = await uninstallRuntimeVersion(id, managed.manager, managed.version, projectRoot);
+                setSuccess(t('tools.success_uninstall_version', { name, size: result.bytes_freed_display }));
+            } else {
+                await uninstallTool(id, path);
+                setSuccess(t('tools.success_uninstall', { name }));
+            }
             await scanToolsData({ preserveMessages: true });
         } catch (e) {
             setError(String(e));
@@ -224,16 +233,46 @@ export function ToolsView() {
                                                                     {t('tools.active')}
                                                                 </span>
                                                             )}
+                                                            {ver.is_default && (
+                                                                <span className="badge badge-info ml-8">
+                                                                    {t('tools.default')}
+                                                                </span>
+                                                            )}
+                                                            {ver.shadowed_by && (
+                                                                <span className="badge badge-danger ml-8" title={t('tools.shadowed_by', { path: ver.shadowed_by })}>
+                                                                    {t('tools.shadowed')}
+                                                                </span>
+                                                            )}
+                                                            {ver.pinned_by && ver.pinned_by.length > 0 && (
+                                                                <span className="badge badge-warning ml-8" title={ver.pinned_by.join('\n')}>
+                                                                    {t('tools.pinned', { count: ver.pinned_by.length })}
+                                                                </span>
+                                                            )}
                                                         </td>
-                                                        <td className="path-cell clickable-path" onClick={() => copyPath(ver.path)}>
+                                                        <td className="path-cell clickable-path" onClick={() => copyPath(ver.path)} title={ver.resolved_path ?? undefined}>
                                                             {ver.path}
+                                                            {ver.resolved_path && (
+                                                                <span className="text-secondary"> → {ver.resolved_path}</span>
+                                                            )}
+                                                            {ver.shim_path && (
+                                                                <div className="text-secondary">
+                                                                    {t('tools.via_shim', { path: ver.shim_path })}
+                                                                    {ver.config_file && ` · ${t('tools.selected_by', { file: ver.config_file })}`}
+                                                                </div>
+                                                            )}
+                                                        </td>
+                                                        <td>
+                                                            {ver.manager && (
+                                                                <span className="text-secondary">
+                                                                    {ver.manager}{ver.size_display ? ` · ${ver.size_display}` : ''}
+                                                                </span>
+                                                            )}
                                                         </td>
-                                                        <td></td>
                                                         <td>
                                                             <button
                                                                 className="btn btn-secondary btn-small"
-                                                                onClick={() => handleUninstall(tool.id, `${tool.name} ${ver.version}`, ver.path)}
-                                                                disabled={uninstallingTool === tool.id}
+                                                                onClick={() => handleUninstall(tool.id, `${tool.name} ${ver.version}`, ver.path, ver.manager ? ver : undefined)}
+                                                                disabled={uninstallingTool === tool.id || ver.is_default || (ver.pinned_by?.length ?? 0) > 0}
                                                             >
                                                                 {t('tools.uninstall')}
                                                             </button>
@@ -1,9 +1,54 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
+
+export type FailureKind =
+    | 'permission_denied'
+    | 'network'
+    | 'not_found'
+    | 'externally_managed'
+    | 'lock_held'
+    | 'timeout'
+    | 'other';
+
+export interface CommandOutcome {
+    command: string;
+    exit_code: number | null;
+    stdout: string;
+    stderr: string;
+    duration_ms: number;
+    failure: FailureKind | null;
+    hint: string | null;
+}
+
+// Structured error returned by commands that fail with a DevJanitorError
+export interface BackendError {
+    kind: string;
+    message: string;
+    outcome: CommandOutcome | null;
+}
+
+export class CommandError extends Error {
+    readonly kind: string;
+    readonly outcome: CommandOutcome | null;
+
+    constructor(cmd: string, error: BackendError) {
+        super(`[${cmd}] ${error.message}`);
+        this.name = 'CommandError';
+        this.kind = error.kind;
+        this.outcome = error.outcome;
+    }
+}
+
+function isBackendError(e: unknown): e is BackendError {
+    return typeof e === 'object' && e !== null && 'kind' in e && 'message' in e;
+}
 
 async function safeInvoke<T>(cmd: string, args?: Record<string, unknown>): Promise<T> {
     try {
         return await invoke<T>(cmd, args);
     } catch (e) {
+        if (isBackendError(e)) {
+            throw new CommandError(cmd, e);
+        }
         const message = e instanceof Error ? e.message : String(e);
         throw new Error(`[${cmd}] ${message}`);
     }
@@ -14,6 +59,16 @@ export interface ToolVersion {
     version: string;
     path: string;
     is_active: boolean;
+    manager?: string | null;
+    install_path?: string | null;
+    size?: number | null;
+    size_display?: string | null;
+    is_default?: boolean;
+    pinned_by?: string[];
+    resolved_path?: string | null;
+    shadowed_by?: string | null;
+    shim_path?: string | null;
+    config_file?: string | null;
 }
 
 export interface ToolInfo {
@@ -25,8 +80,31 @@ export interface ToolInfo {
 }
 
 // Tool commands
-export async function scanTools(): Promise<ToolInfo[]> {
-    return safeInvoke<ToolInfo[]>('scan_tools');
+export async function scanTools(projectRoot?: string): Promise<ToolInfo[]> {
+    return safeInvoke<ToolInfo[]>('scan_tools', { projectRoot });
+}
+
+export interface ToolRuleSummary {
+    id: string;
+    name: string;
+    category: string;
+    source: string;
+}
+
+export interface RuleError {
+    source: string;
+    id: string | null;
+    message: string;
+}
+
+export interface ToolRuleReport {
+    rules_dir: string | null;
+    rules: ToolRuleSummary[];
+    errors: RuleError[];
+}
+
+export async function getToolRules(): Promise<ToolRuleReport> {
+    return safeInvoke<ToolRuleReport>('get_tool_rules_cmd');
 }
 
 export async function getToolInfo(toolId: string): Promise<ToolInfo | null> {
@@ -37,6 +115,41 @@ export async function uninstallTool(toolId: string, path: string): Promise<strin
     return safeInvoke<string>('uninstall_tool', { toolId, path });
 }
 
+export interface OwningPackage {
+    manager: 'apt' | 'dnf' | 'yum' | 'pacman';
+    package: string;
+    version: string | null;
+    path: string;
+    remove_command: string;
+}
+
+export async function getToolOwner(path: string): Promise<OwningPackage | null> {
+    return safeInvoke<OwningPackage | null>('get_tool_owner_cmd', { path });
+}
+
+export async function uninstallSystemPackage(path: string): Promise<CommandOutcome> {
+    return safeInvoke<CommandOutcome>('uninstall_system_package_cmd', { path });
+}
+
+export interface VersionUninstallResult {
+    tool_id: string;
+    manager: string;
+    version: string;
+    install_path: string;
+    bytes_freed: number;
+    bytes_freed_display: string;
+    outcome: CommandOutcome;
+}
+
+export async function uninstallRuntimeVersion(
+    toolId: string,
+    manager: string,
+    version: string,
+    scanRoot?: string
+): Promise<VersionUninstallResult> {
+    return safeInvoke<VersionUninstallResult>('uninstall_runtime_version_cmd', { toolId, manager, version, scanRoot });
+}
+
 // ============ Package Management ============
 
 export interface PackageInfo {
@@ -46,6 +159,15 @@ export interface PackageInfo {
     manager: string;
     is_outdated: boolean;
     description: string | null;
+    is_held: boolean;
+    hold_constraint: string | null;
+    read_only: boolean;
+    read_only_reason: string | null;
+    suggestion: string | null;
+    size: number | null;
+    size_display: string | null;
+    last_accessed: string | null;
+    install_path: string | null;
 }
 
 // Package commands
@@ -53,12 +175,227 @@ export async function scanPackages(): Promise<PackageInfo[]> {
     return safeInvoke<PackageInfo[]>('scan_packages');
 }
 
-export async function updatePackage(manager: string, name: string): Promise<string> {
-    return safeInvoke<string>('update_package', { manager, name });
+export interface UninstallReport {
+    outcome: CommandOutcome;
+    orphans: CommandOutcome[];
+    kept_held: string[];
+}
+
+export async function updatePackage(manager: string, name: string): Promise<CommandOutcome> {
+    return safeInvoke<CommandOutcome>('update_package', { manager, name });
+}
+
+export async function uninstallPackage(
+    manager: string,
+    name: string,
+    force?: boolean,
+    removeOrphans?: boolean,
+): Promise<UninstallReport> {
+    return safeInvoke<UninstallReport>('uninstall_package', { manager, name, force, removeOrphans });
+}
+
+export interface PackageHold {
+    manager: string;
+    name: string;
+    version_constraint: string | null;
+    created_at: string;
+    native: boolean;
+}
+
+export async function listPackageHolds(): Promise<PackageHold[]> {
+    return safeInvoke<PackageHold[]>('list_package_holds_cmd');
+}
+
+export async function holdPackage(manager: string, name: string, versionConstraint?: string): Promise<string> {
+    return safeInvoke<string>('hold_package_cmd', { manager, name, versionConstraint });
+}
+
+export async function releasePackageHold(manager: string, name: string): Promise<string> {
+    return safeInvoke<string>('release_package_hold_cmd', { manager, name });
+}
+
+export interface BrewKeg {
+    name: string;
+    version: string;
+    path: string;
+    size: number;
+    size_display: string;
+    is_linked: boolean;
+    installed_on_request: boolean;
+}
+
+export interface BrewFormulaInfo {
+    name: string;
+    tap: string | null;
+    description: string | null;
+    version: string;
+    latest: string | null;
+    is_outdated: boolean;
+    is_pinned: boolean;
+    is_leaf: boolean;
+    installed_on_request: boolean;
+    kegs: BrewKeg[];
+    size: number;
+    size_display: string;
+}
+
+export interface BrewCaskInfo {
+    token: string;
+    tap: string | null;
+    description: string | null;
+    version: string;
+    latest: string | null;
+    is_outdated: boolean;
+    size: number;
+    size_display: string;
+}
+
+export interface BrewCleanupCandidate {
+    path: string;
+    kind: 'keg' | 'cache';
+    size: number;
+    size_display: string;
+}
+
+export interface HomebrewInventory {
+    program: string;
+    prefix: string;
+    cache_dir: string | null;
+    formulae: BrewFormulaInfo[];
+    casks: BrewCaskInfo[];
+    taps: string[];
+    leaves: string[];
+    cleanup: BrewCleanupCandidate[];
+    total_size: number;
+    total_size_display: string;
+    reclaimable_size: number;
+    reclaimable_size_display: string;
+}
+
+export async function getHomebrewInventory(): Promise<HomebrewInventory | null> {
+    return safeInvoke<HomebrewInventory | null>('get_homebrew_inventory_cmd');
+}
+
+export interface InstalledPhp {
+    version: string;
+    path: string;
+    extensions: string[];
+}
+
+export interface PlatformRequirement {
+    package: string;
+    requirement: string;
+    constraint: string;
+    satisfied_by: string[];
+}
+
+export interface ComposerProject {
+    path: string;
+    name: string | null;
+    lock_path: string | null;
+    vendor_path: string | null;
+    vendor_size: number;
+    vendor_size_display: string;
+    package_count: number;
+    php_constraint: string | null;
+    requirements: PlatformRequirement[];
+    compatible_php: string[];
+    incompatible: boolean;
+}
+
+export interface ComposerProjectReport {
+    php_versions: InstalledPhp[];
+    projects: ComposerProject[];
+}
+
+export async function scanComposerProjects(path: string, maxDepth: number): Promise<ComposerProjectReport> {
+    return safeInvoke<ComposerProjectReport>('scan_composer_projects_cmd', { path, maxDepth });
+}
+
+export type BatchOperation = 'update' | 'uninstall';
+
+export interface BatchPackageRequest {
+    manager: string;
+    name: string;
+}
+
+export interface BatchItemResult {
+    manager: string;
+    name: string;
+    status: 'success' | 'failed' | 'skipped';
+    message: string;
+    outcome: CommandOutcome | null;
+}
+
+export interface BatchSummary {
+    operation: BatchOperation;
+    total: number;
+    succeeded: number;
+    failed: number;
+    skipped: number;
+    results: BatchItemResult[];
+}
+
+export async function batchPackageOperation(
+    operation: BatchOperation,
+    packages: BatchPackageRequest[],
+    onProgress: (result: BatchItemResult) => void,
+): Promise<BatchSummary> {
+    const channel = new Channel<BatchItemResult>();
+    channel.onmessage = onProgress;
+    return safeInvoke<BatchSummary>('batch_package_operation_cmd', { operation, packages, onProgress: channel });
+}
+
+export interface DependencyNode {
+    name: string;
+    version: string;
+    dependencies: string[];
+    required_by: string[];
+    is_user_requested: boolean;
+}
+
+export interface DependencyGraph {
+    manager: string;
+    nodes: DependencyNode[];
+}
+
+export interface PackageDependencyReport {
+    name: string;
+    manager: string;
+    is_user_requested: boolean;
+    dependencies: string[];
+    required_by: string[];
+    orphans_after_uninstall: string[];
+}
+
+export async function getDependencyGraph(manager: string): Promise<DependencyGraph> {
+    return safeInvoke<DependencyGraph>('get_dependency_graph_cmd', { manager });
 }
 
-export async function uninstallPackage(manager: string, name: string): Promise<string> {
-    return safeInvoke<string>('uninstall_package', { manager, name });
+export async function getPackageDependencies(manager: string, name: string): Promise<PackageDependencyReport> {
+    return safeInvoke<PackageDependencyReport>('get_package_dependencies_cmd', { manager, name });
+}
+
+export interface PythonEnvironment {
+    id: string;
+    name: string;
+    kind: string;
+    path: string;
+    interpreter: string | null;
+    python_version: string | null;
+    packages: PackageInfo[];
+    size: number;
+    size_display: string;
+    last_used: string | null;
+    is_base: boolean;
+}
+
+export async function scanPythonEnvs(root: string | null, maxDepth?: number): Promise<PythonEnvironment[]> {
+    return safeInvoke<PythonEnvironment[]>('scan_python_envs_cmd', { root, maxDepth });
+}
+
+export async function deletePythonEnv(path: string): Promise<string> {
+    return safeInvoke<string>('delete_python_env_cmd', { path });
 }
 
 // ============ Cache Management ============
@@ -93,6 +430,48 @@ export async function getTotalCacheSize(paths: string[]): Promise<string> {
     return safeInvoke<string>('get_total_cache_size', { paths });
 }
 
+// Container storage (Docker / Podman)
+export interface ContainerItem {
+    kind: 'image' | 'volume' | 'container' | 'build_cache';
+    id: string;
+    name: string;
+    size: number;
+    size_display: string;
+    created: string | null;
+}
+
+export interface ContainerItemRef {
+    kind: ContainerItem['kind'];
+    id: string;
+}
+
+export interface ContainerStorageReport {
+    engine: string | null;
+    error: string | null;
+    items: ContainerItem[];
+    total_size: number;
+    total_size_display: string;
+}
+
+export interface ContainerPrunePlan {
+    items: ContainerItem[];
+    skipped: ContainerItemRef[];
+    total_size: number;
+    total_size_display: string;
+}
+
+export async function scanContainerStorage(): Promise<ContainerStorageReport> {
+    return safeInvoke<ContainerStorageReport>('scan_container_storage_cmd');
+}
+
+export async function planContainerPrune(items: ContainerItemRef[]): Promise<ContainerPrunePlan> {
+    return safeInvoke<ContainerPrunePlan>('plan_container_prune_cmd', { items });
+}
+
+export async function pruneContainerItems(items: ContainerItemRef[]): Promise<Array<{ Ok?: string; Err?: string }>> {
+    return safeInvoke<Array<{ Ok?: string; Err?: string }>>('prune_container_items_cmd', { items });
+}
+
 // ============ AI Cleanup ============
 
 export interface AiJunkFile {
@@ -187,11 +566,21 @@ export interface DiagnosisIssue {
     suggestion: string | null;
 }
 
+export interface HealthCheckResult {
+    manager: string;
+    command: string;
+    exit_code: number | null;
+    timed_out: boolean;
+    duration_ms: number;
+    issue_count: number;
+}
+
 export interface EnvDiagnosis {
     path_entries: PathEntry[];
     shell_configs: ShellConfig[];
     issues: DiagnosisIssue[];
     suggestions: string[];
+    health_checks: HealthCheckResult[];
 }
 
 // Config commands
@@ -211,6 +600,87 @@ export async function getPathSuggestions(): Promise<string[]> {
     return safeInvoke<string[]>('get_path_suggestions_cmd');
 }
 
+export interface ShadowedBinary {
+    name: string;
+    package: string;
+    npm_path: string;
+    resolved_path: string;
+}
+
+export interface NpmPrefixReport {
+    prefix: string;
+    bin_dir: string;
+    global_root: string;
+    prefix_writable: boolean;
+    bin_on_path: boolean;
+    global_packages: string[];
+    shadowed: ShadowedBinary[];
+    issues: DiagnosisIssue[];
+    suggested_prefix: string | null;
+    fix_available: boolean;
+}
+
+export interface NpmPrefixFix {
+    previous_prefix: string;
+    prefix: string;
+    migrated: string[];
+    failed: CommandOutcome[];
+    shell_config: string | null;
+    path_instruction: string | null;
+    cleanup_command: string | null;
+}
+
+export async function checkNpmPrefix(): Promise<NpmPrefixReport | null> {
+    return safeInvoke<NpmPrefixReport | null>('check_npm_prefix_cmd');
+}
+
+export async function fixNpmPrefix(prefix?: string): Promise<NpmPrefixFix> {
+    return safeInvoke<NpmPrefixFix>('fix_npm_prefix_cmd', { prefix });
+}
+
+export interface ToolRequirement {
+    tool_id: string;
+    constraint: string;
+    file: string;
+    active_version: string | null;
+    installed_versions: string[];
+    satisfied: boolean | null;
+}
+
+export interface ProjectRequirementReport {
+    path: string;
+    requirements: ToolRequirement[];
+    issues: DiagnosisIssue[];
+}
+
+export async function checkProjectRequirements(path: string): Promise<ProjectRequirementReport> {
+    return safeInvoke<ProjectRequirementReport>('check_project_requirements_cmd', { path });
+}
+
+export interface JdkInstall {
+    path: string;
+    source: string;
+    vendor: string | null;
+    version: string | null;
+    arch: string | null;
+    size: number;
+    size_display: string;
+}
+
+export interface JavaHomeReport {
+    java_home: string | null;
+    java_home_version: string | null;
+    path_java: string | null;
+    path_java_home: string | null;
+    path_java_version: string | null;
+    jdks: JdkInstall[];
+    issues: DiagnosisIssue[];
+}
+
+export async function checkJavaHome(): Promise<JavaHomeReport> {
+    return safeInvoke<JavaHomeReport>('check_java_home_cmd');
+}
+
 // ============ AI CLI Tools ============
 
 export interface AiConfigFile {
@@ -248,3 +718,127 @@ export async function updateAiTool(toolId: string): Promise<string> {
 export async function uninstallAiTool(toolId: string): Promise<string> {
     return safeInvoke<string>('uninstall_ai_tool_cmd', { toolId });
 }
+
+// ============ Toolchain Manifest ============
+
+export interface ManifestDiffItem {
+    kind: 'tool' | 'package' | 'ai_tool';
+    manager: string | null;
+    name: string;
+    expected_version: string | null;
+    installed_version: string | null;
+    status: 'missing' | 'extra' | 'version_mismatch';
+}
+
+export interface ManifestDiff {
+    items: ManifestDiffItem[];
+    missing: number;
+    extra: number;
+    version_mismatch: number;
+}
+
+export interface ManifestApplyResult {
+    kind: string;
+    name: string;
+    status: 'installed' | 'failed' | 'manual';
+    message: string;
+}
+
+// Manifest commands
+export async function exportManifest(path: string): Promise<string> {
+    return safeInvoke<string>('export_manifest_cmd', { path });
+}
+
+export async function diffManifest(path: string): Promise<ManifestDiff> {
+    return safeInvoke<ManifestDiff>('diff_manifest_cmd', { path });
+}
+
+export async function applyManifest(path: string): Promise<ManifestApplyResult[]> {
+    return safeInvoke<ManifestApplyResult[]>('apply_manifest_cmd', { path });
+}
+
+// ============ Vulnerability Audit ============
+
+export type RiskLevel = 'Critical' | 'High' | 'Medium' | 'Low';
+
+export interface SecurityFinding {
+    tool_id: string;
+    tool_name: string;
+    issue: string;
+    description: string;
+    risk_level: RiskLevel;
+    remediation: string;
+    details: string;
+}
+
+export interface SecuritySummary {
+    total_findings: number;
+    critical: number;
+    high: number;
+    medium: number;
+    low: number;
+}
+
+export interface PackageAuditResult {
+    scan_time: string;
+    advisory_dir: string;
+    advisories_loaded: number;
+    skipped_files: number;
+    packages_checked: number;
+    sources: string[];
+    findings: SecurityFinding[];
+    summary: SecuritySummary;
+}
+
+// Audit commands
+export async function auditPackages(
+    advisoryDir?: string,
+    projectPath?: string
+): Promise<PackageAuditResult> {
+    return safeInvoke<PackageAuditResult>('audit_packages_cmd', { advisoryDir, projectPath });
+}
+
+export async function getAdvisoryDir(): Promise<string | null> {
+    return safeInvoke<string | null>('get_advisory_dir_cmd');
+}
+
+// ============ Editor Extensions ============
+
+export interface ExtensionVersion {
+    version: string;
+    path: string;
+    target_platform: string | null;
+    size: number;
+    size_display: string;
+    superseded: boolean;
+}
+
+export interface EditorExtension {
+    id: string;
+    display_name: string;
+    publisher: string;
+    description: string | null;
+    versions: ExtensionVersion[];
+}
+
+export interface EditorExtensions {
+    editor_id: string;
+    editor_name: string;
+    extensions_dir: string;
+    extensions: EditorExtension[];
+    total_size: number;
+    total_size_display: string;
+    superseded_size: number;
+    superseded_size_display: string;
+}
+
+export async function scanEditorExtensions(): Promise<EditorExtensions[]> {
+    return safeInvoke<EditorExtensions[]>('scan_editor_extensions_cmd');
+}
+
+export async function removeSupersededExtensions(
+    editorId: string,
+    paths: string[]
+): Promise<Array<{ Ok?: string; Err?: string }>> {
+    return safeInvoke<Array<{ Ok?: string; Err?: string }>>('remove_superseded_extensions_cmd', { editorId, paths });
+}
@@ -157,6 +157,13 @@ export interface PackageInfoStore {
     manager: string;
     is_outdated: boolean;
     description: string | null;
+    read_only?: boolean;
+    read_only_reason?: string | null;
+    suggestion?: string | null;
+    size?: number | null;
+    size_display?: string | null;
+    last_accessed?: string | null;
+    install_path?: string | null;
 }
 
 export interface ToolInfoStore {